@@ -1,20 +1,39 @@
+use super::blast_report_reader::{parse_blast_json, parse_blast_xml};
+use super::daa_reader::parse_daa;
 use super::default::{
     BLACKLIST_STITLE_REGEXS, CAPTURE_REPLACE_DESCRIPTION_PAIRS,
-    CENTER_INVERSE_INFORMATION_CONTENT_AT_QUANTILE, FILTER_REGEXS, NON_INFORMATIVE_WORDS_REGEXS,
-    POLISH_CAPTURE_REPLACE_PAIRS, SEQ_SIM_TABLE_COLUMNS, SPLIT_DESCRIPTION_REGEX,
+    CENTER_INVERSE_INFORMATION_CONTENT_AT_QUANTILE, FILTER_REGEXS,
+    INFER_SEQ_FAMILIES_MAX_QUERIES_PER_HIT, INFER_SEQ_FAMILIES_MIN_JACCARD,
+    NON_INFORMATIVE_WORDS_REGEXS, POLISH_CAPTURE_REPLACE_PAIRS, SEQ_FAMILY_ID_DIGITS,
+    SEQ_FAMILY_ID_PREFIX, SEQ_SIM_TABLE_COLUMNS, SPLIT_DESCRIPTION_REGEX,
     SPLIT_GENE_FAMILY_GENES_REGEX, SPLIT_GENE_FAMILY_ID_FROM_GENE_SET, SSSR_TABLE_FIELD_SEPARATOR,
-    UNKNOWN_FAMILY_DESCRIPTION, UNKNOWN_PROTEIN_DESCRIPTION,
+    SSST_TIER, SSST_WEIGHT, UNIPROT_EVIDENCE_WEIGHTS, UNKNOWN_FAMILY_DESCRIPTION,
+    UNKNOWN_PROTEIN_DESCRIPTION,
 };
+use super::hit::{EvidenceWeights, HitWeightingMode};
+use super::hit_thresholds::HitThresholds;
+use super::input_reader::is_stdin_path;
 use super::model_funcs::{
     apply_capture_replace_pairs, parse_regex_file, parse_regex_replace_tuple_file,
 };
 use super::query::Query;
+use super::reference_fasta::ReferenceTitles;
 use super::seq_family::SeqFamily;
-use super::seq_sim_table_reader::parse_table;
+use super::seq_family_inference::infer_seq_families;
+use super::seq_family_reader::{generate_seq_family_id, SeqFamilyFormat};
+use super::seq_sim_table_reader::{
+    parse_hmmer_table, parse_table, read_fields_comment, MalformedRowsMode, SeqSimTableFormat,
+    SkippedRows, TableOptions,
+};
+use super::taxonomy::{find_taxon_by_name, TaxonFilter};
+use super::uniprot_flat_file::UniProtEntries;
+use super::unsorted_input::{group_queries, UnsortedInputMode};
 use num_cpus;
 use rayon::prelude::*;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 
@@ -29,10 +48,51 @@ pub struct AnnotationProcess {
     pub ssst_columns: Vec<HashMap<String, usize>>,
     /// The field-separators used in the above `seq_sim_search_tables`:
     pub ssst_field_separators: Vec<char>,
+    /// The formats of the above `seq_sim_search_tables`, e.g. tabular or Blast XML:
+    pub ssst_formats: Vec<SeqSimTableFormat>,
+    /// For each sequence similarity search result table (ssst) the maximum e-value a Hit may
+    /// have, `None` meaning no threshold - note that the vector-index is used to pair input ssst
+    /// with its threshold. The same holds for the following three thresholds.
+    pub ssst_max_evalues: Vec<Option<f64>>,
+    /// For each ssst the minimum bit score a Hit must have, `None` meaning no threshold.
+    pub ssst_min_bitscores: Vec<Option<f64>>,
+    /// For each ssst the minimum percent identity a Hit must have, `None` meaning no threshold.
+    pub ssst_min_pidents: Vec<Option<f64>>,
+    /// For each ssst the minimum percent query coverage a Hit must have, `None` meaning no
+    /// threshold.
+    pub ssst_min_qcovhsps: Vec<Option<f64>>,
+    /// For each ssst the number of best Hits (by bit score) to retain per query, `None` meaning
+    /// all Hits are retained.
+    pub ssst_top_n_hits: Vec<Option<usize>>,
+    /// For each ssst the fraction of the best Hit's bit score within which Hits are retained per
+    /// query, `None` meaning all Hits are retained.
+    pub ssst_within_best_bitscores: Vec<Option<f64>>,
+    /// For each ssst the weights of the Hits' evidence, i.e. of their source database and UniProt
+    /// protein existence level, `None` meaning evidence is not weighted.
+    pub ssst_evidence_weights: Vec<Option<EvidenceWeights>>,
+    /// For each ssst the path to the reference FASTA, or to its index file built with
+    /// `reference_fasta::ReferenceIndexFile::build`, its Hit titles (`stitle`) are looked up in,
+    /// `None` meaning the table provides them in its `stitle` column.
+    pub ssst_reference_fastas: Vec<Option<String>>,
+    /// For each ssst the path to the UniProt flat file (`.dat`) whose protein names describe its
+    /// Hits, `None` meaning the Hits are described by their filtered titles (`stitle`).
+    pub ssst_uniprot_dats: Vec<Option<String>>,
+    /// Whether to look up the Hit titles in the tables' references even if the tables have a
+    /// `stitle` column, e.g. because Blast truncated them.
+    pub prefer_reference_titles: bool,
     /// For each sequence similarity search result table (ssst) the list of regular expressions
     /// used to identify to be discarded descriptions (`stitle`) - note that the vector-index is
     /// used to pair input ssst with its blacklist regexs.
     pub ssst_blacklist_regexs: Vec<Vec<Regex>>,
+    /// For each sequence similarity search result table (ssst) the weight of its Hits'
+    /// contribution to the human readable descriptions - note that the vector-index is used to
+    /// pair input ssst with its weight.
+    pub ssst_weights: Vec<f64>,
+    /// For each sequence similarity search result table (ssst) its priority tier in tiered
+    /// annotation, in which the Hits of lower tiers are used first and those of higher tiers only
+    /// if the lower tiers yield no human readable description - note that the vector-index is
+    /// used to pair input ssst with its tier. Empty, if Hits of all tables are used together.
+    pub ssst_tiers: Vec<usize>,
     /// For each sequence similarity search result table (ssst) the list of regular expressions
     /// used to delete, i.e. filter out, matching sub-strings from (`stitle`) - note that the
     /// vector-index is used to pair input ssst with its filter regexs.
@@ -55,8 +115,22 @@ pub struct AnnotationProcess {
     /// A regular expression (Rust syntax) represented as String to satisfy the `Default` trait.
     /// This regex is used to split the list of gene-identifiers in the gene families file.
     pub seq_family_gene_ids_separator: String,
-    /// An in memory index from Query identifier to SeqFamily identifier:
-    pub query_id_to_seq_family_id_index: HashMap<String, String>,
+    /// The format of the gene families file, e.g. OrthoFinder's `Orthogroups.tsv`.
+    pub seq_family_format: SeqFamilyFormat,
+    /// The prefix of the sequence family identifiers generated for raw MCL output.
+    pub seq_family_id_prefix: String,
+    /// The number of digits the index in generated sequence family identifiers is zero-padded to.
+    pub seq_family_id_digits: usize,
+    /// The distinct species of the members of each sequence family, keyed by the family's
+    /// identifier. Only families whose members' species are known, e.g. read from OrthoFinder's
+    /// `Orthogroups.tsv`, are held here.
+    pub seq_family_species: HashMap<String, Vec<String>>,
+    /// The identifiers of the parent families of subfamilies in hierarchical families, keyed by
+    /// the subfamily's identifier (see `SeqFamily::parent_id`).
+    pub seq_family_parents: HashMap<String, String>,
+    /// An in memory index from Query identifier to the identifiers of the SeqFamilies it is a
+    /// member of. Note that overlapping families, e.g. from fuzzy clustering, share members:
+    pub query_id_to_seq_family_id_index: HashMap<String, Vec<String>>,
     /// A regular expression used to split descriptions (`stitle` in Blast terminology) into words.
     pub description_split_regex: Regex,
     /// The path to the optional argument file holding regular expression, one per line, used to
@@ -67,22 +141,94 @@ pub struct AnnotationProcess {
     /// sequences or families (sets of query sequences). Stored here using the query identifier as
     /// key and the generated HRD as values.
     pub human_readable_descriptions: HashMap<String, String>,
+    /// In tiered annotation (see `ssst_tiers`) the tier whose Hits produced the human readable
+    /// description, keyed by the identifier of the annotated query or sequence family.
+    pub annotation_tiers: HashMap<String, usize>,
     /// A list of "capture-replace-pairs", tuples of regular expressions and replace strings, is
     /// held here. These pairs are used to polish assigned human readable descriptions.
     pub polish_capture_replace_pairs: Vec<(fancy_regex::Regex, String)>,
     /// A real value between zero and one used to center the inverse information content scores.
     pub center_iic_at_quantile: f64,
+    /// How to weight the Hit descriptions when generating human readable descriptions, e.g. by
+    /// their bit scores. By default all Hit descriptions count equally.
+    pub hit_weighting: HitWeightingMode,
+    /// How to read sequence similarity search result tables that might not be sorted by query
+    /// identifiers. By default they are expected to be sorted.
+    pub unsorted_input: UnsortedInputMode,
+    /// Whether to skip malformed rows in the sequence similarity search result tables with a
+    /// warning, the default, or to fail.
+    pub malformed_rows: MalformedRowsMode,
+    /// The number of malformed rows skipped in each sequence similarity search result table that
+    /// had any, keyed by the table's path.
+    pub n_malformed_rows: HashMap<String, usize>,
+    /// The number of rows skipped in each sequence similarity search result table, because their
+    /// Hit is found neither in the table's reference FASTA nor in its UniProt flat file, keyed by
+    /// the table's path. Only tables with such rows are held here.
+    pub n_missing_hits: HashMap<String, usize>,
+    /// The NCBI taxonomy identifiers of the taxa whose Hits are excluded, e.g. the query's own
+    /// species (see `taxonomy::TaxonFilter`).
+    pub excluded_taxa: Vec<u64>,
+    /// The path to a local NCBI taxonomy `nodes.dmp`. If given, all taxa below the
+    /// `excluded_taxa` are excluded, too.
+    pub taxonomy_nodes: Option<String>,
+    /// `None` if Hits of the `excluded_taxa` are discarded, the default, otherwise the factor
+    /// their evidence weight is multiplied with.
+    pub excluded_taxa_weight: Option<f64>,
+    /// The NCBI taxonomy identifier or scientific name of the queries' taxon. If given, Hits are
+    /// weighted by their phylogenetic distance to it (see `taxonomy::PhylogeneticWeights`).
+    pub query_taxon: Option<String>,
+    /// The path to a local NCBI taxonomy `names.dmp`, used to look up the `query_taxon` by its
+    /// scientific name.
+    pub taxonomy_names: Option<String>,
     /// The number of parallel threads to use.
     pub n_threads: usize,
     /// In mode FamilyAnnotation also annotate lonely queries, i.e. queries not comprised in a
     /// sequence family?
     pub annotate_lonely_queries: bool,
+    /// The minimum Jaccard similarity of two queries' Hits to link them into the same sequence
+    /// family inferred from the parsed Hits (see `seq_family_inference::infer_seq_families`),
+    /// `None` meaning families are not inferred.
+    pub infer_families_min_jaccard: Option<f64>,
+    /// The maximum number of queries a Hit is used to link into inferred sequence families. Hits
+    /// of more queries are ignored (see `seq_family_inference::infer_seq_families`).
+    pub infer_families_max_queries_per_hit: usize,
+    /// The members of the sequence families inferred from the parsed Hits, keyed by the
+    /// families' generated identifiers.
+    pub inferred_seq_families: HashMap<String, Vec<String>>,
     /// Does the user want informative messages about the annotation process printed out?
     pub verbose: bool,
     /// Exclude results that could not be annotated from the output?
     pub exclude_not_annotated_from_output: bool,
 }
 
+/// A generated human readable description, if any, and in tiered annotation the tier whose Hits
+/// produced it (see `AnnotationProcess::ssst_tiers`).
+pub type TieredHrd = (Option<String>, Option<usize>);
+
+/// A sequence similarity search result table waiting to be parsed by one of the threads of `run`,
+/// together with the settings it is parsed with (see `AnnotationProcess::queued_tables`).
+#[derive(Debug)]
+struct QueuedTable {
+    /// The path to the table
+    path: String,
+    /// The table's format, e.g. tabular or Blast XML
+    format: SeqSimTableFormat,
+    /// The separator of the columns of tabular tables
+    field_separator: char,
+    /// The column positions of tabular tables, `None` meaning they are taken from the table's
+    /// `# Fields:` comment line or the default positions are used
+    columns: Option<HashMap<String, usize>>,
+    /// The table's priority tier, `None` meaning Hits of all tables are used together
+    tier: Option<usize>,
+    /// The path to the reference FASTA, or its index file, to look up the Hit titles in, if any
+    reference_fasta: Option<String>,
+    /// The path to the UniProt flat file whose protein names describe the Hits, if any
+    uniprot_dat: Option<String>,
+    /// The settings all readers share. Note that the `reference_titles` and `uniprot_flat_file`
+    /// are read by the parsing thread.
+    options: TableOptions,
+}
+
 /// Representation of the mode an instance of AnnotationProcess runs in. Can be either (i)
 /// annotation of single biological query sequences `SequenceAnnotation`, or (ii) annotation of
 /// sets of such query sequences `FamilyAnnotation`. Annotation means the generation of human
@@ -107,6 +253,8 @@ pub fn run(mut annotation_process: AnnotationProcess) -> AnnotationProcess {
     // Are we printing information verbosely? (Note that by copying this boolean, we avoid
     // running into problems with the borrow-checker in the threads' println! statement:
     let verbose = annotation_process.verbose;
+    // Same goes for the way to read possibly unsorted input tables:
+    let unsorted_input = annotation_process.unsorted_input;
 
     // Validate input; if invalid panic! with a comprehensive error message:
     annotation_process.validate_fields();
@@ -122,153 +270,107 @@ pub fn run(mut annotation_process: AnnotationProcess) -> AnnotationProcess {
     // Setup communication between threads:
     let (tx, rx) = mpsc::channel();
 
-    // Enable the threads to access the input sequence similarity search result tables, including
-    // their index (position) in the original command line argument call:
-    let sssts_mutex = Arc::new(Mutex::new(
-        annotation_process
-            .seq_sim_search_tables
-            .iter()
-            .map(|x| x.clone())
-            .enumerate()
-            .collect::<Vec<(usize, String)>>(),
-    ));
-
-    // Enable the threads to access the input column order in each input sequence similarity search
-    // result table:
-    let ssst_cols_mutex = Arc::new(Mutex::new(annotation_process.ssst_columns.clone()));
-
-    // Enable the threads to access the input blacklist-regex-lists used to identify to be
-    // discarded Blast Hit descriptions (`stitle`) in each input sequence similarity search result
-    // table:
-    let ssst_blacklist_regexs_mutex =
-        Arc::new(Mutex::new(annotation_process.ssst_blacklist_regexs.clone()));
-
-    // Enable the threads to access the input filter-regex-lists used to identify to be deleted
-    // sub-strings in the parsed Blast Hit descriptions (`stitle`) in each input sequence
-    // similarity search result table:
-    let ssst_filter_regexs_mutex =
-        Arc::new(Mutex::new(annotation_process.ssst_filter_regexs.clone()));
-
-    // Enable the threads to access the input filter-regex-lists used to identify to be deleted
-    // sub-strings in the parsed Blast Hit descriptions (`stitle`) in each input sequence
-    // similarity search result table:
-    let ssst_capture_replace_pairs_mutex = Arc::new(Mutex::new(
-        annotation_process.ssst_capture_replace_pairs.clone(),
-    ));
-
-    // Enable the threads to access the input field separator used in the respective input
+    // Enable the threads to access the input sequence similarity search result tables, each with
+    // the settings it is parsed with:
+    let queued_tables_mutex = Arc::new(Mutex::new(annotation_process.queued_tables()));
+
+    // Enable the threads to report the number of rows they skipped in the respective input
     // sequence similarity search result tables:
-    let ssst_field_seps_mutex =
-        Arc::new(Mutex::new(annotation_process.ssst_field_separators.clone()));
+    let skipped_rows_mutex = Arc::new(Mutex::new(HashMap::new()));
 
     // Prepare `n` threads for sequence similarity search parsing, each thread will parse a table
     // not yet processed until no tables are left to be processed:
+    let mut parsers = Vec::with_capacity(n);
     for _ in 0..n {
         let tx_i = tx.clone();
 
         // Start this sss_tbl's dedicated threat -
         // ... prepare thread local variables:
-        let sssts_mutex_clone = sssts_mutex.clone();
-        let ssst_cols_mutex_clone = ssst_cols_mutex.clone();
-        let ssst_blacklist_regexs_mutex_clone = ssst_blacklist_regexs_mutex.clone();
-        let ssst_filter_regexs_mutex_clone = ssst_filter_regexs_mutex.clone();
-        let ssst_capture_replace_pairs_mutex_clone = ssst_capture_replace_pairs_mutex.clone();
-        let ssst_field_seps_mutex_clone = ssst_field_seps_mutex.clone();
+        let queued_tables_mutex_clone = queued_tables_mutex.clone();
+        let skipped_rows_mutex_clone = skipped_rows_mutex.clone();
 
         // ... start the thread:
-        thread::spawn(move || {
-            // Field-Separator in Sequence Similarity Search (Blast) Result rows (lines):
-            let mut field_separator = *SSSR_TABLE_FIELD_SEPARATOR;
-            // Sequence Similarity Search (Blast) Result column indices:
-            let mut qacc_col: usize = (*SEQ_SIM_TABLE_COLUMNS).get("qacc").unwrap().clone();
-            let mut sacc_col: usize = (*SEQ_SIM_TABLE_COLUMNS).get("sacc").unwrap().clone();
-            let mut stitle_col: usize = (*SEQ_SIM_TABLE_COLUMNS).get("stitle").unwrap().clone();
-
+        parsers.push(thread::spawn(move || {
             loop {
-                let mut ssst = sssts_mutex_clone.lock().unwrap();
+                let mut queued_tables = queued_tables_mutex_clone.lock().unwrap();
 
                 // Stop, if all input sequence similarity search tables have been parsed
                 // already:
-                if ssst.is_empty() {
+                if queued_tables.is_empty() {
                     break;
                 }
 
-                // Get the current input sequence similarity search table and its index:
-                let (i, sss_tbl) = ssst.pop().unwrap();
-                // Free the lock, so other threads may access `ssst_arc_mutex`:
-                drop(ssst);
-
-                // Did the user provide values for the column positions in the argument `sss_tbl`?
-                let ssst_columns = ssst_cols_mutex_clone.lock().unwrap();
-                if !ssst_columns.is_empty() {
-                    let ssst_cols_i = &ssst_columns[i];
-                    qacc_col = ssst_cols_i.get("qacc").unwrap().clone();
-                    sacc_col = ssst_cols_i.get("sacc").unwrap().clone();
-                    stitle_col = ssst_cols_i.get("stitle").unwrap().clone();
-                }
-                // Enable other threads to access `annotation_process.ssst_columns`:
-                drop(ssst_columns);
-
-                // Did the user provide values for the blacklist regex list to be used to identify
-                // to be discarded Blast Hit descriptions (`stitle`) in the argument `sss_tbl`?
-                let ssst_blacklist_regexs = ssst_blacklist_regexs_mutex_clone.lock().unwrap();
-                let mut blacklist_regexs_i = (*BLACKLIST_STITLE_REGEXS).clone();
-                if !ssst_blacklist_regexs.is_empty() {
-                    blacklist_regexs_i = ssst_blacklist_regexs[i].clone();
-                }
-                // Enable other threads to access `annotation_process.ssst_blacklist_regexs`:
-                drop(ssst_blacklist_regexs);
-
-                // Did the user provide values for the filter regex list to be used to identify to
-                // be deleted sub-strings in the Blast Hit descriptions (`stitle`) in the argument
-                // `sss_tbl`?
-                let ssst_filter_regexs = ssst_filter_regexs_mutex_clone.lock().unwrap();
-                let mut filter_regexs_i = (*FILTER_REGEXS).clone();
-                if !ssst_filter_regexs.is_empty() {
-                    filter_regexs_i = ssst_filter_regexs[i].clone();
-                }
-                // Enable other threads to access `annotation_process.ssst_filter_regexs`:
-                drop(ssst_filter_regexs);
-
-                // Did the user provide values for the capture-replace-pairs to be used to identify
-                // to be transformed sub-strings in the Blast Hit descriptions (`stitle`) in the
-                // argument `sss_tbl`?
-                let ssst_capture_replace_pairs =
-                    ssst_capture_replace_pairs_mutex_clone.lock().unwrap();
-                let mut capture_replace_pairs_i = (*CAPTURE_REPLACE_DESCRIPTION_PAIRS).clone();
-                if !ssst_capture_replace_pairs.is_empty() {
-                    capture_replace_pairs_i = ssst_capture_replace_pairs[i].clone();
-                }
-                // Enable other threads to access `annotation_process.ssst_capture_replace_pairs`:
-                drop(ssst_capture_replace_pairs);
-
-                // Did the user provide a custom field-separator for the argument `sss_tbl`?
-                let ssst_field_separators = ssst_field_seps_mutex_clone.lock().unwrap();
-                if !ssst_field_separators.is_empty() {
-                    field_separator = ssst_field_separators[i];
+                // Get the current input sequence similarity search table and its settings:
+                let QueuedTable {
+                    path: sss_tbl,
+                    format,
+                    field_separator,
+                    columns,
+                    tier,
+                    reference_fasta,
+                    uniprot_dat,
+                    mut options,
+                } = queued_tables.pop().unwrap();
+                // Free the lock, so other threads may access the queued tables:
+                drop(queued_tables);
+
+                // Did the user provide a reference FASTA, or its index file, to look up the Hit
+                // titles of the argument `sss_tbl` in? If so, index its headers or map the index.
+                // Did the user provide a UniProt flat file, or its index file, whose protein names
+                // describe the Hits? If so, read its entries or map the index. Note that this happens here, in parallel:
+                options.reference_titles = reference_fasta.as_deref().map(ReferenceTitles::open);
+                options.uniprot_flat_file = uniprot_dat.as_deref().map(UniProtEntries::open);
+
+                // Parse the table, grouping its queries first if it might not be sorted by query
+                // identifiers, and tag its Hits with its tier. Because we are in a `loop` we need
+                // to clone the cloned sender:
+                let skipped_rows_i = send_with_tier(tier, tx_i.clone(), |tx| {
+                    group_queries(&unsorted_input, &options.hit_thresholds, tx, |tx| {
+                        match format {
+                            SeqSimTableFormat::Tabular => parse_table(
+                                &sss_tbl,
+                                &field_separator,
+                                columns.as_ref(),
+                                &options,
+                                tx,
+                            ),
+                            // Blast reports are not parsed row by row, i.e. have no malformed rows:
+                            SeqSimTableFormat::BlastXml => {
+                                parse_blast_xml(&sss_tbl, &options, tx);
+                                SkippedRows::default()
+                            }
+                            SeqSimTableFormat::BlastJson => {
+                                parse_blast_json(&sss_tbl, &options, tx);
+                                SkippedRows::default()
+                            }
+                            // Neither are Diamond DAA files:
+                            SeqSimTableFormat::DiamondDaa => {
+                                parse_daa(&sss_tbl, &options, tx);
+                                SkippedRows::default()
+                            }
+                            // HMMER tables have no reference to look up Hits in:
+                            SeqSimTableFormat::HmmerTblout | SeqSimTableFormat::HmmerDomtblout => {
+                                SkippedRows {
+                                    n_malformed: parse_hmmer_table(&sss_tbl, &format, &options, tx),
+                                    n_missing_hits: 0,
+                                }
+                            }
+                        }
+                    })
+                });
+                if skipped_rows_i != SkippedRows::default() {
+                    skipped_rows_mutex_clone
+                        .lock()
+                        .unwrap()
+                        .insert(sss_tbl.clone(), skipped_rows_i);
                 }
-                // Enable other threads to access `annotation_process.ssst_field_separators`:
-                drop(ssst_field_separators);
-
-                parse_table(
-                    &sss_tbl,
-                    &field_separator,
-                    &qacc_col,
-                    &sacc_col,
-                    &stitle_col,
-                    &blacklist_regexs_i,
-                    &filter_regexs_i,
-                    Some(&capture_replace_pairs_i),
-                    // Because we are in a `loop` we need to clone the cloned sender:
-                    tx_i.clone(),
-                );
 
                 // Inform user, if requested:
                 if verbose {
                     println!("Finished parsing {:?}", &sss_tbl);
                 }
             }
-        });
+        }));
     }
     // Because of the above for loop tx needs to be cloned into tx_i's. tx needs to be dropped,
     // otherwise the below receiver loop will wait forever for tx to send some messages.
@@ -280,6 +382,53 @@ pub fn run(mut annotation_process: AnnotationProcess) -> AnnotationProcess {
         annotation_process.insert_query(qacc, query);
     }
 
+    // Fail, if any thread failed to parse its table, e.g. because of a malformed row in mode
+    // `MalformedRowsMode::Strict`. The panic is passed on unchanged. Note that the above loop
+    // ends once all threads have finished, because all their transmitters have been dropped:
+    for parser in parsers {
+        parser
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    }
+
+    // Summarize the rows skipped in the input tables, malformed ones and those whose Hits are
+    // missing in the tables' references. Note that all threads have finished parsing:
+    let skipped_rows = skipped_rows_mutex.lock().unwrap();
+    let mut skipped_tables: Vec<(&String, &SkippedRows)> = skipped_rows.iter().collect();
+    skipped_tables.sort_by_key(|(sss_tbl, _)| *sss_tbl);
+    for (sss_tbl, skipped_rows_i) in skipped_tables {
+        if skipped_rows_i.n_malformed > 0 {
+            eprintln!(
+                "Skipped {} malformed row(s) in {:?}",
+                skipped_rows_i.n_malformed, sss_tbl
+            );
+            annotation_process
+                .n_malformed_rows
+                .insert(sss_tbl.clone(), skipped_rows_i.n_malformed);
+        }
+        if skipped_rows_i.n_missing_hits > 0 {
+            eprintln!(
+                "Skipped {} row(s) in {:?}, because their Hits are not found in the --reference-fasta or --uniprot-dat",
+                skipped_rows_i.n_missing_hits, sss_tbl
+            );
+            annotation_process
+                .n_missing_hits
+                .insert(sss_tbl.clone(), skipped_rows_i.n_missing_hits);
+        }
+    }
+    drop(skipped_rows);
+
+    // Group the parsed queries into sequence families by the Hits they share, if requested:
+    if annotation_process.infer_families_min_jaccard.is_some() {
+        annotation_process.infer_seq_families();
+        if verbose {
+            println!(
+                "Inferred {:?} sequence families from shared Hits",
+                annotation_process.inferred_seq_families.len()
+            );
+        }
+    }
+
     // Make sure all queries or sequence families are annotated:
     annotation_process.process_rest_data();
 
@@ -292,6 +441,45 @@ pub fn run(mut annotation_process: AnnotationProcess) -> AnnotationProcess {
     annotation_process
 }
 
+/// Runs the argument `parse` function and passes the queries it sends on to the argument
+/// `transmitter`, after setting the priority tier of all their Hits to the argument `tier` (see
+/// `Hit::tier`). If `tier` is `None`, i.e. not annotating in tiers, the queries are sent directly.
+/// Returns what `parse` returns.
+///
+/// # Arguments
+///
+/// * `tier: Option<usize>` - The priority tier of the parsed table, if any
+/// * `transmitter: Sender<(String, Query)>` - Used to send the tagged queries to any receiver
+/// * `parse: F` - Parses the table, sending its queries with the sender it is given
+fn send_with_tier<F, R>(
+    tier: Option<usize>,
+    transmitter: mpsc::Sender<(String, Query)>,
+    parse: F,
+) -> R
+where
+    F: FnOnce(mpsc::Sender<(String, Query)>) -> R + Send,
+    R: Send,
+{
+    match tier {
+        None => parse(transmitter),
+        Some(tier) => {
+            let (tx, rx) = mpsc::channel();
+            thread::scope(|s| {
+                let parser = s.spawn(move || parse(tx));
+                for (qacc, mut query) in rx {
+                    for hit in query.hits.values_mut() {
+                        hit.tier = tier;
+                    }
+                    transmitter.send((qacc, query)).unwrap();
+                }
+                parser
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+        }
+    }
+}
+
 impl AnnotationProcess {
     /// Creates a default instance of struct AnnotationProcess and returns it.
     pub fn new() -> AnnotationProcess {
@@ -304,21 +492,53 @@ impl AnnotationProcess {
             seq_sim_search_tables: vec![],
             ssst_columns: vec![],
             ssst_blacklist_regexs: vec![],
+            ssst_weights: vec![],
+            ssst_tiers: vec![],
             ssst_filter_regexs: vec![],
             ssst_capture_replace_pairs: vec![],
             ssst_field_separators: vec![],
+            ssst_formats: vec![],
+            ssst_max_evalues: vec![],
+            ssst_min_bitscores: vec![],
+            ssst_min_pidents: vec![],
+            ssst_min_qcovhsps: vec![],
+            ssst_top_n_hits: vec![],
+            ssst_within_best_bitscores: vec![],
+            ssst_evidence_weights: vec![],
+            ssst_reference_fastas: vec![],
+            ssst_uniprot_dats: vec![],
+            prefer_reference_titles: false,
             queries: HashMap::new(),
             seq_families: HashMap::new(),
             seq_family_id_genes_separator: (*SPLIT_GENE_FAMILY_ID_FROM_GENE_SET).to_string(),
             seq_family_gene_ids_separator: (*SPLIT_GENE_FAMILY_GENES_REGEX).to_string(),
+            seq_family_format: SeqFamilyFormat::Default,
+            seq_family_id_prefix: (*SEQ_FAMILY_ID_PREFIX).to_string(),
+            seq_family_id_digits: *SEQ_FAMILY_ID_DIGITS,
+            seq_family_species: HashMap::new(),
+            seq_family_parents: HashMap::new(),
             description_split_regex: (*SPLIT_DESCRIPTION_REGEX).clone(),
             non_informative_words_regexs: (*NON_INFORMATIVE_WORDS_REGEXS).clone(),
             query_id_to_seq_family_id_index: HashMap::new(),
             human_readable_descriptions: HashMap::new(),
+            annotation_tiers: HashMap::new(),
             polish_capture_replace_pairs: (*POLISH_CAPTURE_REPLACE_PAIRS).clone(),
             center_iic_at_quantile: *CENTER_INVERSE_INFORMATION_CONTENT_AT_QUANTILE,
+            hit_weighting: HitWeightingMode::None,
+            unsorted_input: UnsortedInputMode::Sorted,
+            malformed_rows: MalformedRowsMode::Skip,
+            n_malformed_rows: HashMap::new(),
+            n_missing_hits: HashMap::new(),
+            excluded_taxa: vec![],
+            taxonomy_nodes: None,
+            excluded_taxa_weight: None,
+            query_taxon: None,
+            taxonomy_names: None,
             n_threads: nt,
             annotate_lonely_queries: false,
+            infer_families_min_jaccard: None,
+            infer_families_max_queries_per_hit: *INFER_SEQ_FAMILIES_MAX_QUERIES_PER_HIT,
+            inferred_seq_families: HashMap::new(),
             verbose: false,
             exclude_not_annotated_from_output: false,
         }
@@ -342,19 +562,22 @@ impl AnnotationProcess {
         // panic! if query.id already in results, this means the input SSSR files were not sorted
         // by query identifiers (`qacc` in Blast terminology):
         if self.human_readable_descriptions.contains_key(&qacc) {
-            panic!( "\n\nFound an unexpected occurrence of query {:?} while parsing input files. Make sure your sequence similarity search result tables are sorted by query identifiers, i.e. `qacc` in Blast terminology. Use GNU sort, e.g. `sort -k <qacc-col-no> <your-blast-out-table>`.\n\n", &qacc);
+            panic!( "\n\nFound an unexpected occurrence of query {:?} while parsing input files. Make sure your sequence similarity search result tables are sorted by query identifiers, i.e. `qacc` in Blast terminology. Use GNU sort, e.g. `sort -k <qacc-col-no> <your-blast-out-table>`, or use the option --unsorted-input (see --help).\n\n", &qacc);
         }
         if !self.queries.contains_key(&qacc) {
             self.queries.insert(qacc.clone(), query);
         } else {
             let already_parsed_query = self.queries.get_mut(&qacc).unwrap();
-            already_parsed_query.hits.extend(query.hits.clone());
+            already_parsed_query.merge_hits(query.hits);
         }
 
         let stored_query = self.queries.get_mut(&qacc).unwrap();
         stored_query.n_parsed_from_sssr_tables += 1;
-        // Have all input SSSR files provided data for the argument `query`?
-        if stored_query.n_parsed_from_sssr_tables == self.seq_sim_search_tables.len() as u16 {
+        // Have all input SSSR files provided data for the argument `query`? Note that queries are
+        // not processed before sequence families have been inferred from all of them:
+        if stored_query.n_parsed_from_sssr_tables == self.seq_sim_search_tables.len() as u16
+            && self.infer_families_min_jaccard.is_none()
+        {
             drop(stored_query);
             // If yes, then process the parsed data:
             self.process_query_data_complete(qacc);
@@ -363,8 +586,14 @@ impl AnnotationProcess {
 
     /// Inserts the argument `seq_family: SeqFamily` into this AnnotationProcess instance's
     /// `seq_families`, while also updating the in memory index of biological query sequence
-    /// identifiers pointing to their respective sequence family (see
-    /// `query_id_to_seq_family_id_index`).
+    /// identifiers pointing to their respective sequence families (see
+    /// `query_id_to_seq_family_id_index`). A sequence may be a member of several families. If a
+    /// family of the same identifier has already been inserted, e.g. as parent of a subfamily, the
+    /// argument `seq_family`'s members are added to it. In hierarchical families, the members of a
+    /// subfamily are added to its parent family (see `SeqFamily::parent_id`) and all further
+    /// ancestors, which are created, if not inserted yet. The parents are recorded in
+    /// `seq_family_parents`. The distinct species of the family's members are recorded in
+    /// `seq_family_species`, if known. Panics, if a family is found to be its own ancestor.
     ///
     /// # Arguments
     ///
@@ -372,17 +601,115 @@ impl AnnotationProcess {
     ///                 serves as an in memory database into which to insert the argument
     ///                 biological sequence family.
     pub fn insert_seq_family(&mut self, seq_family_id: String, seq_family: SeqFamily) {
-        for query_id in &seq_family.query_ids {
-            if self.query_id_to_seq_family_id_index.contains_key(query_id) {
-                let other_family_id = self.query_id_to_seq_family_id_index.get(query_id).unwrap();
-                if *other_family_id != seq_family_id {
-                    panic!("\n\nBiological sequence {:?} already set as member of family {:?}. But found {:?} again declared as member of another family {:?}.\nMake sure each biological sequence appears in one and only one family to avoid this problem.\n\n", query_id, other_family_id, query_id, seq_family_id);
+        if let Some(parent_id) = &seq_family.parent_id {
+            self.seq_family_parents
+                .insert(seq_family_id.clone(), parent_id.clone());
+        }
+        self.merge_seq_family_members(&seq_family_id, &seq_family);
+        // The members of a subfamily, including those of its own subfamilies inserted before,
+        // are members of all its ancestors, too:
+        let members = self.seq_families.get(&seq_family_id).unwrap().clone();
+        let mut ancestor_ids = vec![seq_family_id.clone()];
+        while let Some(ancestor_id) = self
+            .seq_family_parents
+            .get(ancestor_ids.last().unwrap())
+            .cloned()
+        {
+            if ancestor_ids.contains(&ancestor_id) {
+                panic!("\n\nSequence family {:?} is found to be its own ancestor via {:?}. Make sure the hierarchy of sequence families has no cycles.\n\n", ancestor_id, ancestor_ids);
+            }
+            self.merge_seq_family_members(&ancestor_id, &members);
+            ancestor_ids.push(ancestor_id);
+        }
+    }
+
+    /// Adds the members (`query_ids`) of the argument `members` family, and their species, to the
+    /// sequence family of the argument `seq_family_id`, unless already members. The family is
+    /// created, if it does not exist yet, and the parent of the argument `members` is set, if
+    /// they are the family itself. Updates `query_id_to_seq_family_id_index` and
+    /// `seq_family_species` accordingly.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A mutable reference to the current instance of AnnotationProcess
+    /// * `seq_family_id: &String` - The identifier of the family to add the members to
+    /// * `members: &SeqFamily` - The family whose members to add
+    fn merge_seq_family_members(&mut self, seq_family_id: &String, members: &SeqFamily) {
+        let seq_family = self.seq_families.entry(seq_family_id.clone()).or_default();
+        let mut known_query_ids: HashSet<String> = seq_family.query_ids.iter().cloned().collect();
+        for (indx, query_id) in members.query_ids.iter().enumerate() {
+            if known_query_ids.insert(query_id.clone()) {
+                // Species are only retained, if known for all members:
+                if seq_family.species.len() == seq_family.query_ids.len() {
+                    if let Some(species) = members.species.get(indx) {
+                        seq_family.species.push(species.clone());
+                    }
                 }
+                seq_family.query_ids.push(query_id.clone());
+            }
+            let seq_family_ids = self
+                .query_id_to_seq_family_id_index
+                .entry(query_id.clone())
+                .or_default();
+            if !seq_family_ids.contains(seq_family_id) {
+                seq_family_ids.push(seq_family_id.clone());
+            }
+        }
+        if seq_family.parent_id.is_none() {
+            seq_family.parent_id = self.seq_family_parents.get(seq_family_id).cloned();
+        }
+        if !seq_family.species.is_empty() {
+            self.seq_family_species
+                .insert(seq_family_id.clone(), seq_family.distinct_species());
+        }
+    }
+
+    /// Groups the parsed queries into sequence families by the Hits they share (see
+    /// `seq_family_inference::infer_seq_families`) and inserts these families, so they are
+    /// annotated in `AnnotationProcessMode::FamilyAnnotation`. Family identifiers are generated
+    /// from `seq_family_id_prefix` and the index of the family (see
+    /// `seq_family_reader::generate_seq_family_id`). The families' members are recorded in
+    /// `inferred_seq_families`. Queries not grouped into any family are annotated right away, if
+    /// `annotate_lonely_queries` is set, and discarded otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A mutable reference to the current instance of AnnotationProcess
+    pub fn infer_seq_families(&mut self) {
+        let min_jaccard = self
+            .infer_families_min_jaccard
+            .unwrap_or(*INFER_SEQ_FAMILIES_MIN_JACCARD);
+        for (indx, query_ids) in infer_seq_families(
+            &self.queries,
+            min_jaccard,
+            self.infer_families_max_queries_per_hit,
+        )
+        .into_iter()
+        .enumerate()
+        {
+            let seq_family_id = generate_seq_family_id(
+                &self.seq_family_id_prefix,
+                indx + 1,
+                self.seq_family_id_digits,
+            );
+            let mut seq_family = SeqFamily::new();
+            seq_family.query_ids = query_ids.clone();
+            self.insert_seq_family(seq_family_id.clone(), seq_family);
+            self.inferred_seq_families.insert(seq_family_id, query_ids);
+        }
+        let lonely_query_ids: Vec<String> = self
+            .queries
+            .keys()
+            .filter(|query_id| !self.query_id_to_seq_family_id_index.contains_key(*query_id))
+            .cloned()
+            .collect();
+        for query_id in lonely_query_ids {
+            if self.annotate_lonely_queries {
+                self.annotate_query(query_id);
+            } else {
+                self.queries.remove(&query_id);
             }
-            self.query_id_to_seq_family_id_index
-                .insert((*query_id).clone(), seq_family_id.clone());
         }
-        self.seq_families.insert(seq_family_id, seq_family);
     }
 
     /// Informs and returns the mode an AnnotationProcess (argument `&self`) is running in, can be
@@ -413,11 +740,10 @@ impl AnnotationProcess {
     /// * `query_id: String` - An instance of `String` representing the query identifier
     pub fn annotate_query(&mut self, query_id: String) {
         // Generate the desired result, i.e. a human readable description for the Query:
-        let hrd = self.queries.get(&query_id).unwrap().annotate(
-            &self.description_split_regex,
-            &self.non_informative_words_regexs,
-            &self.center_iic_at_quantile,
-        );
+        let (hrd, tier) = self.generate_query_hrd(&query_id);
+        if let Some(tier) = tier {
+            self.annotation_tiers.insert(query_id.clone(), tier);
+        }
         // Add the new result to the in memory database, i.e.
         // `self.human_readable_descriptions`:
         match hrd {
@@ -452,13 +778,11 @@ impl AnnotationProcess {
     ///                              family's (`SeqFamily`) identifier.
     pub fn annotate_seq_family(&mut self, seq_family_id: &String) {
         // Generate the desired result, i.e. a human readable description for the SeqFamily:
+        let (hrd, tier) = self.generate_seq_family_hrd(seq_family_id);
+        if let Some(tier) = tier {
+            self.annotation_tiers.insert((*seq_family_id).clone(), tier);
+        }
         let seq_family = self.seq_families.get(seq_family_id).unwrap();
-        let hrd = seq_family.annotate(
-            &self.queries,
-            &self.description_split_regex,
-            &self.non_informative_words_regexs,
-            &self.center_iic_at_quantile,
-        );
         // Add the new result to the in memory database, i.e.
         // `self.human_readable_descriptions`:
         match hrd {
@@ -481,11 +805,105 @@ impl AnnotationProcess {
         }
         // need to clone, otherwise had problems with the compiler (E0599):
         let query_ids = seq_family.query_ids.clone();
-        // Free memory by removing the parsed input data, no longer required:
+        self.seq_families.remove(seq_family_id);
+        // Free memory by removing the parsed input data, no longer required. Queries that are
+        // members of other families, not yet annotated, are retained for these:
         for query_id in query_ids.iter() {
-            self.queries.remove(query_id);
-            self.query_id_to_seq_family_id_index.remove(query_id);
-            self.seq_families.remove(seq_family_id);
+            if let Some(seq_family_ids) = self.query_id_to_seq_family_id_index.get_mut(query_id) {
+                seq_family_ids.retain(|fam_id| fam_id != seq_family_id);
+                if seq_family_ids.is_empty() {
+                    self.query_id_to_seq_family_id_index.remove(query_id);
+                    self.queries.remove(query_id);
+                }
+            }
+        }
+    }
+
+    /// Returns the priority tiers of the input tables in the order their Hits are used to annotate
+    /// queries or sequence families, or `None` if the Hits of all tables are used together (see
+    /// `ssst_tiers`).
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of AnnotationProcess
+    pub fn tiers(&self) -> Option<Vec<usize>> {
+        if self.ssst_tiers.is_empty() {
+            None
+        } else {
+            let mut tiers = self.ssst_tiers.clone();
+            tiers.sort_unstable();
+            tiers.dedup();
+            Some(tiers)
+        }
+    }
+
+    /// Generates a human readable description (HRD) for the argument `query_id` and returns it
+    /// together with the tier whose Hits produced it. In tiered annotation (see `ssst_tiers`) the
+    /// Hits of each tier are used in turn, until one yields a HRD. Otherwise all Hits are used
+    /// together and no tier is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of AnnotationProcess
+    /// * `query_id: &String` - The identifier of the query to annotate
+    pub fn generate_query_hrd(&self, query_id: &String) -> TieredHrd {
+        let query = self.queries.get(query_id).unwrap();
+        let annotate = |query: &Query| {
+            query.annotate(
+                &self.description_split_regex,
+                &self.non_informative_words_regexs,
+                &self.center_iic_at_quantile,
+                &self.hit_weighting,
+            )
+        };
+        match self.tiers() {
+            None => (annotate(query), None),
+            Some(tiers) => tiers
+                .into_iter()
+                .find_map(|tier| {
+                    annotate(&query.hits_of_tier(tier)).map(|hrd| (Some(hrd), Some(tier)))
+                })
+                .unwrap_or((None, None)),
+        }
+    }
+
+    /// Generates a human readable description (HRD) for the argument `seq_family_id` and returns
+    /// it together with the tier whose Hits produced it. In tiered annotation (see `ssst_tiers`)
+    /// the Hits of each tier, found for any of the family's queries, are used in turn, until one
+    /// yields a HRD. Otherwise all Hits are used together and no tier is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of AnnotationProcess
+    /// * `seq_family_id: &String` - The identifier of the sequence family to annotate
+    pub fn generate_seq_family_hrd(&self, seq_family_id: &String) -> TieredHrd {
+        let seq_family = self.seq_families.get(seq_family_id).unwrap();
+        let annotate = |queries: &HashMap<String, Query>| {
+            seq_family.annotate(
+                queries,
+                &self.description_split_regex,
+                &self.non_informative_words_regexs,
+                &self.center_iic_at_quantile,
+                &self.hit_weighting,
+            )
+        };
+        match self.tiers() {
+            None => (annotate(&self.queries), None),
+            Some(tiers) => tiers
+                .into_iter()
+                .find_map(|tier| {
+                    let queries_of_tier: HashMap<String, Query> = seq_family
+                        .query_ids
+                        .iter()
+                        .filter_map(|qid| {
+                            self.queries
+                                .get(qid)
+                                .map(|query| (qid.clone(), query.hits_of_tier(tier)))
+                        })
+                        .collect();
+                    annotate(&queries_of_tier).map(|hrd| (Some(hrd), Some(tier)))
+                })
+                .unwrap_or((None, None)),
         }
     }
 
@@ -511,21 +929,24 @@ impl AnnotationProcess {
             }
             // Handle annotation of sets of biological sequences, so called "Gene Families":
             AnnotationProcessMode::FamilyAnnotation => {
-                // Get SeqFamily for query sequence identifier (`query_id`):
+                // Get the SeqFamilies of query sequence identifier (`query_id`):
                 if self.query_id_to_seq_family_id_index.contains_key(&query_id) {
-                    let seq_fam_id = self
+                    // need to clone, because annotating a family updates the index:
+                    let seq_fam_ids = self
                         .query_id_to_seq_family_id_index
                         .get(&query_id)
                         .unwrap()
                         .clone();
-                    if self.seq_families.contains_key(&seq_fam_id) {
-                        // Tell Family that parsing of Blast results for argument `query_id` has
-                        // been completed:
-                        let seq_fam = self.seq_families.get_mut(&seq_fam_id).unwrap();
-                        seq_fam.mark_query_id_with_complete_data(&query_id);
-                        // Ask SeqFamily if all queries have complete data:
-                        if seq_fam.all_query_data_complete() {
-                            self.annotate_seq_family(&seq_fam_id);
+                    for seq_fam_id in seq_fam_ids {
+                        if self.seq_families.contains_key(&seq_fam_id) {
+                            // Tell Family that parsing of Blast results for argument `query_id`
+                            // has been completed:
+                            let seq_fam = self.seq_families.get_mut(&seq_fam_id).unwrap();
+                            seq_fam.mark_query_id_with_complete_data(&query_id);
+                            // Ask SeqFamily if all queries have complete data:
+                            if seq_fam.all_query_data_complete() {
+                                self.annotate_seq_family(&seq_fam_id);
+                            }
                         }
                     }
                 } else if self.annotate_lonely_queries {
@@ -552,52 +973,37 @@ impl AnnotationProcess {
         // Mutex. Thus results are collected in terms of tuples containing the annotee identifier
         // and the generated human readable description.
         let mode = self.mode();
-        let hrd_tuples: Vec<(String, Option<String>)>;
-        match mode {
+        let hrd_tuples: Vec<(String, TieredHrd)> = match mode {
             // Handle annotation of single biological sequences:
             AnnotationProcessMode::SequenceAnnotation => {
                 // Process queries that might have gotten parsed results only from a subset of the input
                 // sequence similarity search result (SSSR) files:
-                hrd_tuples = self
-                    .queries
+                self.queries
                     .keys()
                     .cloned()
                     .collect::<Vec<String>>()
                     .par_iter()
-                    .map(|query_id| {
-                        let query = self.queries.get(query_id).unwrap();
-                        let hrd = query.annotate(
-                            &self.description_split_regex,
-                            &self.non_informative_words_regexs,
-                            &self.center_iic_at_quantile,
-                        );
-                        ((*query_id).to_string(), hrd)
-                    })
-                    .collect();
+                    .map(|query_id| ((*query_id).to_string(), self.generate_query_hrd(query_id)))
+                    .collect()
             }
             // Handle annotation of sets of biological sequences, so called "Gene Families":
             AnnotationProcessMode::FamilyAnnotation => {
                 // Process seq families that might have queries that got no blast hits in some
                 // input blast tables:
-                hrd_tuples = self
-                    .seq_families
+                self.seq_families
                     .keys()
                     .cloned()
                     .collect::<Vec<String>>()
                     .par_iter()
                     .map(|seq_fam_id| {
-                        let seq_fam = self.seq_families.get(seq_fam_id).unwrap();
-                        let hrd = seq_fam.annotate(
-                            &self.queries,
-                            &self.description_split_regex,
-                            &self.non_informative_words_regexs,
-                            &self.center_iic_at_quantile,
-                        );
-                        ((*seq_fam_id).to_string(), hrd)
+                        (
+                            (*seq_fam_id).to_string(),
+                            self.generate_seq_family_hrd(seq_fam_id),
+                        )
                     })
-                    .collect();
+                    .collect()
             }
-        }
+        };
 
         // Free memory:
         self.queries = Default::default();
@@ -605,10 +1011,13 @@ impl AnnotationProcess {
         self.query_id_to_seq_family_id_index = Default::default();
 
         // Set the human readable descriptions generated in parallel:
-        for i_tpl in hrd_tuples {
-            match i_tpl.1 {
+        for (annotee_id, (hrd, tier)) in hrd_tuples {
+            if let Some(tier) = tier {
+                self.annotation_tiers.insert(annotee_id.clone(), tier);
+            }
+            match hrd {
                 Some(hrd_str) => {
-                    self.human_readable_descriptions.insert(i_tpl.0, hrd_str);
+                    self.human_readable_descriptions.insert(annotee_id, hrd_str);
                 }
                 None => {
                     // In case the user wants some default 'unknown protein' or 'unknown sequence
@@ -619,11 +1028,11 @@ impl AnnotationProcess {
                         match mode {
                             AnnotationProcessMode::SequenceAnnotation => {
                                 self.human_readable_descriptions
-                                    .insert(i_tpl.0, (*UNKNOWN_PROTEIN_DESCRIPTION).to_string());
+                                    .insert(annotee_id, (*UNKNOWN_PROTEIN_DESCRIPTION).to_string());
                             }
                             AnnotationProcessMode::FamilyAnnotation => {
                                 self.human_readable_descriptions
-                                    .insert(i_tpl.0, (*UNKNOWN_FAMILY_DESCRIPTION).to_string());
+                                    .insert(annotee_id, (*UNKNOWN_FAMILY_DESCRIPTION).to_string());
                             }
                         }
                     }
@@ -695,6 +1104,59 @@ impl AnnotationProcess {
         self.ssst_blacklist_regexs.push(blacklist_regexs);
     }
 
+    /// Parses one (of potentially many) command line argument `table-weight` into the weight of
+    /// the Hits of the respective sequence similarity search result table (SSST), which scales
+    /// their contribution to the human readable descriptions (see `hit::EvidenceWeights::table`).
+    /// Inserts the parsed weight into `self.ssst_weights`, and uses the `default::SSST_WEIGHT` if
+    /// the argument `weight_arg` equals `"default"` (case insensitive). Panics, if the weight is not
+    /// a positive number.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `weight_arg: &str` - The passed command line argument
+    pub fn add_ssst_weight(&mut self, weight_arg: &str) {
+        let weight: f64 = if weight_arg.trim().to_lowercase() == "default" {
+            *SSST_WEIGHT
+        } else {
+            weight_arg
+                .trim()
+                .parse()
+                .ok()
+                .filter(|w: &f64| *w > 0.0)
+                .unwrap_or_else(|| {
+                    panic!("\n\nInvalid --table-weight {:?}. Please use a positive number or 'default'.\n\n", weight_arg)
+                })
+        };
+        self.ssst_weights.push(weight);
+    }
+
+    /// Parses one (of potentially many) command line argument `table-tier` into the priority tier
+    /// of the respective sequence similarity search result table (SSST) in tiered annotation (see
+    /// `ssst_tiers`). Inserts the parsed tier into `self.ssst_tiers`, and uses the
+    /// `default::SSST_TIER` if the argument `tier_arg` equals `"default"` (case insensitive).
+    /// Panics, if the tier is not a positive integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `tier_arg: &str` - The passed command line argument
+    pub fn add_ssst_tier(&mut self, tier_arg: &str) {
+        let tier: usize = if tier_arg.trim().to_lowercase() == "default" {
+            *SSST_TIER
+        } else {
+            tier_arg
+                .trim()
+                .parse()
+                .ok()
+                .filter(|t: &usize| *t > 0)
+                .unwrap_or_else(|| {
+                    panic!("\n\nInvalid --table-tier {:?}. Please use a positive integer or 'default'.\n\n", tier_arg)
+                })
+        };
+        self.ssst_tiers.push(tier);
+    }
+
     /// Parses one (of potentially many) command line argument `filter-regexs` into a `Vec<Regex>`
     /// in which the regular expressions are stored used to identify to be deleted, filtered out
     /// matching sub-strings in the Blast Hit descriptions (`stitle`). These `stitle` strings are
@@ -757,106 +1219,857 @@ impl AnnotationProcess {
             };
     }
 
-    /// Parses the command line argument `field-separator` into a `char` used to split a line (row)
-    /// in a sequence similarity search result table into fields, i.e. a Blast Hit record. If the
-    /// argument `field_separator_arg` equals `"default"` (case insensitive) the value of
-    /// `default::SSSR_TABLE_FIELD_SEPARATOR` is used.
+    /// Parses the command line argument `hit-weighting` into the `HitWeightingMode` used to weight
+    /// Hit descriptions when generating human readable descriptions. If the argument
+    /// `hit_weighting_arg` equals `"default"` or `"none"` (case insensitive) all Hit descriptions
+    /// count equally. Panics if the argument does not name a known mode.
     ///
     /// # Arguments
     ///
     /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
-    /// * `field_separator_arg: &str` - The passed field_separator argument
-    pub fn add_ssst_field_separator(&mut self, field_separator_arg: &str) {
-        let mut seq_sim_table_field_separator = *SSSR_TABLE_FIELD_SEPARATOR;
-        if field_separator_arg.trim().to_lowercase() != "default" {
-            seq_sim_table_field_separator = field_separator_arg.chars().next().unwrap();
-        }
-        self.ssst_field_separators
-            .push(seq_sim_table_field_separator);
+    /// * `hit_weighting_arg: &str` - The passed hit-weighting argument
+    pub fn set_hit_weighting(&mut self, hit_weighting_arg: &str) {
+        self.hit_weighting = match HitWeightingMode::from_arg(hit_weighting_arg) {
+            Some(mode) => mode,
+            None => panic!("\n\nUnknown --hit-weighting {:?}. Please use one of 'none', 'bitscore', 'evalue', or 'default'.\n\n", hit_weighting_arg),
+        };
     }
 
-    /// Function validates the AnnotationProcess's fields and checks whether they are valid and
-    /// complete to start `run`. If invalid the function panics! with a comprehensive error
-    /// message.
+    /// Parses the command line argument `unsorted-input` into the `UnsortedInputMode` used to read
+    /// sequence similarity search result tables that might not be sorted by query identifiers. If
+    /// the argument `unsorted_input_arg` equals `"default"` or `"none"` (case insensitive) the
+    /// tables are expected to be sorted. Panics if the argument does not name a known mode.
     ///
     /// # Arguments
     ///
     /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
-    pub fn validate_fields(&mut self) {
-        let n_ssst = self.seq_sim_search_tables.len();
+    /// * `unsorted_input_arg: &str` - The passed unsorted-input argument
+    pub fn set_unsorted_input(&mut self, unsorted_input_arg: &str) {
+        self.unsorted_input = match UnsortedInputMode::from_arg(unsorted_input_arg) {
+            Some(mode) => mode,
+            None => panic!("\n\nUnknown --unsorted-input {:?}. Please use one of 'none', 'in-memory', 'on-disk', or 'default'.\n\n", unsorted_input_arg),
+        };
+    }
 
-        // --header
-        if !self.ssst_columns.is_empty() && self.ssst_columns.len() != n_ssst {
-            let n_ssst_cols = self.ssst_columns.len();
-            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but only {} column mappings. Please provide either no column mappings, causing the default to be used for all SSSTs, or provide one --field_separator (-e) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L172-L182\n\n", n_ssst_cols, n_ssst);
-        }
-        if !self.ssst_columns.is_empty() {
-            let required_cols = vec!["qacc", "sacc", "stitle"];
-            for (i, ssst_cols_i) in self.ssst_columns.iter().enumerate() {
-                for col_i in &required_cols {
-                    if !ssst_cols_i.contains_key(&col_i.to_string()) {
-                        panic!(
-                            "\n\nCannot run Annotation-Process, because --header (-e) argument number {} does not contain required column {:?}!\n\n",
-                            i + 1, col_i
-                        );
-                    }
-                }
-            }
-        }
+    /// Parses the command line argument `malformed-rows` into the `MalformedRowsMode` used to
+    /// handle malformed rows in sequence similarity search result tables. If the argument
+    /// `malformed_rows_arg` equals `"default"` (case insensitive) malformed rows are skipped with
+    /// a warning. Panics if the argument does not name a known mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `malformed_rows_arg: &str` - The passed malformed-rows argument
+    pub fn set_malformed_rows(&mut self, malformed_rows_arg: &str) {
+        self.malformed_rows = match MalformedRowsMode::from_arg(malformed_rows_arg) {
+            Some(mode) => mode,
+            None => panic!("\n\nUnknown --malformed-rows {:?}. Please use one of 'skip', 'strict', or 'default'.\n\n", malformed_rows_arg),
+        };
+    }
 
-        // --blacklist-regexs
-        if !self.ssst_blacklist_regexs.is_empty() && self.ssst_blacklist_regexs.len() != n_ssst {
-            let n_blacklist_regexs = self.ssst_blacklist_regexs.len();
-            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --blacklist-regexs (-b). Please provide either no --blacklist-regexs, causing the default to be used for all SSSTs, or provide one --blacklist-regexs (-b) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L145-L154\n\n", n_ssst, n_blacklist_regexs);
-        }
+    /// Parses the command line argument `seq-family-format` into the `SeqFamilyFormat` the gene
+    /// families file is read in. Panics if the argument does not name a known format.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `seq_family_format_arg: &str` - The passed seq-family-format argument
+    pub fn set_seq_family_format(&mut self, seq_family_format_arg: &str) {
+        self.seq_family_format = match SeqFamilyFormat::from_arg(seq_family_format_arg) {
+            Some(format) => format,
+            None => panic!("\n\nUnknown --seq-family-format {:?}. Please use one of 'orthofinder', 'orthofinder-hog', 'mcl', or 'default'.\n\n", seq_family_format_arg),
+        };
+    }
 
-        // --filter-regexs
-        if !self.ssst_filter_regexs.is_empty() && self.ssst_filter_regexs.len() != n_ssst {
-            let n_ssst_filter_regexs = self.ssst_filter_regexs.len();
-            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --filter-regexs (-l). Please provide either no --filter-regexs, causing the default to be used for all SSSTs, or provide one --filter-regexs (-l) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L201-L213\n\n", n_ssst, n_ssst_filter_regexs);
-        }
+    /// Parses the command line argument `infer-families` into the minimum Jaccard similarity of two
+    /// queries' Hits to link them into the same inferred sequence family (see
+    /// `infer_families_min_jaccard`). Uses the `default::INFER_SEQ_FAMILIES_MIN_JACCARD` if the
+    /// argument `infer_families_arg` equals `"default"` (case insensitive). Panics, if the
+    /// argument is not a number between zero and one.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `infer_families_arg: &str` - The passed infer-families argument
+    pub fn set_infer_families(&mut self, infer_families_arg: &str) {
+        let min_jaccard: f64 = if infer_families_arg.trim().to_lowercase() == "default" {
+            *INFER_SEQ_FAMILIES_MIN_JACCARD
+        } else {
+            infer_families_arg
+                .trim()
+                .parse()
+                .ok()
+                .filter(|j: &f64| (0.0..=1.0).contains(j))
+                .unwrap_or_else(|| {
+                    panic!("\n\nInvalid --infer-families {:?}. Please use a minimum Jaccard similarity between zero and one, or 'default'.\n\n", infer_families_arg)
+                })
+        };
+        self.infer_families_min_jaccard = Some(min_jaccard);
+    }
 
-        // --capture-replace-pairs
-        if !self.ssst_capture_replace_pairs.is_empty()
-            && self.ssst_capture_replace_pairs.len() != n_ssst
+    /// Parses the command line argument `infer-families-max-queries-per-hit` into the maximum
+    /// number of queries a Hit is used to link into inferred sequence families (see
+    /// `infer_families_max_queries_per_hit`). Uses the
+    /// `default::INFER_SEQ_FAMILIES_MAX_QUERIES_PER_HIT` if the argument
+    /// `max_queries_per_hit_arg` equals `"default"` (case insensitive). Panics, if the argument is
+    /// not a positive integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `max_queries_per_hit_arg: &str` - The passed infer-families-max-queries-per-hit argument
+    pub fn set_infer_families_max_queries_per_hit(&mut self, max_queries_per_hit_arg: &str) {
+        self.infer_families_max_queries_per_hit = if max_queries_per_hit_arg.trim().to_lowercase()
+            == "default"
         {
-            let n_ssst_capture_replace_pairs = self.ssst_capture_replace_pairs.len();
-            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --capture-replace-pairs (-c). Please provide either no --capture-replace-pairs, causing the default to be used for all SSSTs, or provide one --capture-replace-pairs (-c) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L156-L170\n\n", n_ssst, n_ssst_capture_replace_pairs);
-        }
+            *INFER_SEQ_FAMILIES_MAX_QUERIES_PER_HIT
+        } else {
+            max_queries_per_hit_arg
+                .trim()
+                .parse()
+                .ok()
+                .filter(|n: &usize| *n > 0)
+                .unwrap_or_else(|| {
+                    panic!("\n\nInvalid --infer-families-max-queries-per-hit {:?}. Please use a positive integer or 'default'.\n\n", max_queries_per_hit_arg)
+                })
+        };
+    }
 
-        // --field-separator
-        if !self.ssst_field_separators.is_empty() && self.ssst_field_separators.len() != n_ssst {
-            let n_ssst_field_seps = self.ssst_field_separators.len();
-            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} field-separators. Please provide either no field-separators, causing the default to be used for all SSSTs, or provide one --field-separator (-p) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L224-L229\n\n", n_ssst, n_ssst_field_seps);
-        }
+    /// Parses the command line argument `seq-family-id-digits` into the number of digits the index
+    /// in sequence family identifiers generated for raw MCL output is zero-padded to. Uses the
+    /// `default::SEQ_FAMILY_ID_DIGITS` if the argument `seq_family_id_digits_arg` equals
+    /// `"default"` (case insensitive). Panics, if the argument is not a non-negative integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `seq_family_id_digits_arg: &str` - The passed seq-family-id-digits argument
+    pub fn set_seq_family_id_digits(&mut self, seq_family_id_digits_arg: &str) {
+        self.seq_family_id_digits = if seq_family_id_digits_arg.trim().to_lowercase() == "default" {
+            *SEQ_FAMILY_ID_DIGITS
+        } else {
+            seq_family_id_digits_arg.trim().parse().unwrap_or_else(|_| {
+                panic!("\n\nInvalid --seq-family-id-digits {:?}. Please use a non-negative integer or 'default'.\n\n", seq_family_id_digits_arg)
+            })
+        };
+    }
 
-        // --n-threads
-        if self.n_threads < 2 {
-            panic!("\n\nCannot run Annotation-Process, because option '--n-threads' ('-n') must at least be minimum of two (2)!\n\n");
+    /// Parses the command line argument `exclude-taxa` into NCBI taxonomy identifiers and adds
+    /// them to `self.excluded_taxa`. The argument `excluded_taxa_arg` is either the path to a file
+    /// listing one identifier per line, or a comma separated list of identifiers. Panics if an
+    /// identifier cannot be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `excluded_taxa_arg: &str` - The passed exclude-taxa argument
+    pub fn add_excluded_taxa(&mut self, excluded_taxa_arg: &str) {
+        let taxa: Vec<String> = if Path::new(excluded_taxa_arg).is_file() {
+            fs::read_to_string(excluded_taxa_arg)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "\n\nCould not read --exclude-taxa file {:?}:\n{:?}\n\n",
+                        excluded_taxa_arg, e
+                    )
+                })
+                .lines()
+                .map(|line| line.to_string())
+                .collect()
+        } else {
+            excluded_taxa_arg
+                .split(',')
+                .map(|t| t.to_string())
+                .collect()
+        };
+        for taxon in taxa.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            self.excluded_taxa.push(taxon.parse().unwrap_or_else(|_| {
+                panic!(
+                    "\n\nCould not parse --exclude-taxa {:?} into an NCBI taxonomy identifier.\n\n",
+                    taxon
+                )
+            }));
         }
+    }
 
-        // --center-inverse-word-information-content-at-quantile
-        if self.center_iic_at_quantile != 50.0
-            && (self.center_iic_at_quantile < 0.0 || self.center_iic_at_quantile > 1.0)
-        {
-            panic!("\n\nCannot run Annotation-Process, because option '--center-inverse-word-information-content-at-quantile' ('-q') is not a real value between zero and one (both inclusive) or literal 50 (indicating centering at the mean and not a quantile). Please provide a correct value. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L231-L235\n\n");
-        }
+    /// Sets the path to the local NCBI taxonomy `nodes.dmp` used to exclude all taxa below the
+    /// `self.excluded_taxa`, too.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `taxonomy_nodes_arg: &str` - The passed taxonomy-nodes argument
+    pub fn set_taxonomy_nodes(&mut self, taxonomy_nodes_arg: &str) {
+        self.taxonomy_nodes = Some(taxonomy_nodes_arg.to_string());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::Path;
+    /// Parses the command line argument `excluded-taxa-weight` into the factor the evidence weight
+    /// of Hits of the `self.excluded_taxa` is multiplied with. If the argument
+    /// `excluded_taxa_weight_arg` equals `"default"` or `"none"` (case insensitive) these Hits are
+    /// discarded. Panics if the argument is not a value above zero and at most one.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `excluded_taxa_weight_arg: &str` - The passed excluded-taxa-weight argument
+    pub fn set_excluded_taxa_weight(&mut self, excluded_taxa_weight_arg: &str) {
+        let weight = parse_hit_threshold_arg(excluded_taxa_weight_arg, "--excluded-taxa-weight");
+        if weight.is_some_and(|w| w <= 0.0 || w > 1.0) {
+            panic!(
+                "\n\n--excluded-taxa-weight argument {:?} is not a value above zero and at most one.\n\n",
+                excluded_taxa_weight_arg
+            );
+        }
+        self.excluded_taxa_weight = weight;
+    }
 
-    #[test]
-    fn new_annotation_process_initializes_fields() {
-        let ap = AnnotationProcess::new();
-        assert_eq!(ap.seq_sim_search_tables.len(), 0);
-        assert_eq!(ap.seq_families.len(), 0);
+    /// Sets the NCBI taxonomy identifier or scientific name of the queries' taxon, which enables
+    /// the phylogenetic weighting of Hits.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `query_taxon_arg: &str` - The passed query-taxon argument
+    pub fn set_query_taxon(&mut self, query_taxon_arg: &str) {
+        self.query_taxon = Some(query_taxon_arg.trim().to_string());
     }
 
-    #[test]
+    /// Sets the path to the local NCBI taxonomy `names.dmp` used to look up the query taxon by
+    /// its scientific name.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `taxonomy_names_arg: &str` - The passed taxonomy-names argument
+    pub fn set_taxonomy_names(&mut self, taxonomy_names_arg: &str) {
+        self.taxonomy_names = Some(taxonomy_names_arg.to_string());
+    }
+
+    /// Returns the NCBI taxonomy identifier of the `self.query_taxon`, if set. A scientific name
+    /// is looked up in the `self.taxonomy_names`. Panics if that fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to an instance of AnnotationProcess.
+    pub fn query_taxon_id(&self) -> Option<u64> {
+        let query_taxon = self.query_taxon.as_ref()?;
+        if let Ok(taxon_id) = query_taxon.parse() {
+            return Some(taxon_id);
+        }
+        let names_dmp = self.taxonomy_names.as_ref().unwrap_or_else(|| {
+            panic!("\n\n--query-taxon {:?} is not an NCBI taxonomy identifier. Please provide --taxonomy-names to look it up by its scientific name.\n\n", query_taxon)
+        });
+        Some(
+            find_taxon_by_name(names_dmp, query_taxon).unwrap_or_else(|| {
+                panic!(
+                    "\n\n--query-taxon {:?} is not a scientific name in --taxonomy-names {:?}.\n\n",
+                    query_taxon, names_dmp
+                )
+            }),
+        )
+    }
+
+    /// Returns the `TaxonFilter` for the `self.excluded_taxa` and the `self.query_taxon`, if
+    /// any of them are set. Note that this reads the `self.taxonomy_nodes`, if set.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to an instance of AnnotationProcess.
+    pub fn taxon_filter(&self) -> Option<TaxonFilter> {
+        if self.excluded_taxa.is_empty() && self.query_taxon.is_none() {
+            None
+        } else {
+            Some(TaxonFilter::new(
+                &self.excluded_taxa,
+                self.taxonomy_nodes.as_deref(),
+                self.excluded_taxa_weight,
+                self.query_taxon_id(),
+            ))
+        }
+    }
+
+    /// Parses the command line argument `field-separator` into a `char` used to split a line (row)
+    /// in a sequence similarity search result table into fields, i.e. a Blast Hit record. If the
+    /// argument `field_separator_arg` equals `"default"` (case insensitive) the value of
+    /// `default::SSSR_TABLE_FIELD_SEPARATOR` is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `field_separator_arg: &str` - The passed field_separator argument
+    pub fn add_ssst_field_separator(&mut self, field_separator_arg: &str) {
+        let mut seq_sim_table_field_separator = *SSSR_TABLE_FIELD_SEPARATOR;
+        if field_separator_arg.trim().to_lowercase() != "default" {
+            seq_sim_table_field_separator = field_separator_arg.chars().next().unwrap();
+        }
+        self.ssst_field_separators
+            .push(seq_sim_table_field_separator);
+    }
+
+    /// Parses the command line argument `seq-sim-table-format` into a `SeqSimTableFormat` that
+    /// defines how the respective sequence similarity search result table is read. If the argument
+    /// `format_arg` equals `"default"` (case insensitive) `SeqSimTableFormat::Tabular` is used.
+    /// Panics if the argument does not name a known format.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `format_arg: &str` - The passed seq-sim-table-format argument
+    pub fn add_ssst_format(&mut self, format_arg: &str) {
+        match SeqSimTableFormat::from_arg(format_arg) {
+            Some(format) => self.ssst_formats.push(format),
+            None => panic!("\n\nUnknown --seq-sim-table-format (-t) {:?}. Please use one of 'tabular', 'blast-xml', 'blast-json', 'hmmer-tblout', 'hmmer-domtblout', 'diamond-daa', or 'default'.\n\n", format_arg),
+        }
+    }
+
+    /// Parses the command line argument `max-evalue` into the maximum e-value Hits in the
+    /// respective sequence similarity search result table must not exceed. If the argument
+    /// `max_evalue_arg` equals `"default"` or `"none"` (case insensitive) no threshold is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `max_evalue_arg: &str` - The passed max-evalue argument
+    pub fn add_ssst_max_evalue(&mut self, max_evalue_arg: &str) {
+        self.ssst_max_evalues
+            .push(parse_hit_threshold_arg(max_evalue_arg, "--max-evalue"));
+    }
+
+    /// Parses the command line argument `min-bitscore` into the minimum bit score Hits in the
+    /// respective sequence similarity search result table must have. If the argument
+    /// `min_bitscore_arg` equals `"default"` or `"none"` (case insensitive) no threshold is
+    /// applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `min_bitscore_arg: &str` - The passed min-bitscore argument
+    pub fn add_ssst_min_bitscore(&mut self, min_bitscore_arg: &str) {
+        self.ssst_min_bitscores
+            .push(parse_hit_threshold_arg(min_bitscore_arg, "--min-bitscore"));
+    }
+
+    /// Parses the command line argument `min-pident` into the minimum percent identity Hits in
+    /// the respective sequence similarity search result table must have. If the argument
+    /// `min_pident_arg` equals `"default"` or `"none"` (case insensitive) no threshold is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `min_pident_arg: &str` - The passed min-pident argument
+    pub fn add_ssst_min_pident(&mut self, min_pident_arg: &str) {
+        self.ssst_min_pidents
+            .push(parse_hit_threshold_arg(min_pident_arg, "--min-pident"));
+    }
+
+    /// Parses the command line argument `min-qcovhsp` into the minimum percent query coverage
+    /// Hits in the respective sequence similarity search result table must have. If the argument
+    /// `min_qcovhsp_arg` equals `"default"` or `"none"` (case insensitive) no threshold is
+    /// applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `min_qcovhsp_arg: &str` - The passed min-qcovhsp argument
+    pub fn add_ssst_min_qcovhsp(&mut self, min_qcovhsp_arg: &str) {
+        self.ssst_min_qcovhsps
+            .push(parse_hit_threshold_arg(min_qcovhsp_arg, "--min-qcovhsp"));
+    }
+
+    /// Parses the command line argument `top-n-hits` into the number of best Hits (by bit score)
+    /// to retain per query in the respective sequence similarity search result table. If the
+    /// argument `top_n_hits_arg` equals `"default"` or `"none"` (case insensitive) all Hits are
+    /// retained.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `top_n_hits_arg: &str` - The passed top-n-hits argument
+    pub fn add_ssst_top_n_hits(&mut self, top_n_hits_arg: &str) {
+        let top_n_hits = parse_hit_threshold_arg(top_n_hits_arg, "--top-n-hits").map(|n| {
+            if n < 1.0 || n.fract() != 0.0 {
+                panic!(
+                    "\n\n--top-n-hits argument {:?} is not a positive integer.\n\n",
+                    top_n_hits_arg
+                );
+            }
+            n as usize
+        });
+        self.ssst_top_n_hits.push(top_n_hits);
+    }
+
+    /// Parses the command line argument `within-best-bitscore` into the fraction of the best Hit's
+    /// bit score within which Hits are retained per query in the respective sequence similarity
+    /// search result table. If the argument `within_best_bitscore_arg` equals `"default"` or
+    /// `"none"` (case insensitive) all Hits are retained.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `within_best_bitscore_arg: &str` - The passed within-best-bitscore argument
+    pub fn add_ssst_within_best_bitscore(&mut self, within_best_bitscore_arg: &str) {
+        let fraction = parse_hit_threshold_arg(within_best_bitscore_arg, "--within-best-bitscore");
+        if fraction.is_some_and(|f| !(0.0..=1.0).contains(&f)) {
+            panic!(
+                "\n\n--within-best-bitscore argument {:?} is not a value between zero and one (both inclusive).\n\n",
+                within_best_bitscore_arg
+            );
+        }
+        self.ssst_within_best_bitscores.push(fraction);
+    }
+
+    /// Parses the command line argument `evidence-weights` into the weights of the Hits' evidence
+    /// in the respective sequence similarity search result table (see `hit::EvidenceWeights`). If
+    /// the argument `evidence_weights_arg` equals `"default"` or `"none"` (case insensitive) the
+    /// evidence is not weighted, `"uniprot"` uses `default::UNIPROT_EVIDENCE_WEIGHTS`. Any other
+    /// argument is parsed as a comma separated list of `key=weight` pairs. Panics if that fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `evidence_weights_arg: &str` - The passed evidence-weights argument
+    pub fn add_ssst_evidence_weights(&mut self, evidence_weights_arg: &str) {
+        let evidence_weights = match evidence_weights_arg.trim().to_lowercase().as_str() {
+            "default" | "none" => None,
+            "uniprot" => EvidenceWeights::parse(&UNIPROT_EVIDENCE_WEIGHTS),
+            spec => Some(EvidenceWeights::parse(spec).unwrap_or_else(|| {
+                panic!("\n\nInvalid --evidence-weights {:?}. Please use 'none', 'uniprot', 'default', or a comma separated list of key=weight pairs with positive weights and keys 'sp', 'tr', 'uniref', 'other', 'pe1' to 'pe5', or 'pe-missing'.\n\n", evidence_weights_arg)
+            })),
+        };
+        self.ssst_evidence_weights.push(evidence_weights);
+    }
+
+    /// Parses the command line argument `reference-fasta` into the path of the reference FASTA, or
+    /// of its index file, the Hit titles (`stitle`) of the respective tabular sequence similarity
+    /// search result table are looked up in. If the argument `reference_fasta_arg` equals `"default"` or `"none"`
+    /// (case insensitive) the table is expected to provide a `stitle` column.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `reference_fasta_arg: &str` - The passed reference-fasta argument
+    pub fn add_ssst_reference_fasta(&mut self, reference_fasta_arg: &str) {
+        let reference_fasta = match reference_fasta_arg.trim().to_lowercase().as_str() {
+            "default" | "none" => None,
+            _ => Some(reference_fasta_arg.to_string()),
+        };
+        self.ssst_reference_fastas.push(reference_fasta);
+    }
+
+    /// Parses the command line argument `uniprot-dat` into the path of the UniProt flat file whose
+    /// protein names describe the Hits of the respective tabular sequence similarity search result
+    /// table. If the argument `uniprot_dat_arg` equals `"default"` or `"none"` (case insensitive)
+    /// the Hits are described by their filtered titles (`stitle`).
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    /// * `uniprot_dat_arg: &str` - The passed uniprot-dat argument
+    pub fn add_ssst_uniprot_dat(&mut self, uniprot_dat_arg: &str) {
+        let uniprot_dat = match uniprot_dat_arg.trim().to_lowercase().as_str() {
+            "default" | "none" => None,
+            _ => Some(uniprot_dat_arg.to_string()),
+        };
+        self.ssst_uniprot_dats.push(uniprot_dat);
+    }
+
+    /// Combines the per table Hit thresholds (`self.ssst_max_evalues`, `self.ssst_min_bitscores`,
+    /// `self.ssst_min_pidents`, `self.ssst_min_qcovhsps`, `self.ssst_top_n_hits`, and
+    /// `self.ssst_within_best_bitscores`) into one `HitThresholds` for each sequence similarity
+    /// search result table. Thresholds that have not been provided are not
+    /// applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to an instance of AnnotationProcess.
+    pub fn ssst_hit_thresholds(&self) -> Vec<HitThresholds> {
+        let threshold = |thresholds: &Vec<Option<f64>>, i: usize| -> Option<f64> {
+            thresholds.get(i).cloned().flatten()
+        };
+        (0..self.seq_sim_search_tables.len())
+            .map(|i| HitThresholds {
+                max_evalue: threshold(&self.ssst_max_evalues, i),
+                min_bitscore: threshold(&self.ssst_min_bitscores, i),
+                min_pident: threshold(&self.ssst_min_pidents, i),
+                min_qcovhsp: threshold(&self.ssst_min_qcovhsps, i),
+                top_n_hits: self.ssst_top_n_hits.get(i).cloned().flatten(),
+                within_best_bitscore: threshold(&self.ssst_within_best_bitscores, i),
+            })
+            .collect()
+    }
+
+    /// Returns the input sequence similarity search result tables, each with the settings it is
+    /// parsed with, in reverse order, so `run` pops them in the order they were given. Per table
+    /// settings the user did not provide default to those in module `default`. The weight of a
+    /// table (`ssst_weights`) scales the evidence weight of each of its Hits. Note that reading
+    /// the taxonomy happens here, once for all tables (see `taxon_filter`).
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to an instance of AnnotationProcess.
+    fn queued_tables(&self) -> Vec<QueuedTable> {
+        let taxon_filter = self.taxon_filter().map(Arc::new);
+        let hit_thresholds = self.ssst_hit_thresholds();
+        let mut queued_tables: Vec<QueuedTable> = self
+            .seq_sim_search_tables
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let evidence_weights = self.ssst_evidence_weights.get(i).cloned().flatten();
+                let weight = self.ssst_weights.get(i).copied().unwrap_or(*SSST_WEIGHT);
+                let evidence_weights = if weight == *SSST_WEIGHT {
+                    evidence_weights
+                } else {
+                    Some(EvidenceWeights {
+                        table: weight,
+                        ..evidence_weights.unwrap_or_default()
+                    })
+                };
+                QueuedTable {
+                    path: path.clone(),
+                    format: self
+                        .ssst_formats
+                        .get(i)
+                        .copied()
+                        .unwrap_or(SeqSimTableFormat::Tabular),
+                    field_separator: self
+                        .ssst_field_separators
+                        .get(i)
+                        .copied()
+                        .unwrap_or(*SSSR_TABLE_FIELD_SEPARATOR),
+                    columns: self.ssst_columns.get(i).cloned(),
+                    tier: self.ssst_tiers.get(i).copied(),
+                    reference_fasta: self.ssst_reference_fastas.get(i).cloned().flatten(),
+                    uniprot_dat: self.ssst_uniprot_dats.get(i).cloned().flatten(),
+                    options: TableOptions {
+                        hit_thresholds: hit_thresholds[i],
+                        evidence_weights,
+                        taxon_filter: taxon_filter.clone(),
+                        reference_titles: None,
+                        prefer_reference_titles: self.prefer_reference_titles,
+                        uniprot_flat_file: None,
+                        blacklist_regexs: self
+                            .ssst_blacklist_regexs
+                            .get(i)
+                            .cloned()
+                            .unwrap_or_else(|| BLACKLIST_STITLE_REGEXS.clone()),
+                        filter_regexs: self
+                            .ssst_filter_regexs
+                            .get(i)
+                            .cloned()
+                            .unwrap_or_else(|| FILTER_REGEXS.clone()),
+                        capture_replace_pairs: Some(
+                            self.ssst_capture_replace_pairs
+                                .get(i)
+                                .cloned()
+                                .unwrap_or_else(|| CAPTURE_REPLACE_DESCRIPTION_PAIRS.clone()),
+                        ),
+                        malformed_rows_mode: self.malformed_rows,
+                    },
+                }
+            })
+            .collect();
+        queued_tables.reverse();
+        queued_tables
+    }
+
+    /// Returns the names (Blast terminology) of the Hit quality measures the sequence similarity
+    /// search result table with index `ssst_index` provides. Tabular tables provide those listed
+    /// in their `--header` or, if none was given, in their `# Fields:` comment line. Tables read
+    /// from standard input cannot be inspected beforehand, so without `--header` they are assumed
+    /// to have the default columns (`default::SEQ_SIM_TABLE_COLUMNS`), i.e. to provide none of
+    /// them. The other formats provide a fixed set.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to an instance of AnnotationProcess.
+    /// * `ssst_index: usize` - The index of the table in `self.seq_sim_search_tables`
+    pub fn ssst_provided_scores(&self, ssst_index: usize) -> Vec<String> {
+        let format = self
+            .ssst_formats
+            .get(ssst_index)
+            .cloned()
+            .unwrap_or(SeqSimTableFormat::Tabular);
+        let all_scores = ["evalue", "bitscore", "pident", "qcovhsp"];
+        let ssst = &self.seq_sim_search_tables[ssst_index];
+        match format {
+            SeqSimTableFormat::Tabular => match self.ssst_columns.get(ssst_index) {
+                Some(columns) => columns.keys().cloned().collect(),
+                None if is_stdin_path(ssst) => SEQ_SIM_TABLE_COLUMNS.keys().cloned().collect(),
+                None => read_fields_comment(ssst)
+                    .unwrap_or_else(|| SEQ_SIM_TABLE_COLUMNS.clone())
+                    .into_keys()
+                    .collect(),
+            },
+            SeqSimTableFormat::HmmerTblout | SeqSimTableFormat::HmmerDomtblout => {
+                vec!["evalue".to_string(), "bitscore".to_string()]
+            }
+            SeqSimTableFormat::BlastXml
+            | SeqSimTableFormat::BlastJson
+            | SeqSimTableFormat::DiamondDaa => all_scores.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Function validates the AnnotationProcess's fields and checks whether they are valid and
+    /// complete to start `run`. If invalid the function panics! with a comprehensive error
+    /// message.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A reference to a mutable instance of AnnotationProcess.
+    pub fn validate_fields(&mut self) {
+        let n_ssst = self.seq_sim_search_tables.len();
+
+        // --seq-sim-table
+        let n_stdin_sssts = self
+            .seq_sim_search_tables
+            .iter()
+            .filter(|ssst| is_stdin_path(ssst))
+            .count();
+        if n_stdin_sssts > 1 {
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs) to be read from standard input ('-'). Standard input can only be read once, please provide at most one --seq-sim-table (-s) argument '-'.\n\n", n_stdin_sssts);
+        }
+
+        // --header
+        if !self.ssst_columns.is_empty() && self.ssst_columns.len() != n_ssst {
+            let n_ssst_cols = self.ssst_columns.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but only {} column mappings. Please provide either no column mappings, causing the default to be used for all SSSTs, or provide one --field_separator (-e) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L172-L182\n\n", n_ssst_cols, n_ssst);
+        }
+        if !self.ssst_columns.is_empty() {
+            let required_cols = vec!["qacc", "sacc", "stitle"];
+            for (i, ssst_cols_i) in self.ssst_columns.iter().enumerate() {
+                for col_i in &required_cols {
+                    // Hit titles can be looked up in the table's reference FASTA or UniProt flat
+                    // file instead:
+                    let has_reference_fasta = self
+                        .ssst_reference_fastas
+                        .get(i)
+                        .is_some_and(|f| f.is_some())
+                        || self.ssst_uniprot_dats.get(i).is_some_and(|f| f.is_some());
+                    if *col_i == "stitle" && has_reference_fasta {
+                        continue;
+                    }
+                    if !ssst_cols_i.contains_key(&col_i.to_string()) {
+                        panic!(
+                            "\n\nCannot run Annotation-Process, because --header (-e) argument number {} does not contain required column {:?}!\n\n",
+                            i + 1, col_i
+                        );
+                    }
+                }
+            }
+        }
+
+        // --blacklist-regexs
+        if !self.ssst_blacklist_regexs.is_empty() && self.ssst_blacklist_regexs.len() != n_ssst {
+            let n_blacklist_regexs = self.ssst_blacklist_regexs.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --blacklist-regexs (-b). Please provide either no --blacklist-regexs, causing the default to be used for all SSSTs, or provide one --blacklist-regexs (-b) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L145-L154\n\n", n_ssst, n_blacklist_regexs);
+        }
+
+        // --table-weight
+        if !self.ssst_weights.is_empty() && self.ssst_weights.len() != n_ssst {
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --table-weight args. Please provide either no --table-weight, causing all SSSTs to be weighted equally, or provide one --table-weight argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, self.ssst_weights.len());
+        }
+
+        // --infer-families
+        if self.infer_families_min_jaccard.is_some() && !self.seq_families.is_empty() {
+            panic!("\n\nCannot run Annotation-Process, because sequence families are both given and to be inferred (--infer-families). Please provide either --seq-families (-f) or --infer-families.\n\n");
+        }
+
+        // --table-tier
+        if !self.ssst_tiers.is_empty() && self.ssst_tiers.len() != n_ssst {
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --table-tier args. Please provide either no --table-tier, causing the Hits of all SSSTs to be used together, or provide one --table-tier argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, self.ssst_tiers.len());
+        }
+
+        // --filter-regexs
+        if !self.ssst_filter_regexs.is_empty() && self.ssst_filter_regexs.len() != n_ssst {
+            let n_ssst_filter_regexs = self.ssst_filter_regexs.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --filter-regexs (-l). Please provide either no --filter-regexs, causing the default to be used for all SSSTs, or provide one --filter-regexs (-l) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L201-L213\n\n", n_ssst, n_ssst_filter_regexs);
+        }
+
+        // --capture-replace-pairs
+        if !self.ssst_capture_replace_pairs.is_empty()
+            && self.ssst_capture_replace_pairs.len() != n_ssst
+        {
+            let n_ssst_capture_replace_pairs = self.ssst_capture_replace_pairs.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --capture-replace-pairs (-c). Please provide either no --capture-replace-pairs, causing the default to be used for all SSSTs, or provide one --capture-replace-pairs (-c) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L156-L170\n\n", n_ssst, n_ssst_capture_replace_pairs);
+        }
+
+        // --field-separator
+        if !self.ssst_field_separators.is_empty() && self.ssst_field_separators.len() != n_ssst {
+            let n_ssst_field_seps = self.ssst_field_separators.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} field-separators. Please provide either no field-separators, causing the default to be used for all SSSTs, or provide one --field-separator (-p) argument for each of your input SSSTs. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L224-L229\n\n", n_ssst, n_ssst_field_seps);
+        }
+
+        // --seq-sim-table-format
+        if !self.ssst_formats.is_empty() && self.ssst_formats.len() != n_ssst {
+            let n_ssst_formats = self.ssst_formats.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --seq-sim-table-format (-t) arguments. Please provide either no --seq-sim-table-format, causing the default (tabular) to be used for all SSSTs, or provide one --seq-sim-table-format (-t) argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, n_ssst_formats);
+        }
+        for (i, ssst) in self.seq_sim_search_tables.iter().enumerate() {
+            // The reference names follow the alignments in DAA files, which are thus read twice:
+            if self.ssst_formats.get(i) == Some(&SeqSimTableFormat::DiamondDaa)
+                && is_stdin_path(ssst)
+            {
+                panic!("\n\nCannot run Annotation-Process, because sequence similarity search result table (SSST) number {} is a Diamond DAA file to be read from standard input ('-'). Diamond DAA files cannot be read from standard input, please provide the path to the DAA file.\n\n", i + 1);
+            }
+        }
+
+        // --max-evalue, --min-bitscore, --min-pident, --min-qcovhsp, --top-n-hits, and
+        // --within-best-bitscore
+        for (n_thresholds, option) in [
+            (self.ssst_max_evalues.len(), "--max-evalue"),
+            (self.ssst_min_bitscores.len(), "--min-bitscore"),
+            (self.ssst_min_pidents.len(), "--min-pident"),
+            (self.ssst_min_qcovhsps.len(), "--min-qcovhsp"),
+            (self.ssst_top_n_hits.len(), "--top-n-hits"),
+            (
+                self.ssst_within_best_bitscores.len(),
+                "--within-best-bitscore",
+            ),
+        ] {
+            if n_thresholds != 0 && n_thresholds != n_ssst {
+                panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} {} arguments. Please provide either no {}, causing no threshold to be applied, or provide one {} argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, n_thresholds, option, option, option);
+            }
+        }
+        // --evidence-weights
+        if !self.ssst_evidence_weights.is_empty() && self.ssst_evidence_weights.len() != n_ssst {
+            let n_evidence_weights = self.ssst_evidence_weights.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --evidence-weights arguments. Please provide either no --evidence-weights, causing the evidence not to be weighted, or provide one --evidence-weights argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, n_evidence_weights);
+        }
+
+        // --reference-fasta
+        if !self.ssst_reference_fastas.is_empty() && self.ssst_reference_fastas.len() != n_ssst {
+            let n_reference_fastas = self.ssst_reference_fastas.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --reference-fasta arguments. Please provide either no --reference-fasta, causing the Hit titles to be read from the SSSTs' 'stitle' columns, or provide one --reference-fasta argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, n_reference_fastas);
+        }
+        for (i, reference_fasta_i) in self.ssst_reference_fastas.iter().enumerate() {
+            let format = self
+                .ssst_formats
+                .get(i)
+                .cloned()
+                .unwrap_or(SeqSimTableFormat::Tabular);
+            if reference_fasta_i.is_some() && format != SeqSimTableFormat::Tabular {
+                panic!("\n\nCannot run Annotation-Process, because a --reference-fasta is given for sequence similarity search result table (SSST) number {}, which is not tabular. Only tabular SSSTs can have their Hit titles looked up in a reference FASTA. Please set the respective --reference-fasta to 'none'.\n\n", i + 1);
+            }
+        }
+        // --uniprot-dat
+        if !self.ssst_uniprot_dats.is_empty() && self.ssst_uniprot_dats.len() != n_ssst {
+            let n_uniprot_dats = self.ssst_uniprot_dats.len();
+            panic!("\n\nCannot run Annotation-Process, because got {} sequence similarity search result tables (SSSTs), but {} --uniprot-dat arguments. Please provide either no --uniprot-dat, causing the Hits to be described by their filtered titles, or provide one --uniprot-dat argument for each of your input SSSTs. See --help for more details.\n\n", n_ssst, n_uniprot_dats);
+        }
+        for (i, uniprot_dat_i) in self.ssst_uniprot_dats.iter().enumerate() {
+            let format = self
+                .ssst_formats
+                .get(i)
+                .cloned()
+                .unwrap_or(SeqSimTableFormat::Tabular);
+            if uniprot_dat_i.is_some() && format != SeqSimTableFormat::Tabular {
+                panic!("\n\nCannot run Annotation-Process, because a --uniprot-dat is given for sequence similarity search result table (SSST) number {}, which is not tabular. Only the Hits of tabular SSSTs can be described by UniProt flat file entries. Please set the respective --uniprot-dat to 'none'.\n\n", i + 1);
+            }
+        }
+        // --prefer-reference-titles
+        if self.prefer_reference_titles && !self.ssst_reference_fastas.iter().any(|f| f.is_some()) {
+            panic!("\n\nCannot run Annotation-Process, because --prefer-reference-titles is set, but no --reference-fasta is given. Please provide the reference FASTA, or its index built with 'prot-scriber index', of at least one of your input SSSTs.\n\n");
+        }
+
+        // Tables read from standard input cannot be inspected for their '# Fields:' comment line
+        // beforehand, so the quality measures thresholds and weighting need must be declared:
+        for (i, hit_thresholds_i) in self.ssst_hit_thresholds().iter().enumerate() {
+            let needs_scores = !hit_thresholds_i.required_scores().is_empty()
+                || self.hit_weighting.required_score().is_some();
+            if needs_scores
+                && is_stdin_path(&self.seq_sim_search_tables[i])
+                && self.ssst_columns.get(i).is_none()
+                && self
+                    .ssst_formats
+                    .get(i)
+                    .is_none_or(|f| *f == SeqSimTableFormat::Tabular)
+            {
+                panic!("\n\nCannot run Annotation-Process, because sequence similarity search result table (SSST) number {} is read from standard input ('-') without --header (-e), but a threshold (or Hit selection) or --hit-weighting requires its quality measures. Standard input cannot be inspected for a '# Fields:' comment line beforehand, please provide the SSST's columns with the respective --header (-e) argument.\n\n", i + 1);
+            }
+        }
+
+        for (i, hit_thresholds_i) in self.ssst_hit_thresholds().iter().enumerate() {
+            let provided_scores = self.ssst_provided_scores(i);
+            for score in hit_thresholds_i.required_scores() {
+                if !provided_scores.iter().any(|s| s == score) {
+                    panic!("\n\nCannot run Annotation-Process, because a threshold (or Hit selection) is set that requires {:?}, but sequence similarity search result table (SSST) number {} does not provide this column. Please add it to the respective --header (-e) argument or do not set a threshold on it for this SSST.\n\n", score, i + 1);
+                }
+            }
+        }
+
+        // --hit-weighting
+        if let Some(score) = self.hit_weighting.required_score() {
+            for i in 0..n_ssst {
+                if !self.ssst_provided_scores(i).iter().any(|s| s == score) {
+                    panic!("\n\nCannot run Annotation-Process, because --hit-weighting {:?} requires the column {:?}, but sequence similarity search result table (SSST) number {} does not provide it. Please add it to the respective --header (-e) argument.\n\n", score, score, i + 1);
+                }
+            }
+        }
+
+        // --excluded-taxa-weight
+        if self.excluded_taxa.is_empty() && self.excluded_taxa_weight.is_some() {
+            panic!("\n\nCannot run Annotation-Process, because --excluded-taxa-weight is given, but no --exclude-taxa. Please provide the NCBI taxonomy identifiers of the taxa to exclude.\n\n");
+        }
+
+        // --taxonomy-nodes and --query-taxon
+        if self.taxonomy_nodes.is_some()
+            && self.excluded_taxa.is_empty()
+            && self.query_taxon.is_none()
+        {
+            panic!("\n\nCannot run Annotation-Process, because --taxonomy-nodes is given, but neither --exclude-taxa nor --query-taxon. Please provide either of them or omit --taxonomy-nodes.\n\n");
+        }
+        if self.query_taxon.is_some() && self.taxonomy_nodes.is_none() {
+            panic!("\n\nCannot run Annotation-Process, because weighting Hits by their phylogenetic distance to the --query-taxon requires a local NCBI taxonomy. Please provide its nodes.dmp with --taxonomy-nodes.\n\n");
+        }
+
+        // --n-threads
+        if self.n_threads < 2 {
+            panic!("\n\nCannot run Annotation-Process, because option '--n-threads' ('-n') must at least be minimum of two (2)!\n\n");
+        }
+
+        // --center-inverse-word-information-content-at-quantile
+        if self.center_iic_at_quantile != 50.0
+            && (self.center_iic_at_quantile < 0.0 || self.center_iic_at_quantile > 1.0)
+        {
+            panic!("\n\nCannot run Annotation-Process, because option '--center-inverse-word-information-content-at-quantile' ('-q') is not a real value between zero and one (both inclusive) or literal 50 (indicating centering at the mean and not a quantile). Please provide a correct value. See --help or the following link for more details.\n\nhttps://github.com/usadellab/prot-scriber/blob/880d32bab31ab5d0b2a3708a9faec8f37b53be9b/README.md?plain=1#L231-L235\n\n");
+        }
+    }
+}
+
+/// Parses a command line argument setting a Hit threshold (e.g. `--max-evalue`) into a number.
+/// Returns `None`, i.e. no threshold, if the argument equals `"default"` or `"none"` (case
+/// insensitive). Panics, if the argument cannot be parsed into a number.
+///
+/// # Arguments
+///
+/// * `threshold_arg: &str` - The passed command line argument
+/// * `option: &str` - The name of the option, used in the error message
+fn parse_hit_threshold_arg(threshold_arg: &str, option: &str) -> Option<f64> {
+    match threshold_arg.trim().to_lowercase().as_str() {
+        "default" | "none" => None,
+        value => Some(value.parse::<f64>().unwrap_or_else(|_| {
+            panic!(
+                "\n\nCould not parse {} argument {:?} into a number.\n\n",
+                option, threshold_arg
+            )
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hit;
+    use std::path::Path;
+
+    #[test]
+    fn new_annotation_process_initializes_fields() {
+        let ap = AnnotationProcess::new();
+        assert_eq!(ap.seq_sim_search_tables.len(), 0);
+        assert_eq!(ap.seq_families.len(), 0);
+    }
+
+    #[test]
     fn annotation_process_mode_detected_correctly() {
         let mut ap = AnnotationProcess::new();
         assert!(matches!(
@@ -876,8 +2089,20 @@ mod tests {
         let mut nq1 = Query::new();
         let h1 = ("hit_One","sp|C0LGP4|Y3475_ARATH Probable LRR receptor-like serine/threonine-protein kinase At3g47570 OS=Arabidopsis thaliana OX=3702 GN=At3g47570 PE=2 SV=1");
         let h2 = ("hit_Two","sp|C0LGP4|Y3475_ARATH Probable LRR receptor-like serine/threonine-protein kinase At3g47570 OS=Arabidopsis thaliana OX=3702 GN=At3g47570 PE=2 SV=1");
-        nq1.hits.insert(h1.0.to_string(), h1.1.to_string());
-        nq1.hits.insert(h2.0.to_string(), h2.1.to_string());
+        nq1.hits.insert(
+            h1.0.to_string(),
+            Hit {
+                description: h1.1.to_string(),
+                ..Default::default()
+            },
+        );
+        nq1.hits.insert(
+            h2.0.to_string(),
+            Hit {
+                description: h2.1.to_string(),
+                ..Default::default()
+            },
+        );
         // Test insert_query
         let qacc = "Soltu.DM.02G015700.1".to_string();
         ap.insert_query(qacc.clone(), nq1);
@@ -895,8 +2120,20 @@ mod tests {
         let mut nq2 = Query::new();
         let h3 = ("hit_Three","tr|A0A2G9HZP3|A0A2G9HZP3_9LAMI Serine/threonine protein kinase OS=Handroanthus impetiginosus OX=429701 GN=CDL12_04291 PE=4 SV=1");
         let h4 = ("hit_Four","tr|A0A0V0ITN0|A0A0V0ITN0_SOLCH Protein kinase domain-containing protein OS=Solanum chacoense OX=4108 PE=4 SV=1");
-        nq2.hits.insert(h3.0.to_string(), h3.1.to_string());
-        nq2.hits.insert(h4.0.to_string(), h4.1.to_string());
+        nq2.hits.insert(
+            h3.0.to_string(),
+            Hit {
+                description: h3.1.to_string(),
+                ..Default::default()
+            },
+        );
+        nq2.hits.insert(
+            h4.0.to_string(),
+            Hit {
+                description: h4.1.to_string(),
+                ..Default::default()
+            },
+        );
         ap.insert_query(qacc.clone(), nq2);
 
         // check if query 'nq2' got inserted correctly
@@ -939,15 +2176,15 @@ mod tests {
         assert!(ap.seq_families.contains_key("SeqFamily1"));
         assert_eq!(
             *ap.query_id_to_seq_family_id_index.get("Query1").unwrap(),
-            sf_id1
+            vec![sf_id1.clone()]
         );
         assert_eq!(
             *ap.query_id_to_seq_family_id_index.get("Query2").unwrap(),
-            sf_id1
+            vec![sf_id1.clone()]
         );
         assert_eq!(
             *ap.query_id_to_seq_family_id_index.get("Query3").unwrap(),
-            sf_id1
+            vec![sf_id1.clone()]
         );
         let mut sf2 = SeqFamily::new();
         sf2.query_ids = vec![
@@ -960,21 +2197,20 @@ mod tests {
         assert!(ap.seq_families.contains_key("SeqFamily2"));
         assert_eq!(
             *ap.query_id_to_seq_family_id_index.get("Query4").unwrap(),
-            sf_id2
+            vec![sf_id2.clone()]
         );
         assert_eq!(
             *ap.query_id_to_seq_family_id_index.get("Query5").unwrap(),
-            sf_id2
+            vec![sf_id2.clone()]
         );
         assert_eq!(
             *ap.query_id_to_seq_family_id_index.get("Query6").unwrap(),
-            sf_id2
+            vec![sf_id2.clone()]
         );
     }
 
     #[test]
-    #[should_panic]
-    fn double_assignment_of_seq_id_to_different_families_causes_panic() {
+    fn assigns_seq_ids_to_overlapping_families() {
         let mut ap = AnnotationProcess::new();
         let mut sf1 = SeqFamily::new();
         sf1.query_ids = vec![
@@ -988,6 +2224,143 @@ mod tests {
         sf2.query_ids = vec!["Query1".to_string(), "Query4".to_string()];
         let sf_id2 = "SeqFamily2".to_string();
         ap.insert_seq_family(sf_id2.clone(), sf2);
+        assert_eq!(
+            *ap.query_id_to_seq_family_id_index.get("Query1").unwrap(),
+            vec![sf_id1.clone(), sf_id2.clone()]
+        );
+        // Annotating the first family retains the shared query for the second:
+        let mut query1 = Query::new();
+        query1.hits.insert(
+            "Q1MX30".to_string(),
+            Hit {
+                description: "receptor kinase protein xa".to_string(),
+                ..Default::default()
+            },
+        );
+        ap.queries.insert("Query1".to_string(), query1);
+        ap.annotate_seq_family(&sf_id1);
+        assert!(ap.queries.contains_key("Query1"));
+        assert_eq!(
+            *ap.query_id_to_seq_family_id_index.get("Query1").unwrap(),
+            vec![sf_id2.clone()]
+        );
+        assert!(!ap.query_id_to_seq_family_id_index.contains_key("Query2"));
+        ap.annotate_seq_family(&sf_id2);
+        assert!(!ap.queries.contains_key("Query1"));
+        assert!(!ap.query_id_to_seq_family_id_index.contains_key("Query1"));
+    }
+
+    #[test]
+    fn run_annotates_hierarchical_families() {
+        let table = Path::new("misc").join("hierarchical_families_table.txt");
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables
+            .push(table.to_str().unwrap().to_string());
+        // The parent family is only defined by its subfamilies, of which one is nested further:
+        for (seq_family_id, query_ids, parent_id) in [
+            ("SubSubFamily1", vec!["Query1"], "SubFamily1"),
+            ("SubFamily1", vec!["Query2"], "Family1"),
+            ("SubFamily2", vec!["Query3"], "Family1"),
+        ] {
+            let mut seq_family = SeqFamily::new();
+            seq_family.query_ids = query_ids.iter().map(|qid| qid.to_string()).collect();
+            seq_family.parent_id = Some(parent_id.to_string());
+            ap.insert_seq_family(seq_family_id.to_string(), seq_family);
+        }
+        let mut family_query_ids = ap.seq_families.get("Family1").unwrap().query_ids.clone();
+        family_query_ids.sort();
+        assert_eq!(vec!["Query1", "Query2", "Query3"], family_query_ids);
+        ap = run(ap);
+        let hrds = ap.human_readable_descriptions;
+        assert_eq!(
+            hrds.get("SubSubFamily1").unwrap(),
+            "receptor kinase protein xa"
+        );
+        assert_eq!(hrds.get("SubFamily2").unwrap(), "hva protein a");
+        // The parent family is described by the Hits of all its descendants:
+        assert_eq!(hrds.get("Family1").unwrap(), "receptor kinase protein xa");
+        assert_eq!(4, hrds.len());
+        assert_eq!(
+            Some(&"SubFamily1".to_string()),
+            ap.seq_family_parents.get("SubSubFamily1")
+        );
+        assert_eq!(None, ap.seq_family_parents.get("Family1"));
+    }
+
+    #[test]
+    #[should_panic(expected = "is found to be its own ancestor")]
+    fn insert_seq_family_rejects_cyclic_hierarchies() {
+        let mut ap = AnnotationProcess::new();
+        for (seq_family_id, parent_id) in [("Family1", "Family2"), ("Family2", "Family1")] {
+            let mut seq_family = SeqFamily::new();
+            seq_family.query_ids = vec![format!("{}Query", seq_family_id)];
+            seq_family.parent_id = Some(parent_id.to_string());
+            ap.insert_seq_family(seq_family_id.to_string(), seq_family);
+        }
+    }
+
+    #[test]
+    fn run_annotates_overlapping_families() {
+        let table = Path::new("misc").join("overlapping_families_table.txt");
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables
+            .push(table.to_str().unwrap().to_string());
+        for (seq_family_id, query_ids) in [
+            ("Family1", vec!["Query1", "Query2"]),
+            ("Family2", vec!["Query2", "Query3"]),
+            ("Family3", vec!["Query1"]),
+        ] {
+            let mut seq_family = SeqFamily::new();
+            seq_family.query_ids = query_ids.iter().map(|qid| qid.to_string()).collect();
+            ap.insert_seq_family(seq_family_id.to_string(), seq_family);
+        }
+        ap = run(ap);
+        let hrds = ap.human_readable_descriptions;
+        // The shared queries' Hits contribute to every family containing them:
+        assert_eq!(hrds.get("Family2").unwrap(), "hva protein a");
+        assert_eq!(hrds.get("Family3").unwrap(), "receptor kinase protein xa");
+        assert_eq!(hrds.get("Family1").unwrap(), "receptor kinase protein xa");
+        assert_eq!(3, hrds.len());
+
+        // A shared query is retained until all of its families have been annotated:
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables = vec![table.to_str().unwrap().to_string()];
+        for (seq_family_id, query_ids) in [
+            ("Family1", vec!["Query1", "Query2"]),
+            ("Family2", vec!["Query2", "Query3"]),
+        ] {
+            let mut seq_family = SeqFamily::new();
+            seq_family.query_ids = query_ids.iter().map(|qid| qid.to_string()).collect();
+            ap.insert_seq_family(seq_family_id.to_string(), seq_family);
+        }
+        ap.insert_query("Query1".to_string(), Query::new());
+        ap.insert_query("Query2".to_string(), Query::new());
+        assert!(ap.human_readable_descriptions.contains_key("Family1"));
+        assert!(!ap.human_readable_descriptions.contains_key("Family2"));
+        assert!(!ap.queries.contains_key("Query1"));
+        assert!(ap.queries.contains_key("Query2"));
+        ap.insert_query("Query3".to_string(), Query::new());
+        assert!(ap.human_readable_descriptions.contains_key("Family2"));
+        assert!(ap.queries.is_empty());
+        assert!(ap.query_id_to_seq_family_id_index.is_empty());
+    }
+
+    #[test]
+    fn run_infers_families_from_shared_hits() {
+        let table = Path::new("misc").join("shared_hits_table.txt");
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables
+            .push(table.to_str().unwrap().to_string());
+        ap.set_infer_families("default");
+        ap = run(ap);
+        let hrds = ap.human_readable_descriptions;
+        assert_eq!(hrds.get("Seq-Fam_0000001").unwrap(), "hva protein a");
+        assert_eq!(
+            Some(&vec!["Query1".to_string(), "Query2".to_string()]),
+            ap.inferred_seq_families.get("Seq-Fam_0000001")
+        );
+        // Queries sharing no Hits are not annotated without --annotate-non-family-queries:
+        assert_eq!(1, hrds.len());
     }
 
     // This test also tests the functions
@@ -1032,8 +2405,20 @@ mod tests {
         let qacc = "Soltu.DM.02G015700.1".to_string();
         let h1 = ("hit_One","sp|C0LGP4|Y3475_ARATH Probable LRR receptor-like serine/threonine-protein kinase At3g47570 OS=Arabidopsis thaliana OX=3702 GN=At3g47570 PE=2 SV=1");
         let h2 = ("hit_Two","sp|C0LGP4|Y3475_ARATH Probable LRR receptor-like serine/threonine-protein kinase At3g47570 OS=Arabidopsis thaliana OX=3702 GN=At3g47570 PE=2 SV=1");
-        nq1.hits.insert(h1.0.to_string(), h1.1.to_string());
-        nq1.hits.insert(h2.0.to_string(), h2.1.to_string());
+        nq1.hits.insert(
+            h1.0.to_string(),
+            Hit {
+                description: h1.1.to_string(),
+                ..Default::default()
+            },
+        );
+        nq1.hits.insert(
+            h2.0.to_string(),
+            Hit {
+                description: h2.1.to_string(),
+                ..Default::default()
+            },
+        );
 
         ap.insert_query(qacc.clone(), nq1);
         ap.process_rest_data();
@@ -1062,6 +2447,23 @@ mod tests {
         assert!(!ap.seq_families.contains_key(&sf_id2));
     }
 
+    #[test]
+    #[should_panic]
+    fn validate_fields_panics_on_multiple_stdin_tables() {
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables = vec!["-".to_string(), "-".to_string()];
+        ap.validate_fields();
+    }
+
+    #[test]
+    #[should_panic(expected = "is read from standard input ('-') without --header")]
+    fn validate_fields_requires_header_for_stdin_tables_with_thresholds() {
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables = vec!["-".to_string()];
+        ap.add_ssst_min_bitscore("50");
+        ap.validate_fields();
+    }
+
     #[test]
     fn run_annotates_queries() {
         let mut ap = AnnotationProcess::new();
@@ -1103,6 +2505,278 @@ mod tests {
         }
     }
 
+    #[test]
+    fn run_annotates_queries_from_blast_reports() {
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables.push(
+            Path::new("misc")
+                .join("Two_Proteins_vs_Swissprot_blastp.xml")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        ap.seq_sim_search_tables.push(
+            Path::new("misc")
+                .join("Two_Proteins_vs_Swissprot_blastp.json")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        ap.add_ssst_format("blast-xml");
+        ap.add_ssst_format("blast-json");
+        ap = run(ap);
+        let hrds = ap.human_readable_descriptions;
+        assert_eq!(hrds.len(), 2);
+        assert!(hrds.contains_key("Soltu.DM.02G015700.1"));
+        assert!(hrds.contains_key("Soltu.DM.01G045390.1"));
+    }
+
+    #[test]
+    fn run_applies_hit_thresholds_to_tabular_tables() {
+        let table = Path::new("misc").join("hit_thresholds_table.txt");
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables
+            .push(table.to_str().unwrap().to_string());
+        ap.add_ssst_columns("qacc sacc evalue pident stitle");
+        ap.add_ssst_max_evalue("1e-2");
+        ap.add_ssst_min_pident("30");
+        ap = run(ap);
+        let hrds = ap.human_readable_descriptions;
+        // Only the Xa21 Hit passes the thresholds:
+        assert_eq!(hrds.get("Query1").unwrap(), "receptor kinase protein xa");
+        assert!(!hrds.contains_key("Query2"));
+    }
+
+    #[test]
+    fn run_weights_hits_by_their_tables() {
+        let swiss_prot = Path::new("misc").join("sprot_table.txt");
+        let trembl = Path::new("misc").join("weighted_trembl_table.txt");
+        for (weights, expected) in [
+            (vec!["default", "default"], "receptor kinase protein xa"),
+            (vec!["1", "0.1"], "hva protein a"),
+        ] {
+            let mut ap = AnnotationProcess::new();
+            for table in [&swiss_prot, &trembl] {
+                ap.seq_sim_search_tables
+                    .push(table.to_str().unwrap().to_string());
+            }
+            for weight in weights {
+                ap.add_ssst_weight(weight);
+            }
+            ap = run(ap);
+            assert_eq!(
+                ap.human_readable_descriptions.get("Query1").unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn insert_query_keeps_higher_weighted_of_shared_hits() {
+        let query = |weight: f64| {
+            let mut query = Query::new();
+            query.hits.insert(
+                "P0DKH0".to_string(),
+                Hit {
+                    description: "hva protein a".to_string(),
+                    evidence_weight: Some(weight),
+                    ..Default::default()
+                },
+            );
+            query
+        };
+        for weights in [[1.0, 0.1], [0.1, 1.0]] {
+            let mut ap = AnnotationProcess::new();
+            for weight in weights {
+                ap.insert_query("Query1".to_string(), query(weight));
+            }
+            let hit = ap
+                .queries
+                .get("Query1")
+                .unwrap()
+                .hits
+                .get("P0DKH0")
+                .unwrap();
+            assert_eq!(Some(1.0), hit.evidence_weight);
+        }
+    }
+
+    #[test]
+    fn insert_query_keeps_lowest_tier_of_shared_hits() {
+        let query = |tier: usize| {
+            let mut query = Query::new();
+            query.hits.insert(
+                "P0DKH0".to_string(),
+                Hit {
+                    description: "hva protein a".to_string(),
+                    tier,
+                    ..Default::default()
+                },
+            );
+            query
+        };
+        for tiers in [[1, 2], [2, 1]] {
+            let mut ap = AnnotationProcess::new();
+            for tier in tiers {
+                ap.insert_query("Query1".to_string(), query(tier));
+            }
+            let hit = ap
+                .queries
+                .get("Query1")
+                .unwrap()
+                .hits
+                .get("P0DKH0")
+                .unwrap();
+            assert_eq!(1, hit.tier);
+        }
+    }
+
+    #[test]
+    fn run_weights_hits_shared_by_tables() {
+        // Both tables report Hit P0DKH0, which must keep the weight of the Swiss-Prot table,
+        // regardless of the order the tables are given and parsed in:
+        let swiss_prot = Path::new("misc").join("sprot_table.txt");
+        let trembl = Path::new("misc").join("shared_hits_trembl_table.txt");
+        for tables in [
+            [(&swiss_prot, "1"), (&trembl, "0.1")],
+            [(&trembl, "0.1"), (&swiss_prot, "1")],
+        ] {
+            let mut ap = AnnotationProcess::new();
+            for (table, weight) in tables {
+                ap.seq_sim_search_tables
+                    .push(table.to_str().unwrap().to_string());
+                ap.add_ssst_weight(weight);
+            }
+            ap = run(ap);
+            assert_eq!(
+                ap.human_readable_descriptions.get("Query1").unwrap(),
+                "hva protein a"
+            );
+        }
+    }
+
+    #[test]
+    fn run_annotates_in_tiers() {
+        let swiss_prot = Path::new("misc").join("sprot_table.txt");
+        let trembl = Path::new("misc").join("tiered_trembl_table.txt");
+        let new_annotation_process = || {
+            let mut ap = AnnotationProcess::new();
+            for table in [&swiss_prot, &trembl] {
+                ap.seq_sim_search_tables
+                    .push(table.to_str().unwrap().to_string());
+            }
+            ap.add_ssst_tier("default");
+            ap.add_ssst_tier("2");
+            ap
+        };
+        // Queries fall back to the second tier only if the first yields no description:
+        let ap = run(new_annotation_process());
+        let hrds = &ap.human_readable_descriptions;
+        assert_eq!(hrds.get("Query1").unwrap(), "hva protein a");
+        assert_eq!(hrds.get("Query2").unwrap(), "receptor kinase protein xa");
+        assert_eq!(Some(&1), ap.annotation_tiers.get("Query1"));
+        assert_eq!(Some(&2), ap.annotation_tiers.get("Query2"));
+        // Sequence families alike:
+        for (members, expected, tier) in [
+            (vec!["Query1", "Query2"], "hva protein a", 1),
+            (vec!["Query2", "Query3"], "receptor kinase protein xa", 2),
+        ] {
+            let mut ap = new_annotation_process();
+            let mut seq_family = SeqFamily::new();
+            seq_family.query_ids = members.iter().map(|qid| qid.to_string()).collect();
+            ap.insert_seq_family("Family1".to_string(), seq_family);
+            ap = run(ap);
+            assert_eq!(
+                ap.human_readable_descriptions.get("Family1").unwrap(),
+                expected
+            );
+            assert_eq!(Some(&tier), ap.annotation_tiers.get("Family1"));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid --table-weight")]
+    fn add_ssst_weight_rejects_non_positive_weights() {
+        AnnotationProcess::new().add_ssst_weight("0");
+    }
+
+    #[test]
+    fn run_annotates_unsorted_tables() {
+        let table = Path::new("misc").join("unsorted_table.txt");
+        for mode in ["in-memory", "on-disk"] {
+            let mut ap = AnnotationProcess::new();
+            ap.seq_sim_search_tables
+                .push(table.to_str().unwrap().to_string());
+            ap.add_ssst_columns("qacc sacc bitscore stitle");
+            ap.add_ssst_top_n_hits("1");
+            ap.set_unsorted_input(mode);
+            ap = run(ap);
+            let hrds = ap.human_readable_descriptions;
+            // The best Hit of Query1 is found in its second block of rows:
+            assert_eq!(hrds.get("Query1").unwrap(), "receptor kinase protein xa");
+            assert_eq!(hrds.get("Query2").unwrap(), "hva protein a");
+        }
+    }
+
+    #[test]
+    fn run_summarizes_malformed_rows() {
+        let table = Path::new("misc").join("truncated_blast_table.txt");
+        let table_path = table.to_str().unwrap().to_string();
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables.push(table_path.clone());
+        ap = run(ap);
+        assert_eq!(Some(&1), ap.n_malformed_rows.get(&table_path));
+        assert_eq!(
+            ap.human_readable_descriptions.get("Query1").unwrap(),
+            "receptor kinase protein xa"
+        );
+    }
+
+    #[test]
+    fn run_skips_hits_missing_in_reference_in_strict_mode() {
+        let table_path = Path::new("misc")
+            .join("Two_Queries_vs_Reference_diamond.txt")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables.push(table_path.clone());
+        ap.add_ssst_columns("qacc sacc pident length evalue bitscore");
+        ap.add_ssst_reference_fasta("misc/Two_Reference_Proteins.fasta");
+        ap.set_malformed_rows("strict");
+        ap = run(ap);
+        // The Hit of Query2 is not in the reference, which is not a malformed row:
+        assert_eq!(Some(&1), ap.n_missing_hits.get(&table_path));
+        assert!(ap.n_malformed_rows.is_empty());
+        assert!(ap.human_readable_descriptions.contains_key("Query1"));
+        assert!(!ap.human_readable_descriptions.contains_key("Query2"));
+    }
+
+    #[test]
+    #[should_panic(expected = "is malformed")]
+    fn run_fails_on_malformed_rows_in_strict_mode() {
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables.push(
+            Path::new("misc")
+                .join("truncated_blast_table.txt")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        ap.set_malformed_rows("strict");
+        run(ap);
+    }
+
+    #[test]
+    #[should_panic]
+    fn validate_fields_panics_on_threshold_without_column() {
+        let mut ap = AnnotationProcess::new();
+        ap.seq_sim_search_tables
+            .push("misc/Twelve_Proteins_vs_Swissprot_blastp.txt".to_string());
+        ap.add_ssst_min_bitscore("50");
+        ap.validate_fields();
+    }
+
     #[test]
     fn run_annotates_families() {
         let mut ap = AnnotationProcess::new();
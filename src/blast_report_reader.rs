@@ -0,0 +1,616 @@
+//! Code used to parse Blast XML (`-outfmt 5`) and Blast single file JSON (`-outfmt 15`) reports
+//! is implemented in this module. Both readers produce the same messages as
+//! `seq_sim_table_reader::parse_table`, i.e. tuples of query identifier and `Query`.
+use super::default::BLAST_GENERIC_QUERY_ID_REGEX;
+use super::hit::SourceDatabase;
+use super::hit_thresholds::HitScores;
+use super::input_reader::open_input_file;
+use super::query::Query;
+use super::seq_sim_table_reader::{insert_hit, TableOptions};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::Sender;
+
+/// A Blast Hit as found in a Blast report, i.e. the Hit's identifier and title, and the quality
+/// measures of its first (best) HSP.
+#[derive(Debug, Clone, Default)]
+struct BlastReportHit {
+    /// The Hit's identifier (`sacc` in Blast terminology).
+    sacc: String,
+    /// The Hit's title (`stitle` in Blast terminology).
+    stitle: String,
+    /// The quality measures of the Hit's best HSP.
+    scores: HitScores,
+    /// The database the Hit's sequence was taken from, as recognized from the Hit identifier.
+    /// Note that neither the accession nor the title carry the database prefix in Blast reports.
+    source_db: Option<SourceDatabase>,
+}
+
+/// Collects the Hits of consecutive Blast report entries (iterations or searches) into instances
+/// of `Query` and sends these to the registered receiver, whenever the query identifier changes.
+/// This mimics the behavior of `seq_sim_table_reader::parse_table`.
+struct QueryCollector<'a> {
+    last_qacc: String,
+    curr_query: Query,
+    options: &'a TableOptions,
+    transmitter: Sender<(String, Query)>,
+}
+
+impl<'a> QueryCollector<'a> {
+    /// Adds the argument `hits` found for the query `qacc`. If the argument `qacc` differs from the
+    /// one of the last call, the last query is send to the registered receiver.
+    fn add(&mut self, qacc: String, hits: &[BlastReportHit]) {
+        // Queries without any Hits do not appear in tabular results either:
+        if hits.is_empty() {
+            return;
+        }
+        if qacc != self.last_qacc && !self.last_qacc.is_empty() {
+            let query = std::mem::replace(&mut self.curr_query, Query::new());
+            self.options
+                .send_query(&self.transmitter, self.last_qacc.clone(), query);
+        }
+        let hit_thresholds = &self.options.hit_thresholds;
+        for hit in hits.iter().filter(|h| hit_thresholds.passes(&h.scores)) {
+            insert_hit(
+                &mut self.curr_query,
+                &hit.sacc,
+                &hit.stitle,
+                hit.scores,
+                self.options,
+            );
+            if let Some(inserted_hit) = self.curr_query.hits.get_mut(&hit.sacc) {
+                inserted_hit.source_db = inserted_hit.source_db.or(hit.source_db);
+            }
+        }
+        self.last_qacc = qacc;
+    }
+
+    /// Sends the last collected query, if any.
+    fn finish(self) {
+        if !self.last_qacc.is_empty() {
+            self.options
+                .send_query(&self.transmitter, self.last_qacc, self.curr_query);
+        }
+    }
+}
+
+/// Blast assigns generic query identifiers like `Query_1` unless `-parse_deflines` is used. In
+/// that case the query's accession is the first word of the query definition line. Returns the
+/// query accession (`qacc` in Blast terminology).
+///
+/// # Arguments
+///
+/// * `query_id: &str` - The query identifier as given in the Blast report.
+/// * `query_def: &str` - The query definition line as given in the Blast report.
+fn blast_query_accession(query_id: &str, query_def: &str) -> String {
+    let query_def_acc = query_def.split_whitespace().next().unwrap_or("");
+    if BLAST_GENERIC_QUERY_ID_REGEX.is_match(query_id.trim()) && !query_def_acc.is_empty() {
+        query_def_acc.to_string()
+    } else {
+        query_id.trim().to_string()
+    }
+}
+
+/// Creates a `BlastReportHit` from the Hit identifier, accession, and definition as given in a
+/// Blast report. If the Blast database was created without `-parse_seqids` Blast reports generic
+/// identifiers (`gnl|BL_ORD_ID|...`), in which case the first word of the Hit definition is used
+/// as the Hit's identifier.
+///
+/// # Arguments
+///
+/// * `hit_id: &str` - The Hit identifier as given in the Blast report.
+/// * `hit_accession: &str` - The Hit accession as given in the Blast report.
+/// * `hit_def: &str` - The Hit definition (title) as given in the Blast report.
+/// * `scores: HitScores` - The quality measures of the Hit's best HSP.
+fn blast_report_hit(
+    hit_id: &str,
+    hit_accession: &str,
+    hit_def: &str,
+    scores: HitScores,
+) -> BlastReportHit {
+    let sacc = if hit_id.starts_with("gnl|BL_ORD_ID|") || hit_accession.trim().is_empty() {
+        hit_def
+            .split_whitespace()
+            .next()
+            .unwrap_or(hit_id)
+            .to_string()
+    } else {
+        hit_accession.trim().to_string()
+    };
+    BlastReportHit {
+        sacc,
+        stitle: hit_def.trim().to_string(),
+        scores,
+        source_db: SourceDatabase::from_identifier(hit_id),
+    }
+}
+
+/// Computes the quality measures of a HSP from the values given in a Blast report. Percent
+/// identity (`pident`) and query coverage (`qcovhsp`) are not reported by Blast directly, but
+/// computed from the number of identical positions and the alignment length, and from the aligned
+/// query region and the query length, respectively.
+///
+/// # Arguments
+///
+/// * `evalue: f64` - The HSP's expectation value
+/// * `bitscore: f64` - The HSP's bit score
+/// * `identity: f64` - The number of identical positions in the HSP
+/// * `align_len: f64` - The length of the HSP's alignment
+/// * `query_from: f64` - The start of the HSP in the query sequence
+/// * `query_to: f64` - The end of the HSP in the query sequence
+/// * `query_len: f64` - The length of the query sequence
+fn blast_hsp_scores(
+    evalue: f64,
+    bitscore: f64,
+    identity: f64,
+    align_len: f64,
+    query_from: f64,
+    query_to: f64,
+    query_len: f64,
+) -> HitScores {
+    HitScores {
+        evalue: Some(evalue),
+        bitscore: Some(bitscore),
+        pident: if align_len > 0.0 {
+            Some(100.0 * identity / align_len)
+        } else {
+            None
+        },
+        qcovhsp: if query_len > 0.0 {
+            Some(100.0 * ((query_to - query_from).abs() + 1.0) / query_len)
+        } else {
+            None
+        },
+    }
+}
+
+/// Finds a Blast XML report (`-outfmt 5`) file (`path`) and parses it in a stream approach, i.e.
+/// element by element. Every time an instance of Query is successfully and completely parsed it
+/// is send using the argument `transmitter` to the respective registered receiver.
+///
+/// # Arguments
+///
+/// * `path: String` - The path to the Blast XML report to parse
+/// * `options: &TableOptions` - The settings to parse the report with. Note that Hits whose best
+///   HSP does not pass the `hit_thresholds` are discarded, and that descriptions are the Hits'
+///   definitions (`Hit_def`).
+/// * `transmitter: Sender<Query>` - Used to send instances of `Query` to any receiver.
+pub fn parse_blast_xml(
+    path: &String,
+    options: &TableOptions,
+    transmitter: Sender<(String, Query)>,
+) {
+    let input = open_input_file(path)
+        .unwrap_or_else(|_| panic!("An error occurred reading file {:?}", &path));
+    let mut reader = Reader::from_reader(input);
+    let mut collector = QueryCollector {
+        last_qacc: String::new(),
+        curr_query: Query::new(),
+        options,
+        transmitter,
+    };
+
+    let mut buf = Vec::new();
+    // The name of the element whose text is currently read:
+    let mut curr_element = String::new();
+    let mut query_id = String::new();
+    let mut query_def = String::new();
+    let mut hit_id = String::new();
+    let mut hit_accession = String::new();
+    let mut hit_def = String::new();
+    let mut query_len = String::new();
+    // The text values of the elements of the currently read HSP:
+    let mut hsp_values: HashMap<String, String> = HashMap::new();
+    // The quality measures of the current Hit's first, i.e. best, HSP:
+    let mut hit_scores: Option<HitScores> = None;
+    let mut iteration_hits: Vec<BlastReportHit> = vec![];
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                curr_element = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match curr_element.as_str() {
+                    "Iteration" => {
+                        query_id.clear();
+                        query_def.clear();
+                        query_len.clear();
+                        iteration_hits.clear();
+                    }
+                    "Hit" => {
+                        hit_id.clear();
+                        hit_accession.clear();
+                        hit_def.clear();
+                        hit_scores = None;
+                    }
+                    "Hsp" => hsp_values.clear(),
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let text = e
+                    .unescape()
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&e).to_string().into());
+                match curr_element.as_str() {
+                    "Iteration_query-ID" => query_id.push_str(&text),
+                    "Iteration_query-def" => query_def.push_str(&text),
+                    "Hit_id" => hit_id.push_str(&text),
+                    "Hit_accession" => hit_accession.push_str(&text),
+                    "Hit_def" => hit_def.push_str(&text),
+                    "Iteration_query-len" => query_len.push_str(&text),
+                    hsp_element if hsp_element.starts_with("Hsp_") => hsp_values
+                        .entry(hsp_element.to_string())
+                        .or_default()
+                        .push_str(&text),
+                    _ => {}
+                }
+            }
+            Ok(Event::End(e)) => {
+                match e.name().as_ref() {
+                    b"Hsp" if hit_scores.is_none() => {
+                        let value = |element: &str| -> f64 {
+                            hsp_values
+                                .get(element)
+                                .and_then(|v| v.trim().parse::<f64>().ok())
+                                .unwrap_or(f64::NAN)
+                        };
+                        hit_scores = Some(blast_hsp_scores(
+                            value("Hsp_evalue"),
+                            value("Hsp_bit-score"),
+                            value("Hsp_identity"),
+                            value("Hsp_align-len"),
+                            value("Hsp_query-from"),
+                            value("Hsp_query-to"),
+                            query_len.trim().parse::<f64>().unwrap_or(f64::NAN),
+                        ));
+                    }
+                    b"Hit" => {
+                        iteration_hits.push(blast_report_hit(
+                            &hit_id,
+                            &hit_accession,
+                            &hit_def,
+                            hit_scores.unwrap_or_default(),
+                        ));
+                    }
+                    b"Iteration" => {
+                        collector.add(
+                            blast_query_accession(&query_id, &query_def),
+                            &iteration_hits,
+                        );
+                    }
+                    _ => {}
+                }
+                curr_element.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                panic!(
+                    "\n\nAn error occurred while parsing Blast XML report {:?} at position {}:\n{:?}\n\n",
+                    path,
+                    reader.buffer_position(),
+                    e
+                );
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    // Send last parsed query:
+    collector.finish();
+}
+
+/// The parts of a Blast JSON report entry (`BlastOutput2` array element) prot-scriber uses.
+#[derive(Debug, Deserialize)]
+struct BlastJsonReport {
+    report: BlastJsonReportBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlastJsonReportBody {
+    results: BlastJsonResults,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlastJsonResults {
+    search: BlastJsonSearch,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlastJsonSearch {
+    query_id: String,
+    #[serde(default)]
+    query_title: String,
+    #[serde(default)]
+    query_len: f64,
+    #[serde(default)]
+    hits: Vec<BlastJsonHit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlastJsonHit {
+    description: Vec<BlastJsonHitDescription>,
+    #[serde(default)]
+    hsps: Vec<BlastJsonHsp>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlastJsonHsp {
+    bit_score: f64,
+    evalue: f64,
+    identity: f64,
+    align_len: f64,
+    query_from: f64,
+    query_to: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlastJsonHitDescription {
+    id: String,
+    #[serde(default)]
+    accession: String,
+    #[serde(default)]
+    title: String,
+}
+
+/// Deserializes the top level object of a Blast JSON report and hands each element of its
+/// `BlastOutput2` array to the `QueryCollector` as soon as it has been read. This avoids holding
+/// the whole report in memory.
+struct BlastJsonReportsSeed<'a, 'b> {
+    collector: &'b mut QueryCollector<'a>,
+}
+
+impl<'de, 'a, 'b> DeserializeSeed<'de> for BlastJsonReportsSeed<'a, 'b> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // The top level object is a map, the value of its `BlastOutput2` entry a sequence:
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a, 'b> Visitor<'de> for BlastJsonReportsSeed<'a, 'b> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Blast JSON report with a 'BlastOutput2' array")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "BlastOutput2" {
+                map.next_value_seed(BlastJsonReportsSeed {
+                    collector: &mut *self.collector,
+                })?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(report) = seq.next_element::<BlastJsonReport>()? {
+            let search = report.report.results.search;
+            let query_len = search.query_len;
+            let hits: Vec<BlastReportHit> = search
+                .hits
+                .iter()
+                .filter_map(|hit| hit.description.first().map(|desc| (desc, hit.hsps.first())))
+                .map(|(desc, hsp)| {
+                    let scores = hsp.map_or_else(HitScores::default, |hsp| {
+                        blast_hsp_scores(
+                            hsp.evalue,
+                            hsp.bit_score,
+                            hsp.identity,
+                            hsp.align_len,
+                            hsp.query_from,
+                            hsp.query_to,
+                            query_len,
+                        )
+                    });
+                    blast_report_hit(&desc.id, &desc.accession, &desc.title, scores)
+                })
+                .collect();
+            self.collector.add(
+                blast_query_accession(&search.query_id, &search.query_title),
+                &hits,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Finds a Blast single file JSON report (`-outfmt 15`) file (`path`) and parses it. Each entry of
+/// the report's `BlastOutput2` array is processed as soon as it has been read. Every time an
+/// instance of Query is successfully and completely parsed it is send using the argument
+/// `transmitter` to the respective registered receiver.
+///
+/// # Arguments
+///
+/// * `path: String` - The path to the Blast JSON report to parse
+/// * `options: &TableOptions` - The settings to parse the report with. Note that Hits whose best
+///   HSP does not pass the `hit_thresholds` are discarded, and that descriptions are the Hits'
+///   titles (`title`).
+/// * `transmitter: Sender<Query>` - Used to send instances of `Query` to any receiver.
+pub fn parse_blast_json(
+    path: &String,
+    options: &TableOptions,
+    transmitter: Sender<(String, Query)>,
+) {
+    let input = open_input_file(path)
+        .unwrap_or_else(|_| panic!("An error occurred reading file {:?}", &path));
+    let mut collector = QueryCollector {
+        last_qacc: String::new(),
+        curr_query: Query::new(),
+        options,
+        transmitter,
+    };
+    let mut deserializer = serde_json::Deserializer::from_reader(input);
+    if let Err(e) = (BlastJsonReportsSeed {
+        collector: &mut collector,
+    })
+    .deserialize(&mut deserializer)
+    {
+        panic!(
+            "\n\nAn error occurred while parsing Blast JSON report {:?}:\n{:?}\n\n",
+            path, e
+        );
+    }
+
+    // Send last parsed query:
+    collector.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default::{BLACKLIST_STITLE_REGEXS, FILTER_REGEXS};
+    use crate::hit_thresholds::HitThresholds;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::mpsc;
+
+    fn received_queries(rx: mpsc::Receiver<(String, Query)>) -> HashMap<String, Query> {
+        rx.iter().collect()
+    }
+
+    #[test]
+    fn query_accession_from_generic_blast_query_id() {
+        assert_eq!(
+            "Soltu.DM.02G015700.1",
+            blast_query_accession("Query_1", "Soltu.DM.02G015700.1 some protein")
+        );
+        assert_eq!(
+            "Soltu.DM.02G015700.1",
+            blast_query_accession("lcl|Query_12", "Soltu.DM.02G015700.1")
+        );
+        assert_eq!(
+            "Soltu.DM.02G015700.1",
+            blast_query_accession("Soltu.DM.02G015700.1", "some protein")
+        );
+    }
+
+    #[test]
+    fn parses_blast_xml() {
+        let (tx, rx) = mpsc::channel();
+        let p = Path::new("misc")
+            .join("Two_Proteins_vs_Swissprot_blastp.xml")
+            .to_str()
+            .unwrap()
+            .to_string();
+        parse_blast_xml(
+            &p,
+            &TableOptions {
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                ..Default::default()
+            },
+            tx,
+        );
+        let queries = received_queries(rx);
+        assert_eq!(queries.len(), 2);
+        let q1 = queries.get("Soltu.DM.02G015700.1").unwrap();
+        // One of the three Hits is blacklisted ("Probable"):
+        assert_eq!(q1.hits.len(), 2);
+        let xa21 = q1.hits.get("Q1MX30").unwrap();
+        assert_eq!(xa21.description, "receptor kinase protein xa21");
+        // UniProt header fields are parsed from the Hit definition and the Hit identifier:
+        assert_eq!(xa21.taxon_id, Some(39946));
+        assert_eq!(xa21.gene_name, Some("XA21".to_string()));
+        assert_eq!(xa21.source_db, Some(SourceDatabase::SwissProt));
+        let q2 = queries.get("Soltu.DM.01G045390.1").unwrap();
+        assert_eq!(q2.hits.len(), 1);
+        assert!(q2.hits.contains_key("sp|Q9S7V4|HA22A_ARATH"));
+    }
+
+    #[test]
+    fn parses_blast_json() {
+        let (tx, rx) = mpsc::channel();
+        let p = Path::new("misc")
+            .join("Two_Proteins_vs_Swissprot_blastp.json")
+            .to_str()
+            .unwrap()
+            .to_string();
+        parse_blast_json(
+            &p,
+            &TableOptions {
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                ..Default::default()
+            },
+            tx,
+        );
+        let queries = received_queries(rx);
+        assert_eq!(queries.len(), 2);
+        let q1 = queries.get("Soltu.DM.02G015700.1").unwrap();
+        assert_eq!(q1.hits.len(), 2);
+        assert_eq!(
+            q1.hits.get("Q1MX30").unwrap().description,
+            "receptor kinase protein xa21"
+        );
+        let q2 = queries.get("Soltu.DM.01G045390.1").unwrap();
+        assert_eq!(q2.hits.len(), 1);
+        assert!(q2.hits.contains_key("sp|Q9S7V4|HA22A_ARATH"));
+    }
+
+    #[test]
+    fn applies_hit_thresholds_to_blast_reports() {
+        let thresholds = HitThresholds {
+            max_evalue: Some(1e-100),
+            min_pident: Some(37.0),
+            ..Default::default()
+        };
+        for (file, is_xml) in [
+            ("Two_Proteins_vs_Swissprot_blastp.xml", true),
+            ("Two_Proteins_vs_Swissprot_blastp.json", false),
+        ] {
+            let (tx, rx) = mpsc::channel();
+            let p = Path::new("misc").join(file).to_str().unwrap().to_string();
+            if is_xml {
+                parse_blast_xml(
+                    &p,
+                    &TableOptions {
+                        hit_thresholds: thresholds,
+                        filter_regexs: FILTER_REGEXS.clone(),
+                        ..Default::default()
+                    },
+                    tx,
+                );
+            } else {
+                parse_blast_json(
+                    &p,
+                    &TableOptions {
+                        hit_thresholds: thresholds,
+                        filter_regexs: FILTER_REGEXS.clone(),
+                        ..Default::default()
+                    },
+                    tx,
+                );
+            }
+            let queries = received_queries(rx);
+            // Only Xa21 has more than 37% identical positions (385 of 1011):
+            let q1 = queries.get("Soltu.DM.02G015700.1").unwrap();
+            assert_eq!(q1.hits.len(), 1);
+            assert!(q1.hits.contains_key("Q1MX30"));
+            // The only Hit of the second query has an e-value of 3.7e-76:
+            assert_eq!(
+                queries
+                    .get("Soltu.DM.01G045390.1")
+                    .map_or(0, |q| q.hits.len()),
+                0
+            );
+        }
+    }
+}
@@ -0,0 +1,600 @@
+//! Code used to parse Diamond's binary alignment archives (DAA, `--outfmt 100`) is implemented
+//! in this module. The reader produces the same messages as `seq_sim_table_reader::parse_table`,
+//! i.e. tuples of query identifier and `Query`, so DAA files need not be converted with
+//! `diamond view` first.
+//!
+//! A DAA file consists of two headers, the alignment block holding one record per query, the
+//! block of reference (subject) names, and the block of reference lengths. All numbers are
+//! little endian.
+use super::hit_thresholds::HitScores;
+use super::input_reader::open_input_file;
+use super::query::Query;
+use super::seq_sim_table_reader::{insert_hit, TableOptions};
+use std::convert::TryInto;
+use std::io::{self, BufRead, Read};
+use std::sync::mpsc::Sender;
+
+/// The magic number every DAA file starts with.
+pub const DAA_MAGIC_NUMBER: u64 = 0x3c0e53476d3ee36b;
+
+/// The length of the first DAA header, i.e. the magic number and the format version.
+const DAA_HEADER1_LEN: u64 = 16;
+
+/// The maximum number of blocks a DAA file can have.
+const DAA_MAX_BLOCKS: usize = 256;
+
+/// The block type of the alignment block, i.e. of the query records.
+const DAA_ALIGNMENTS_BLOCK: u8 = 1;
+
+/// The block type of the block of reference (subject) names.
+const DAA_REF_NAMES_BLOCK: u8 = 2;
+
+/// The alignment mode of `diamond blastx`, i.e. of translated nucleotide queries.
+const DAA_BLASTX_MODE: i32 = 3;
+
+/// The alignment mode of `diamond blastp`, i.e. of protein queries.
+const DAA_BLASTP_MODE: i32 = 2;
+
+/// The fields of the second DAA header needed to read the query records.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaaHeader {
+    /// The number of letters in the reference database, used to compute e-values.
+    pub db_letters: u64,
+    /// The number of reference sequences with at least one alignment, i.e. with a name in the
+    /// reference names block.
+    pub db_seqs_used: u64,
+    /// The alignment mode, e.g. `2` for `blastp` and `3` for `blastx`.
+    pub mode: i32,
+    /// The Karlin-Altschul parameter `K` of the scoring scheme.
+    pub k: f64,
+    /// The Karlin-Altschul parameter `lambda` of the scoring scheme.
+    pub lambda: f64,
+    /// The sizes of the blocks in bytes.
+    pub block_sizes: Vec<u64>,
+    /// The types of the blocks, e.g. `1` for alignments and `2` for reference names.
+    pub block_types: Vec<u8>,
+}
+
+impl DaaHeader {
+    /// Reads both DAA headers from the argument `reader` and returns the fields of the second.
+    /// Fails if the magic number is not found.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader: &mut R` - The reader positioned at the start of the DAA file
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<DaaHeader> {
+        if read_u64(reader)? != DAA_MAGIC_NUMBER {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a Diamond DAA file, magic number not found",
+            ));
+        }
+        // The format version:
+        read_u64(reader)?;
+        // Diamond build, number of reference sequences:
+        read_u64(reader)?;
+        read_u64(reader)?;
+        let db_seqs_used = read_u64(reader)?;
+        let db_letters = read_u64(reader)?;
+        // Flags, number of query records:
+        read_u64(reader)?;
+        read_u64(reader)?;
+        let mode = read_i32(reader)?;
+        // Gap open, gap extend, reward, penalty, and three reserved fields:
+        for _ in 0..7 {
+            read_i32(reader)?;
+        }
+        let k = read_f64(reader)?;
+        let lambda = read_f64(reader)?;
+        // The e-value threshold and a reserved field:
+        read_f64(reader)?;
+        read_f64(reader)?;
+        // The name of the score matrix:
+        read_bytes(reader, 16)?;
+        let block_sizes = (0..DAA_MAX_BLOCKS)
+            .map(|_| read_u64(reader))
+            .collect::<io::Result<Vec<u64>>>()?;
+        let block_types = read_bytes(reader, DAA_MAX_BLOCKS)?;
+        Ok(DaaHeader {
+            db_letters,
+            db_seqs_used,
+            mode,
+            k,
+            lambda,
+            block_sizes,
+            block_types,
+        })
+    }
+
+    /// Returns the offset of the first block of the argument `block_type` relative to the end of
+    /// the headers, if there is such a block.
+    fn block_offset(&self, block_type: u8) -> Option<u64> {
+        let i = self.block_types.iter().position(|t| *t == block_type)?;
+        Some(self.block_sizes[..i].iter().sum())
+    }
+
+    /// Returns the size in bytes of the first block of the argument `block_type`, if there is
+    /// such a block.
+    fn block_size(&self, block_type: u8) -> Option<u64> {
+        let i = self.block_types.iter().position(|t| *t == block_type)?;
+        Some(self.block_sizes[i])
+    }
+
+    /// Converts the argument `raw_score` of an alignment into its bit score.
+    pub fn bitscore(&self, raw_score: u32) -> f64 {
+        (self.lambda * raw_score as f64 - self.k.ln()) / 2_f64.ln()
+    }
+
+    /// Computes the e-value of the argument `bitscore` for a query of the argument `query_len`
+    /// letters (amino acids) searched against the reference database.
+    pub fn evalue(&self, bitscore: f64, query_len: u32) -> f64 {
+        self.db_letters as f64 * query_len as f64 * 2_f64.powf(-bitscore)
+    }
+}
+
+/// An alignment of a query against a reference (subject) sequence as found in a DAA query
+/// record, reduced to what is needed to compute the Hit's quality measures.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DaaAlignment {
+    /// The index of the subject in the reference names block.
+    pub subject_id: u32,
+    /// The raw alignment score.
+    pub raw_score: u32,
+    /// The number of alignment columns.
+    pub length: u32,
+    /// The number of identical positions.
+    pub identities: u32,
+    /// The number of query letters (amino acids) covered by the alignment.
+    pub query_span: u32,
+}
+
+/// Parses the argument `record` of a DAA file, i.e. a query record without its leading size,
+/// into the query name and its alignments. The alignments' edit transcripts are reduced to their
+/// numbers of columns, identities, and covered query letters. Returns `None` if the record is
+/// truncated.
+///
+/// # Arguments
+///
+/// * `record: &[u8]` - The bytes of the query record
+/// * `mode: i32` - The alignment mode of the DAA file (see `DaaHeader::mode`)
+pub fn parse_query_record(record: &[u8], mode: i32) -> Option<(String, u32, Vec<DaaAlignment>)> {
+    let mut pos: usize = 0;
+    let query_len = u32::from_le_bytes(record.get(0..4)?.try_into().ok()?);
+    pos += 4;
+    let name_len = record[pos..].iter().position(|b| *b == 0)?;
+    let query_name = String::from_utf8_lossy(&record[pos..pos + name_len]).to_string();
+    pos += name_len + 1;
+    let flags = *record.get(pos)?;
+    pos += 1;
+    // Skip the packed query sequence, five bits per amino acid, and two or, if it has `N`s,
+    // three bits per nucleotide:
+    let bits_per_letter = match (mode, flags & 1) {
+        (DAA_BLASTP_MODE, _) => 5,
+        (_, 1) => 3,
+        _ => 2,
+    };
+    pos += (query_len as usize * bits_per_letter).div_ceil(8);
+    let mut alignments = vec![];
+    while pos < record.len() {
+        let subject_id = u32::from_le_bytes(record.get(pos..pos + 4)?.try_into().ok()?);
+        pos += 4;
+        let flag = *record.get(pos)?;
+        pos += 1;
+        let raw_score = read_packed(record, &mut pos, flag & 3)?;
+        // Query and subject start:
+        read_packed(record, &mut pos, (flag >> 2) & 3)?;
+        read_packed(record, &mut pos, (flag >> 4) & 3)?;
+        let mut alignment = DaaAlignment {
+            subject_id,
+            raw_score,
+            ..Default::default()
+        };
+        // The edit transcript is terminated by a zero byte. Each operation has its type in the
+        // upper two bits and its count (matches and insertions) or a subject letter (deletions
+        // and substitutions) in the lower six bits:
+        loop {
+            let operation = *record.get(pos)?;
+            pos += 1;
+            if operation == 0 {
+                break;
+            }
+            let count = (operation & 63) as u32;
+            match operation >> 6 {
+                // Match:
+                0 => {
+                    alignment.length += count;
+                    alignment.identities += count;
+                    alignment.query_span += count;
+                }
+                // Insertion, i.e. a gap in the subject:
+                1 => {
+                    alignment.length += count;
+                    alignment.query_span += count;
+                }
+                // Deletion, i.e. a gap in the query:
+                2 => alignment.length += 1,
+                // Substitution:
+                _ => {
+                    alignment.length += 1;
+                    alignment.query_span += 1;
+                }
+            }
+        }
+        alignments.push(alignment);
+    }
+    Some((query_name, query_len, alignments))
+}
+
+/// Reads a number stored in the argument `kind` of packed integer, i.e. `0` for `u8`, `1` for
+/// `u16`, and `2` for `u32`, from the argument `record` at the argument position `pos`, and
+/// advances the latter.
+fn read_packed(record: &[u8], pos: &mut usize, kind: u8) -> Option<u32> {
+    let n_bytes = match kind {
+        0 => 1,
+        1 => 2,
+        _ => 4,
+    };
+    let mut bytes = [0u8; 4];
+    bytes[..n_bytes].copy_from_slice(record.get(*pos..*pos + n_bytes)?);
+    *pos += n_bytes;
+    Some(u32::from_le_bytes(bytes))
+}
+
+/// Reads the names of the reference (subject) sequences of the DAA file found at the argument
+/// `path`, whose headers are the argument `header`. The alignment block is skipped, so
+/// compressed DAA files can be read, too. Reading is confined to the reference names block, whose
+/// size bounds the number of names, so a corrupt header cannot cause huge allocations.
+fn read_ref_names(path: &str, header: &DaaHeader) -> io::Result<Vec<String>> {
+    let mut reader = open_input_file(path)?;
+    let no_ref_names_block =
+        || io::Error::new(io::ErrorKind::InvalidData, "no reference names block found");
+    let ref_names_offset = header
+        .block_offset(DAA_REF_NAMES_BLOCK)
+        .ok_or_else(no_ref_names_block)?;
+    let ref_names_len = header
+        .block_size(DAA_REF_NAMES_BLOCK)
+        .ok_or_else(no_ref_names_block)?;
+    // Each name is terminated by a zero byte:
+    if header.db_seqs_used > ref_names_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "more reference names are used than fit into the reference names block",
+        ));
+    }
+    let headers_len = DAA_HEADER1_LEN + header_len();
+    io::copy(
+        &mut (&mut reader).take(headers_len + ref_names_offset),
+        &mut io::sink(),
+    )?;
+    let mut ref_names_block = reader.take(ref_names_len);
+    let mut ref_names = vec![];
+    for _ in 0..header.db_seqs_used {
+        let mut name = vec![];
+        ref_names_block.read_until(0, &mut name)?;
+        if name.pop() != Some(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "reference names block is truncated",
+            ));
+        }
+        ref_names.push(String::from_utf8_lossy(&name).to_string());
+    }
+    Ok(ref_names)
+}
+
+/// Returns the length of the second DAA header in bytes.
+fn header_len() -> u64 {
+    // Six `u64` and eight `i32` fields, four `f64` fields, the score matrix name, and the block
+    // sizes and types:
+    (6 * 8 + 8 * 4 + 4 * 8 + 16 + DAA_MAX_BLOCKS * 8 + DAA_MAX_BLOCKS) as u64
+}
+
+/// Finds a Diamond DAA file (`path`) and parses it query record by query record. The first
+/// (best) alignment of each subject is used to compute the Hit's quality measures: the bit score
+/// and e-value from the raw score (see `DaaHeader::bitscore` and `DaaHeader::evalue`), the
+/// percentage of identical positions (`pident`), and the percentage of the query covered by the
+/// alignment (`qcovhsp`). The first word of the reference name is used as Hit identifier (`sacc`)
+/// and the whole name as Hit title (`stitle`). Every time an instance of Query is completely
+/// parsed it is send using the argument `transmitter` to the respective registered receiver.
+///
+/// # Arguments
+///
+/// * `path: String` - The path to the DAA file to parse
+/// * `options: &TableOptions` - The settings to parse the DAA file with. Note that Hits whose best
+///   alignment does not pass the `hit_thresholds` are discarded.
+/// * `transmitter: Sender<Query>` - Used to send instances of `Query` to any receiver.
+pub fn parse_daa(path: &String, options: &TableOptions, transmitter: Sender<(String, Query)>) {
+    let mut reader = open_input_file(path).unwrap_or_else(|e| daa_error(path, e));
+    let header = DaaHeader::read(&mut reader).unwrap_or_else(|e| daa_error(path, e));
+    if header.block_offset(DAA_ALIGNMENTS_BLOCK) != Some(0) {
+        daa_error(
+            path,
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the alignment block is not the first block",
+            ),
+        );
+    }
+    let ref_names = read_ref_names(path, &header).unwrap_or_else(|e| daa_error(path, e));
+    let truncated_record = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "query record is truncated or refers to an unknown reference",
+        )
+    };
+
+    // Query records are preceded by their size, a size of zero terminates the alignment block:
+    loop {
+        let record_len = read_u32(&mut reader).unwrap_or_else(|e| daa_error(path, e));
+        if record_len == 0 {
+            break;
+        }
+        let record =
+            read_bytes(&mut reader, record_len as usize).unwrap_or_else(|e| daa_error(path, e));
+        let (query_name, query_len, alignments) = parse_query_record(&record, header.mode)
+            .unwrap_or_else(|| daa_error(path, truncated_record()));
+        // Translated queries are given in nucleotides:
+        let query_aa_len = if header.mode == DAA_BLASTX_MODE {
+            query_len / 3
+        } else {
+            query_len
+        };
+        let mut query = Query::new();
+        for alignment in alignments {
+            let ref_name = ref_names
+                .get(alignment.subject_id as usize)
+                .unwrap_or_else(|| daa_error(path, truncated_record()));
+            let sacc = ref_name.split_whitespace().next().unwrap_or("");
+            let bitscore = header.bitscore(alignment.raw_score);
+            let scores = HitScores {
+                evalue: Some(header.evalue(bitscore, query_aa_len)),
+                bitscore: Some(bitscore),
+                pident: Some(100.0 * alignment.identities as f64 / alignment.length.max(1) as f64),
+                qcovhsp: Some(
+                    (100.0 * alignment.query_span as f64 / query_aa_len.max(1) as f64).min(100.0),
+                ),
+            };
+            if options.hit_thresholds.passes(&scores) {
+                insert_hit(&mut query, sacc, ref_name, scores, options);
+            }
+        }
+        let qacc = query_name
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_string();
+        options.send_query(&transmitter, qacc, query);
+    }
+}
+
+/// Panics with the argument `error` that occurred while parsing the DAA file found at the
+/// argument `path`.
+fn daa_error(path: &str, error: io::Error) -> ! {
+    panic!(
+        "\n\nAn error occurred while parsing Diamond DAA file {:?}:\n{:?}\n\n",
+        path, error
+    )
+}
+
+/// Reads a little endian `u64` from the argument `reader`.
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads a little endian `u32` from the argument `reader`.
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Reads a little endian `i32` from the argument `reader`.
+fn read_i32<R: Read>(reader: &mut R) -> io::Result<i32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(i32::from_le_bytes(bytes))
+}
+
+/// Reads a little endian `f64` from the argument `reader`.
+fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+/// Reads the argument `n` bytes from the argument `reader`.
+fn read_bytes<R: Read>(reader: &mut R, n: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; n];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default::{BLACKLIST_STITLE_REGEXS, FILTER_REGEXS};
+    use crate::hit_thresholds::HitThresholds;
+    use std::path::Path;
+    use std::sync::mpsc::channel;
+
+    /// Writes a `blastp` DAA file with two query records. `Query1` is aligned against both
+    /// references, `Query2` against the second one only.
+    fn write_test_daa(path: &Path) {
+        let ref_names = [
+            "sp|Q1MX30|XA21_ORYSI Receptor kinase-like protein Xa21 OS=Oryza sativa subsp. indica OX=39946 GN=XA21 PE=1 SV=1",
+            "sp|P15538|C11B1_HUMAN Cytochrome P450 11B1, mitochondrial OS=Homo sapiens OX=9606 GN=CYP11B1 PE=1 SV=5",
+        ];
+        let mut records: Vec<u8> = vec![];
+        for (query_name, query_len, alignments) in [
+            // 90 matches, a substitution, and 9 matches, i.e. pident 99 and qcovhsp 100 / 200.
+            // At most 63 matches fit into a single operation:
+            (
+                "Query1 first query",
+                200u32,
+                vec![
+                    (0u32, 1000u32, vec![63u8, 27, 3 << 6 | 1, 9]),
+                    (1, 100, vec![50]),
+                ],
+            ),
+            // 40 matches, a gap of two in the subject, 10 matches, and a gap in the query:
+            (
+                "Query2",
+                100,
+                vec![(1, 300, vec![40, 1 << 6 | 2, 10, 2 << 6 | 1])],
+            ),
+        ] {
+            let mut record: Vec<u8> = vec![];
+            record.extend(query_len.to_le_bytes());
+            record.extend(query_name.as_bytes());
+            record.push(0);
+            record.push(0);
+            record.extend(vec![0u8; (query_len as usize * 5).div_ceil(8)]);
+            for (subject_id, raw_score, transcript) in alignments {
+                record.extend(subject_id.to_le_bytes());
+                // Score as `u16`, query and subject start as `u8`:
+                record.push(1);
+                record.extend((raw_score as u16).to_le_bytes());
+                record.push(0);
+                record.push(0);
+                record.extend(transcript);
+                record.push(0);
+            }
+            records.extend((record.len() as u32).to_le_bytes());
+            records.extend(record);
+        }
+        records.extend(0u32.to_le_bytes());
+        let ref_names_block: Vec<u8> = ref_names
+            .iter()
+            .flat_map(|name| name.bytes().chain(std::iter::once(0)))
+            .collect();
+
+        let mut daa: Vec<u8> = vec![];
+        daa.extend(DAA_MAGIC_NUMBER.to_le_bytes());
+        daa.extend(1u64.to_le_bytes());
+        // Diamond build, number of reference sequences, those used, and letters, flags, and
+        // number of query records:
+        for n in [2000u64, 10, 2, 1_000_000, 0, 2] {
+            daa.extend(n.to_le_bytes());
+        }
+        // Mode, gap open, gap extend, reward, penalty, reserved:
+        for n in [DAA_BLASTP_MODE, 11, 1, 0, 0, 0, 0, 0] {
+            daa.extend(n.to_le_bytes());
+        }
+        // K, lambda, e-value threshold, reserved:
+        for x in [0.041, 0.267, 0.001, 0.0] {
+            daa.extend(f64::to_le_bytes(x));
+        }
+        let mut score_matrix = b"blosum62".to_vec();
+        score_matrix.resize(16, 0);
+        daa.extend(score_matrix);
+        let mut block_sizes = [0u64; DAA_MAX_BLOCKS];
+        block_sizes[0] = records.len() as u64;
+        block_sizes[1] = ref_names_block.len() as u64;
+        block_sizes[2] = 8;
+        for size in block_sizes {
+            daa.extend(size.to_le_bytes());
+        }
+        let mut block_types = [0u8; DAA_MAX_BLOCKS];
+        block_types[..3].copy_from_slice(&[1, 2, 3]);
+        daa.extend(block_types);
+        assert_eq!((DAA_HEADER1_LEN + header_len()) as usize, daa.len());
+        daa.extend(records);
+        daa.extend(ref_names_block);
+        daa.extend(1025u32.to_le_bytes());
+        daa.extend(503u32.to_le_bytes());
+        std::fs::write(path, daa).unwrap();
+    }
+
+    #[test]
+    fn parses_diamond_daa_files() {
+        let path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_daa_reader_test.daa",
+            std::process::id()
+        ));
+        write_test_daa(&path);
+        let (tx, rx) = channel();
+        parse_daa(
+            &path.to_str().unwrap().to_string(),
+            &TableOptions {
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                ..Default::default()
+            },
+            tx,
+        );
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        assert_eq!(2, queries.len());
+        assert_eq!("Query1", queries[0].0);
+        assert_eq!(2, queries[0].1.hits.len());
+        let xa21 = &queries[0].1.hits["sp|Q1MX30|XA21_ORYSI"];
+        assert_eq!("receptor kinase protein xa21", xa21.description);
+        assert_eq!(Some(39946), xa21.taxon_id);
+        let bitscore = (0.267 * 1000.0 - 0.041_f64.ln()) / 2_f64.ln();
+        assert_eq!(Some(bitscore), xa21.scores.bitscore);
+        assert_eq!(
+            Some(1_000_000.0 * 200.0 * 2_f64.powf(-bitscore)),
+            xa21.scores.evalue
+        );
+        assert_eq!(Some(99.0), xa21.scores.pident);
+        assert_eq!(Some(50.0), xa21.scores.qcovhsp);
+        assert_eq!("Query2", queries[1].0);
+        let cyp = &queries[1].1.hits["sp|P15538|C11B1_HUMAN"];
+        // 53 alignment columns, 50 identities, and 52 query letters:
+        assert_eq!(Some(100.0 * 50.0 / 53.0), cyp.scores.pident);
+        assert_eq!(Some(52.0), cyp.scores.qcovhsp);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn applies_hit_thresholds_to_daa_alignments() {
+        let path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_daa_reader_thresholds_test.daa",
+            std::process::id()
+        ));
+        write_test_daa(&path);
+        let (tx, rx) = channel();
+        parse_daa(
+            &path.to_str().unwrap().to_string(),
+            &TableOptions {
+                hit_thresholds: HitThresholds {
+                    min_qcovhsp: Some(50.0),
+                    ..Default::default()
+                },
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                ..Default::default()
+            },
+            tx,
+        );
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        let mut retained: Vec<&String> = queries[0].1.hits.keys().collect();
+        retained.sort();
+        assert_eq!(vec!["sp|Q1MX30|XA21_ORYSI"], retained);
+        assert_eq!(2, queries.len());
+        assert_eq!(1, queries[1].1.hits.len());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn confines_reference_names_to_their_block() {
+        let path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_daa_reader_corrupt_header_test.daa",
+            std::process::id()
+        ));
+        write_test_daa(&path);
+        let path_str = path.to_str().unwrap();
+        let mut header = DaaHeader::read(&mut open_input_file(path_str).unwrap()).unwrap();
+        assert_eq!(2, read_ref_names(path_str, &header).unwrap().len());
+        // A corrupt header is not trusted to allocate the names:
+        header.db_seqs_used = u64::MAX;
+        let error = read_ref_names(path_str, &header).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        // Names are not read beyond their block:
+        header.db_seqs_used = 3;
+        let error = read_ref_names(path_str, &header).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -7,6 +7,10 @@ lazy_static! {
     /// The score assigned to non informative words:
     pub static ref NON_INFORMATIVE_WORD_SCORE : f64 = 0.000001;
 
+    /// The minimum weight of a Hit description, assigned when weighting Hits by their quality
+    /// (see `hit::weighted_descriptions`), but the respective quality measure is missing:
+    pub static ref MIN_HIT_WEIGHT : f64 = 0.000001;
+
     /// The default argument `AnnotationProcess.center_iic_at_quantile` to be used for centering
     /// the inverse information content values of words. The literal 50.0 indicates centering at
     /// the mean and not actually a quantile:
@@ -84,6 +88,67 @@ lazy_static! {
         h
     };
 
+    /// The column positions in HMMER per-sequence hit tables (`--tblout`). The free-text
+    /// description of the target is the last column and may contain whitespace. E-value and bit
+    /// score are the ones of the full sequence.
+    pub static ref HMMER_TBLOUT_COLUMNS: HashMap<String, usize> = {
+        let mut h = HashMap::new();
+        h.insert("target_name".to_string(), 0);
+        h.insert("sacc".to_string(), 1);
+        h.insert("qacc".to_string(), 2);
+        h.insert("evalue".to_string(), 4);
+        h.insert("bitscore".to_string(), 5);
+        h.insert("stitle".to_string(), 18);
+        h
+    };
+
+    /// The column positions in HMMER per-domain hit tables (`--domtblout`). The free-text
+    /// description of the target is the last column and may contain whitespace. E-value and bit
+    /// score are the ones of the full sequence.
+    pub static ref HMMER_DOMTBLOUT_COLUMNS: HashMap<String, usize> = {
+        let mut h = HashMap::new();
+        h.insert("target_name".to_string(), 0);
+        h.insert("sacc".to_string(), 1);
+        h.insert("qacc".to_string(), 3);
+        h.insert("evalue".to_string(), 6);
+        h.insert("bitscore".to_string(), 7);
+        h.insert("stitle".to_string(), 22);
+        h
+    };
+
+    /// Blast tabular output with comment lines (`-outfmt 7`) names its columns in a comment line
+    /// starting with this prefix:
+    pub static ref BLAST_FIELDS_COMMENT_PREFIX: &'static str = "# Fields:";
+
+    /// Maps the column labels used in the Blast `# Fields:` comment line to the column names
+    /// prot-scriber uses. If more than one label maps to the same column name, the first one
+    /// present in the comment line, in the order given here, is used:
+    pub static ref BLAST_FIELDS_COMMENT_LABELS: Vec<(&'static str, &'static str)> = vec![
+        ("query acc.", "qacc"),
+        ("query acc.ver", "qacc"),
+        ("query id", "qacc"),
+        ("subject acc.", "sacc"),
+        ("subject acc.ver", "sacc"),
+        ("subject id", "sacc"),
+        ("subject title", "stitle"),
+        ("evalue", "evalue"),
+        ("bit score", "bitscore"),
+        ("% identity", "pident"),
+        ("% query coverage per hsp", "qcovhsp"),
+    ];
+
+    /// Matches the keys of the fields following the description in UniProt FASTA headers, e.g.
+    /// `OS=Homo sapiens`, and in UniRef FASTA headers, e.g. `TaxID=9606`. The key is captured:
+    pub static ref UNIPROT_HEADER_FIELD_KEY_REGEX: Regex =
+        Regex::new(r"\s(OS|OX|GN|PE|SV|n|Tax|TaxID|RepID)=").unwrap();
+
+    /// HMMER reports this value for missing accessions and descriptions:
+    pub static ref HMMER_MISSING_VALUE: &'static str = "-";
+
+    /// Blast assigns generic query identifiers matching this regular expression unless the
+    /// option `-parse_deflines` is used:
+    pub static ref BLAST_GENERIC_QUERY_ID_REGEX: Regex = Regex::new(r"^(lcl\|)?Query_\d+$").unwrap();
+
     /// A Hit's description is split into words using this default regular expression.
     pub static ref SPLIT_DESCRIPTION_REGEX: Regex = Regex::new(r"([()~_\-/|\\;,':.\s]+)").unwrap();
 
@@ -151,6 +216,35 @@ lazy_static! {
     /// `REPLACE_REGEXS_DESCRIPTION`):
     pub static ref MAX_MATCH_REPLACE_ITERATIONS: u8 = u8::MAX;
 
+    /// The path argument that indicates to read input from standard input (STDIN) instead of a
+    /// file:
+    pub static ref STDIN_INPUT_PATH: &'static str = "-";
+
+    /// The magic bytes gzip compressed files start with. Note that block gzip (bgzip) compressed
+    /// files are series of gzip members and thus start with the same bytes:
+    pub static ref GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+
+    /// The magic bytes zstd compressed files start with:
+    pub static ref ZSTD_MAGIC_BYTES: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+    /// The number of temporary files the queries of a single unsorted sequence similarity search
+    /// result table are partitioned into, when grouping them on disk (see `unsorted_input`):
+    pub static ref UNSORTED_INPUT_PARTITIONS: usize = 64;
+
+    /// The evidence weights (see `hit::EvidenceWeights`) used, when `uniprot` is passed as
+    /// argument `--evidence-weights`. Reviewed Swiss-Prot entries and entries with evidence at
+    /// protein level are preferred:
+    pub static ref UNIPROT_EVIDENCE_WEIGHTS: &'static str =
+        "sp=1.0,tr=0.5,uniref=0.5,other=1.0,pe1=1.0,pe2=0.8,pe3=0.6,pe4=0.4,pe5=0.2,pe-missing=1.0";
+
+    /// The default weight of a sequence similarity search result table, i.e. of all of its Hits
+    /// (see `--table-weight`):
+    pub static ref SSST_WEIGHT: f64 = 1.0;
+
+    /// The default priority tier of a sequence similarity search result table in tiered annotation
+    /// (see `--table-tier`):
+    pub static ref SSST_TIER: usize = 1;
+
     /// Default sequence similarity search result table field separator:
     pub static ref SSSR_TABLE_FIELD_SEPARATOR: char = '\t';
 
@@ -168,4 +262,20 @@ lazy_static! {
     /// The default character used to split gene-family-identifiers from the set of genes the
     /// respective family is comprised of:
     pub static ref SPLIT_GENE_FAMILY_ID_FROM_GENE_SET: &'static str = "\t";
+
+    /// The default prefix of the identifiers generated for the clusters of MCL output (see
+    /// `seq_family_reader::SeqFamilyFormat::Mcl`):
+    pub static ref SEQ_FAMILY_ID_PREFIX: &'static str = "Seq-Fam_";
+
+    /// The default number of digits the index in identifiers generated for the clusters of MCL
+    /// output is zero-padded to, e.g. `Seq-Fam_0000001`:
+    pub static ref SEQ_FAMILY_ID_DIGITS: usize = 7;
+
+    /// The default minimum Jaccard similarity of two queries' Hits to link them into the same
+    /// inferred sequence family (see `--infer-families`):
+    pub static ref INFER_SEQ_FAMILIES_MIN_JACCARD: f64 = 0.5;
+
+    /// The default maximum number of queries a Hit is used to link into inferred sequence
+    /// families. Hits of more queries are ignored (see `--infer-families-max-queries-per-hit`):
+    pub static ref INFER_SEQ_FAMILIES_MAX_QUERIES_PER_HIT: usize = 1000;
 }
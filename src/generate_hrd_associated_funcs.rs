@@ -5,7 +5,9 @@ use statrs::statistics::{Data, Distribution, OrderStatistics};
 use std::cmp::Ordering::Less;
 use std::collections::HashMap;
 
-/// Main function for generating human-readable descriptions (hrds).
+/// Main function for generating human-readable descriptions (hrds). All descriptions are weighted
+/// equally, i.e. this is `generate_weighted_human_readable_description` with all weights equal to
+/// one.
 ///
 /// # TODO
 ///
@@ -29,78 +31,145 @@ pub fn generate_human_readable_description(
     non_informative_words_regexs: &Vec<Regex>,
     center_at_quantile: &f64,
 ) -> Option<String> {
-    // Initialize default result:
-    let mut human_readable_rescription_result: Option<String> = None;
+    // Every description contributes one to the word frequencies:
+    let weighted_descriptions: Vec<(String, f64)> = descriptions
+        .iter()
+        .map(|desc| (desc.clone(), 1.0))
+        .collect();
+    generate_weighted_human_readable_description(
+        &weighted_descriptions,
+        None,
+        split_regex,
+        non_informative_words_regexs,
+        center_at_quantile,
+    )
+}
 
-    if descriptions.len() > 0 {
-        // Split the descriptions into vectors of words:
-        let description_words: Vec<Vec<String>> = descriptions
-            .iter()
-            .map(|dsc| split_descriptions(dsc, split_regex))
-            .collect();
-
-        // The universe of informative words, maintaining the word-frequencies:
-        let mut informative_words_universe: Vec<String> = vec![];
-        for desc_words in &description_words {
-            for word in desc_words {
-                // Build the word universe for later calculation of word-frequencies, but only consider
-                // words that are not classified as non-informative. Note that if a word already is
-                // contained in the universe, it has passed the blacklist in a past iteration, so we
-                // don't need to check again:
-                if informative_words_universe.contains(&word)
-                    || !matches_blacklist(&word, non_informative_words_regexs)
-                {
-                    informative_words_universe.push(word.clone());
-                }
+/// Generates a human-readable description (hrd) from weighted Hit descriptions. Each word of a
+/// description contributes the description's weight, instead of one, to the word frequencies and
+/// thus to the centered inverse information content of the words (see `weighted_frequencies`). If
+/// the argument `phrase_weights` are given, they also scale the score of each description's
+/// highest scoring phrase in the final choice among the phrases (see
+/// `highest_scoring_description_phrase`).
+///
+/// # Arguments
+///
+/// * `descriptions: &[(String, f64)]` - A slice of tuples holding all Hit descriptions and
+///   their respective weights.
+/// * `phrase_weights: Option<&[f64]>` - The weight of each description's phrase, e.g. the Hit's
+///   evidence weight (see `hit::EvidenceWeights`), in the order of the argument `descriptions`.
+/// * `split_regex` - The regular expression used to split descriptions (parsed `stitle`) into
+///   vectors of words (`String`).
+/// * `non_informative_words_regexs` - A reference to a vector holding regular expressions used to
+///   identify non informative words, that receive only a minimum score.
+/// * `center_at_quantile` - A real value between zero and one used to center the inverse
+///   information content scores.
+pub fn generate_weighted_human_readable_description(
+    descriptions: &[(String, f64)],
+    phrase_weights: Option<&[f64]>,
+    split_regex: &Regex,
+    non_informative_words_regexs: &Vec<Regex>,
+    center_at_quantile: &f64,
+) -> Option<String> {
+    if descriptions.is_empty() {
+        return None;
+    }
+    // Split the descriptions into vectors of words:
+    let description_words: Vec<Vec<String>> = descriptions
+        .iter()
+        .map(|(dsc, _)| split_descriptions(dsc, split_regex))
+        .collect();
+
+    // The universe of informative words, each accompanied by the weight of the description it
+    // appears in. Note that if a word already is contained in the universe, it has passed the
+    // blacklist in a past iteration, so we don't need to check again:
+    let mut informative_words_universe: Vec<(String, f64)> = vec![];
+    for (desc_words, (_, desc_weight)) in description_words.iter().zip(descriptions.iter()) {
+        for word in desc_words {
+            if informative_words_universe.iter().any(|(w, _)| w == word)
+                || !matches_blacklist(word, non_informative_words_regexs)
+            {
+                informative_words_universe.push((word.clone(), *desc_weight));
             }
         }
+    }
+    if informative_words_universe.is_empty() {
+        return None;
+    }
+    let word_frequencies = weighted_frequencies(&informative_words_universe);
+    highest_scoring_description_phrase(
+        &description_words,
+        phrase_weights,
+        &word_frequencies,
+        center_at_quantile,
+    )
+}
 
-        // Only continue with the process of generating a human readable description if at least a
-        // single informative word has been found:
-        if informative_words_universe.len() > 0 {
-            // Calculate the frequency of the informative universe words:
-            let word_frequencies = frequencies(&informative_words_universe);
-            let ciic: HashMap<String, f64> =
-                centered_inverse_information_content(&word_frequencies, center_at_quantile);
-
-            // Find highest scoring phrase
-            let mut phrases: Vec<(Vec<String>, f64)> = vec![];
-
-            for desc in &description_words {
-                let hsp_option = highest_scoring_phrase(&desc, &ciic);
-                match hsp_option {
-                    Some(hsp) => {
-                        if !phrases.contains(&hsp) {
-                            phrases.push(hsp);
-                        }
-                    }
-                    None => {}
-                }
-            }
-            if phrases.len() > 0 {
-                let mut high_score_ind: usize = 0;
-                for i in 0..phrases.len() {
-                    if phrases[i].1 > phrases[high_score_ind].1 {
-                        high_score_ind = i;
-                    // In case the two phrases receive an equal score, use the
-                    // phrase that alphabetically comes before the other to ensure a
-                    // reproducible behavior of prot-scriber
-                    } else if phrases[i].1 == phrases[high_score_ind].1
-                        && phrases[i]
-                            .0
-                            .join(" ")
-                            .cmp(&phrases[high_score_ind].0.join(" "))
-                            == Less
-                    {
-                        high_score_ind = i;
-                    }
-                }
+/// Generates a human-readable description (hrd) from the argument `description_words`, i.e. Hit
+/// descriptions split into words, and the frequencies of the informative words among them. The
+/// words are scored by their centered inverse information content, and the highest scoring phrase
+/// of all descriptions is returned. If the argument `phrase_weights` are given, a description's
+/// phrase score is multiplied with the description's weight, if it is positive, and divided by
+/// it otherwise. So a phrase of a higher weighted description is preferred either way.
+///
+/// # Arguments
+///
+/// * `description_words: &[Vec<String>]` - The Hit descriptions, each split into words.
+/// * `phrase_weights: Option<&[f64]>` - The positive weight of each description, if any.
+/// * `word_frequencies: &HashMap<String, f64>` - The (weighted) frequencies of the informative
+///   words.
+/// * `center_at_quantile` - A real value between zero and one used to center the inverse
+///   information content scores.
+fn highest_scoring_description_phrase(
+    description_words: &[Vec<String>],
+    phrase_weights: Option<&[f64]>,
+    word_frequencies: &HashMap<String, f64>,
+    center_at_quantile: &f64,
+) -> Option<String> {
+    // Initialize default result:
+    let mut human_readable_rescription_result: Option<String> = None;
+    let ciic: HashMap<String, f64> =
+        centered_inverse_information_content(word_frequencies, center_at_quantile);
+
+    // Find highest scoring phrase
+    let mut phrases: Vec<(Vec<String>, f64)> = vec![];
 
-                let human_readable_description: String = phrases[high_score_ind].0.join(" ");
-                human_readable_rescription_result = Some(human_readable_description);
+    for (k, desc) in description_words.iter().enumerate() {
+        if let Some(mut hsp) = highest_scoring_phrase(desc, &ciic) {
+            if let Some(weight) = phrase_weights.map(|weights| weights[k]) {
+                hsp.1 = if hsp.1 > 0.0 {
+                    hsp.1 * weight
+                } else {
+                    hsp.1 / weight
+                };
+            }
+            if !phrases.contains(&hsp) {
+                phrases.push(hsp);
             }
         }
     }
+    if !phrases.is_empty() {
+        let mut high_score_ind: usize = 0;
+        for i in 0..phrases.len() {
+            if phrases[i].1 > phrases[high_score_ind].1 {
+                high_score_ind = i;
+            // In case the two phrases receive an equal score, use the
+            // phrase that alphabetically comes before the other to ensure a
+            // reproducible behavior of prot-scriber
+            } else if phrases[i].1 == phrases[high_score_ind].1
+                && phrases[i]
+                    .0
+                    .join(" ")
+                    .cmp(&phrases[high_score_ind].0.join(" "))
+                    == Less
+            {
+                high_score_ind = i;
+            }
+        }
+
+        let human_readable_description: String = phrases[high_score_ind].0.join(" ");
+        human_readable_rescription_result = Some(human_readable_description);
+    }
     human_readable_rescription_result
 }
 
@@ -210,20 +279,18 @@ pub fn split_descriptions(description: &String, split_regex: &Regex) -> Vec<Stri
         .collect()
 }
 
-/// Calculates the word frequencies for argument `universe_words` and returns a `HashMap<String,
-/// f64>` mapping the words to their respective frequency. Note that this functions returns
+/// Calculates the weighted word frequencies for argument `universe_words` and returns a
+/// `HashMap<String, f64>` mapping the words to their respective frequency. Each appearance of a
+/// word adds its weight to the word's frequency, i.e. with all weights equal to one these are
 /// absolute frequencies in terms of number of appearances.
 ///
 /// # Arguments
 ///
-/// * `universe_words: &Vector<String>` - vector of words
-pub fn frequencies(universe_words: &Vec<String>) -> HashMap<String, f64> {
+/// * `universe_words: &[(String, f64)]` - slice of words and their respective weights
+pub fn weighted_frequencies(universe_words: &[(String, f64)]) -> HashMap<String, f64> {
     let mut word_freqs: HashMap<String, f64> = HashMap::new();
-    for word in universe_words.iter() {
-        if !word_freqs.contains_key(word) {
-            let n_appearances = universe_words.iter().filter(|x| (*x) == word).count() as f64;
-            word_freqs.insert((*word).clone(), n_appearances);
-        }
+    for (word, weight) in universe_words.iter() {
+        *word_freqs.entry(word.clone()).or_insert(0.0) += weight;
     }
     word_freqs
 }
@@ -366,7 +433,80 @@ mod tests {
     }
 
     #[test]
-    fn test_frequencies() {
+    fn test_weighted_frequencies() {
+        let words = vec![
+            ("kinase".to_string(), 1.0),
+            ("receptor".to_string(), 0.5),
+            ("kinase".to_string(), 0.25),
+        ];
+        let mut expected = HashMap::new();
+        expected.insert("kinase".to_string(), 1.25);
+        expected.insert("receptor".to_string(), 0.5);
+        assert_eq!(expected, weighted_frequencies(&words));
+    }
+
+    #[test]
+    fn weighted_descriptions_outweigh_numerous_weak_ones() {
+        let mut descriptions = vec![
+            ("receptor kinase xa21".to_string(), 1.0),
+            ("receptor kinase xa21".to_string(), 1.0),
+        ];
+        for _ in 0..10 {
+            descriptions.push(("leucine rich repeat".to_string(), 0.01));
+        }
+        let unweighted: Vec<String> = descriptions.iter().map(|(d, _)| d.clone()).collect();
+        assert_eq!(
+            Some("leucine rich repeat".to_string()),
+            generate_human_readable_description(
+                &unweighted,
+                &SPLIT_DESCRIPTION_REGEX,
+                &NON_INFORMATIVE_WORDS_REGEXS,
+                &CENTER_INVERSE_INFORMATION_CONTENT_AT_QUANTILE,
+            )
+        );
+        assert_eq!(
+            Some("receptor kinase xa21".to_string()),
+            generate_weighted_human_readable_description(
+                &descriptions,
+                None,
+                &SPLIT_DESCRIPTION_REGEX,
+                &NON_INFORMATIVE_WORDS_REGEXS,
+                &CENTER_INVERSE_INFORMATION_CONTENT_AT_QUANTILE,
+            )
+        );
+    }
+
+    #[test]
+    fn phrase_weights_prefer_phrases_of_higher_weighted_descriptions() {
+        let descriptions = vec![
+            ("receptor kinase xa21".to_string(), 1.0),
+            ("leucine rich repeat".to_string(), 1.0),
+        ];
+        let hrd = |phrase_weights: Option<&[f64]>| {
+            generate_weighted_human_readable_description(
+                &descriptions,
+                phrase_weights,
+                &SPLIT_DESCRIPTION_REGEX,
+                &NON_INFORMATIVE_WORDS_REGEXS,
+                &CENTER_INVERSE_INFORMATION_CONTENT_AT_QUANTILE,
+            )
+        };
+        let unweighted = hrd(None);
+        assert_eq!(unweighted, hrd(Some(&[1.0, 1.0])));
+        for (phrase_weights, expected) in [
+            ([1.0, 0.1], "receptor kinase xa21"),
+            ([0.1, 1.0], "leucine rich repeat"),
+        ] {
+            assert_eq!(Some(expected.to_string()), hrd(Some(&phrase_weights)));
+        }
+    }
+
+    #[test]
+    fn test_unit_weighted_frequencies() {
+        // With all weights equal to one the frequencies are the numbers of appearances:
+        let unit_weighted = |words: &[String]| -> Vec<(String, f64)> {
+            words.iter().map(|word| (word.clone(), 1.0)).collect()
+        };
         let mut words = vec![
             "alcohol".to_string(),
             "dehydrogenase".to_string(),
@@ -378,7 +518,7 @@ mod tests {
         expected.insert("dehydrogenase".to_string(), 1.0);
         expected.insert("alcohol".to_string(), 1.0);
         expected.insert("c".to_string(), 1.0);
-        assert_eq!(expected, frequencies(&words));
+        assert_eq!(expected, weighted_frequencies(&unit_weighted(&words)));
 
         words = vec![
             "importin".to_string(),
@@ -409,7 +549,7 @@ mod tests {
         expected.insert("6".to_string(), 2.0);
         expected.insert("beta".to_string(), 2.0);
         expected.insert("importin".to_string(), 4.0);
-        assert_eq!(expected, frequencies(&words));
+        assert_eq!(expected, weighted_frequencies(&unit_weighted(&words)));
     }
 
     #[test]
@@ -0,0 +1,509 @@
+//! A Hit found for a query sequence in a sequence similarity search, and the ways its quality
+//! can be used to weight its description, are implemented in this module.
+use super::default::{MIN_HIT_WEIGHT, UNIPROT_HEADER_FIELD_KEY_REGEX};
+use super::hit_thresholds::HitScores;
+use super::uniprot_flat_file::UniProtEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A Hit found for a query sequence in a sequence similarity search, e.g. Blast or Diamond.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hit {
+    /// The Hit's description, i.e. the filtered title (`stitle` in Blast terminology).
+    pub description: String,
+    /// The quality measures of the Hit, as far as provided by the parsed search results.
+    pub scores: HitScores,
+    /// The organism the Hit's sequence stems from (`OS` in UniProt and `Tax` in UniRef headers).
+    pub organism: Option<String>,
+    /// The NCBI taxonomy identifier of the organism (`OX` in UniProt and `TaxID` in UniRef
+    /// headers).
+    pub taxon_id: Option<u64>,
+    /// The name of the gene encoding the Hit's sequence (`GN` in UniProt headers).
+    pub gene_name: Option<String>,
+    /// The UniProt protein existence level (`PE`), from one (evidence at protein level) to five
+    /// (uncertain).
+    pub protein_existence: Option<u8>,
+    /// The version of the Hit's sequence (`SV` in UniProt headers).
+    pub sequence_version: Option<u32>,
+    /// The database the Hit's sequence was taken from, e.g. Swiss-Prot.
+    pub source_db: Option<SourceDatabase>,
+    /// The weight of the Hit's evidence (see `EvidenceWeights`), `None` if evidence is not
+    /// weighted for the table the Hit was parsed from.
+    pub evidence_weight: Option<f64>,
+    /// The EC numbers of the Hit's protein, as found in UniProt flat files (see
+    /// `uniprot_flat_file::UniProtEntry`).
+    pub ec_numbers: Vec<String>,
+    /// The priority tier of the table the Hit was parsed from, lower tiers being tried first in
+    /// tiered annotation (see `AnnotationProcess::ssst_tiers`).
+    pub tier: usize,
+}
+
+impl Hit {
+    /// Returns a new Hit with the argument `description` and `scores`. The organism, taxon
+    /// identifier, gene name, protein existence level, and sequence version are parsed from the
+    /// argument `stitle`, if it is a UniProt or UniRef FASTA header (see
+    /// `parse_uniprot_header_fields`). The source database is recognized from the prefix of the
+    /// argument `sacc` or `stitle` (see `SourceDatabase::from_identifier`).
+    ///
+    /// # Arguments
+    ///
+    /// * `sacc: &str` - The Hit's identifier
+    /// * `stitle: &str` - The Hit's unfiltered title
+    /// * `description: String` - The Hit's description, i.e. the filtered title
+    /// * `scores: HitScores` - The quality measures of the Hit
+    pub fn new(sacc: &str, stitle: &str, description: String, scores: HitScores) -> Hit {
+        let fields = parse_uniprot_header_fields(stitle);
+        let field = |keys: &[&str]| -> Option<&str> {
+            keys.iter()
+                .find_map(|key| fields.get(key))
+                .copied()
+                .filter(|value| !value.is_empty())
+        };
+        Hit {
+            description,
+            scores,
+            organism: field(&["OS", "Tax"]).map(|value| value.to_string()),
+            taxon_id: field(&["OX", "TaxID"]).and_then(|value| value.parse().ok()),
+            gene_name: field(&["GN"]).map(|value| value.to_string()),
+            protein_existence: field(&["PE"]).and_then(|value| value.parse().ok()),
+            sequence_version: field(&["SV"]).and_then(|value| value.parse().ok()),
+            source_db: SourceDatabase::from_identifier(sacc)
+                .or_else(|| SourceDatabase::from_identifier(stitle)),
+            evidence_weight: None,
+            ec_numbers: vec![],
+            tier: 0,
+        }
+    }
+
+    /// Returns a new Hit with the argument `description` and `scores`, whose organism, taxon
+    /// identifier, gene name, protein existence level, source database, and EC numbers are taken
+    /// from the argument UniProt flat file `entry`.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry: &UniProtEntry` - The Hit's UniProt flat file entry
+    /// * `description: String` - The Hit's description, i.e. the filtered protein name
+    /// * `scores: HitScores` - The quality measures of the Hit
+    pub fn from_uniprot_entry(entry: &UniProtEntry, description: String, scores: HitScores) -> Hit {
+        Hit {
+            description,
+            scores,
+            organism: entry.organism.clone(),
+            taxon_id: entry.taxon_id,
+            gene_name: entry.gene_name.clone(),
+            protein_existence: entry.protein_existence,
+            sequence_version: None,
+            source_db: entry.source_db,
+            evidence_weight: None,
+            ec_numbers: entry.ec_numbers.clone(),
+            tier: 0,
+        }
+    }
+
+    /// Returns the raw, i.e. not yet normalized, weight of this Hit's description under the
+    /// argument `mode`. `None` is returned, if the required quality measure is missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to this instance of Hit
+    /// * `mode: &HitWeightingMode` - How to weight the Hit
+    pub fn raw_weight(&self, mode: &HitWeightingMode) -> Option<f64> {
+        match mode {
+            HitWeightingMode::None => Some(1.0),
+            HitWeightingMode::Bitscore => self.scores.bitscore,
+            // An e-value of zero is replaced with the smallest positive number to avoid infinite
+            // weights:
+            HitWeightingMode::Evalue => self
+                .scores
+                .evalue
+                .map(|evalue| -evalue.max(f64::MIN_POSITIVE).log10()),
+        }
+    }
+
+    /// Returns `true`, if this Hit is to be kept instead of the argument `other` Hit, i.e. a Hit
+    /// with the same identifier (`sacc`) parsed from a different table. The Hit of the lowest
+    /// priority tier (see `Hit::tier`) is preferred, then the one of higher evidence weight, and
+    /// remaining ties are broken by the alphabetically first description. Thus the kept Hit does
+    /// not depend on the order in which the tables have been parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to this instance of Hit
+    /// * `other: &Hit` - The Hit competing with this one
+    pub fn is_preferred_over(&self, other: &Hit) -> bool {
+        if self.tier != other.tier {
+            return self.tier < other.tier;
+        }
+        let weight = self.evidence_weight.unwrap_or(1.0);
+        let other_weight = other.evidence_weight.unwrap_or(1.0);
+        weight > other_weight || (weight == other_weight && self.description < other.description)
+    }
+}
+
+/// The UniProt databases a Hit's sequence can be taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SourceDatabase {
+    /// UniProtKB/Swiss-Prot, i.e. reviewed entries.
+    SwissProt,
+    /// UniProtKB/TrEMBL, i.e. unreviewed entries.
+    TrEMBL,
+    /// UniRef clusters, e.g. UniRef90.
+    UniRef,
+}
+
+impl SourceDatabase {
+    /// Recognizes the database from the prefix of the argument `identifier`, i.e. `sp|` for
+    /// Swiss-Prot, `tr|` for TrEMBL, and `UniRef` for UniRef. Returns `None` for other prefixes.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier: &str` - A sequence identifier or FASTA header, e.g. `sp|P15538|C11B1_HUMAN`
+    pub fn from_identifier(identifier: &str) -> Option<SourceDatabase> {
+        let identifier = identifier.trim_start();
+        if identifier.starts_with("sp|") {
+            Some(SourceDatabase::SwissProt)
+        } else if identifier.starts_with("tr|") {
+            Some(SourceDatabase::TrEMBL)
+        } else if identifier.starts_with("UniRef") {
+            Some(SourceDatabase::UniRef)
+        } else {
+            None
+        }
+    }
+}
+
+/// The weights of a Hit's evidence, i.e. of the database its sequence was taken from and of its
+/// UniProt protein existence level. The evidence weight of a Hit is the product of both (see
+/// `EvidenceWeights::weight`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvidenceWeights {
+    /// The weight of Hits from UniProtKB/Swiss-Prot (`sp`).
+    pub swiss_prot: f64,
+    /// The weight of Hits from UniProtKB/TrEMBL (`tr`).
+    pub trembl: f64,
+    /// The weight of Hits from UniRef (`uniref`).
+    pub uniref: f64,
+    /// The weight of Hits from other or unrecognized databases (`other`).
+    pub other_database: f64,
+    /// The weights of the protein existence levels one to five (`pe1` to `pe5`).
+    pub protein_existence: [f64; 5],
+    /// The weight of Hits without protein existence level (`pe-missing`).
+    pub missing_protein_existence: f64,
+    /// The weight of all Hits of the sequence similarity search result table, e.g. to let the
+    /// Hits of a small Swiss-Prot table outweigh those of a large trEMBL table.
+    pub table: f64,
+}
+
+impl Default for EvidenceWeights {
+    fn default() -> Self {
+        EvidenceWeights {
+            swiss_prot: 1.0,
+            trembl: 1.0,
+            uniref: 1.0,
+            other_database: 1.0,
+            protein_existence: [1.0; 5],
+            missing_protein_existence: 1.0,
+            table: 1.0,
+        }
+    }
+}
+
+impl EvidenceWeights {
+    /// Parses the argument `spec`, a comma separated list of `key=weight` pairs, e.g.
+    /// `sp=1.0,tr=0.5,pe5=0.2`, into an instance of `EvidenceWeights`. Valid keys are `sp`, `tr`,
+    /// `uniref`, `other`, `pe1` to `pe5`, and `pe-missing`. Weights not set in the argument `spec`
+    /// default to one. Returns `None` if a key is unknown or a weight is not a positive number.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec: &str` - The weights, e.g. as passed on the command line.
+    pub fn parse(spec: &str) -> Option<EvidenceWeights> {
+        let mut weights = EvidenceWeights::default();
+        for pair in spec.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (key, value) = pair.split_once('=')?;
+            let weight: f64 = value.trim().parse().ok().filter(|w: &f64| *w > 0.0)?;
+            match key.trim().to_lowercase().as_str() {
+                "sp" => weights.swiss_prot = weight,
+                "tr" => weights.trembl = weight,
+                "uniref" => weights.uniref = weight,
+                "other" => weights.other_database = weight,
+                "pe-missing" => weights.missing_protein_existence = weight,
+                pe_key => {
+                    let level: usize = pe_key.strip_prefix("pe")?.parse().ok()?;
+                    *weights.protein_existence.get_mut(level.checked_sub(1)?)? = weight;
+                }
+            }
+        }
+        Some(weights)
+    }
+
+    /// Returns the evidence weight of the argument `hit`, i.e. the weight of its source database
+    /// multiplied with the weight of its protein existence level and the weight of its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `hit: &Hit` - The Hit to weight
+    pub fn weight(&self, hit: &Hit) -> f64 {
+        let db_weight = match hit.source_db {
+            Some(SourceDatabase::SwissProt) => self.swiss_prot,
+            Some(SourceDatabase::TrEMBL) => self.trembl,
+            Some(SourceDatabase::UniRef) => self.uniref,
+            None => self.other_database,
+        };
+        let pe_weight = hit
+            .protein_existence
+            .and_then(|level| (level as usize).checked_sub(1))
+            .and_then(|i| self.protein_existence.get(i))
+            .copied()
+            .unwrap_or(self.missing_protein_existence);
+        db_weight * pe_weight * self.table
+    }
+}
+
+/// Parses the fields following the description in a UniProt FASTA header, e.g. `OS=Homo sapiens
+/// OX=9606 GN=CYP11B1 PE=1 SV=5`, or a UniRef FASTA header, e.g. `n=1 Tax=Homo sapiens
+/// TaxID=9606 RepID=C11B1_HUMAN`. Returns a map of field keys to their values, which is empty if
+/// the argument `stitle` has no such fields.
+///
+/// # Arguments
+///
+/// * `stitle: &str` - The Hit's title (`stitle` in Blast terminology)
+pub fn parse_uniprot_header_fields(stitle: &str) -> HashMap<&str, &str> {
+    let keys: Vec<regex::Captures> = UNIPROT_HEADER_FIELD_KEY_REGEX
+        .captures_iter(stitle)
+        .collect();
+    let mut fields = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        let value_start = key.get(0).unwrap().end();
+        let value_end = keys
+            .get(i + 1)
+            .map_or(stitle.len(), |next_key| next_key.get(0).unwrap().start());
+        fields
+            .entry(key.get(1).unwrap().as_str())
+            .or_insert_with(|| stitle[value_start..value_end].trim());
+    }
+    fields
+}
+
+/// The modes of weighting Hit descriptions in the generation of human readable descriptions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitWeightingMode {
+    /// Every Hit description counts equally.
+    None,
+    /// Hit descriptions are weighted by their bit score.
+    Bitscore,
+    /// Hit descriptions are weighted by `-log10(evalue)`.
+    Evalue,
+}
+
+impl HitWeightingMode {
+    /// Parses the argument `mode_arg` into a `HitWeightingMode` (case insensitive). Returns
+    /// `None` if the argument does not name a known mode. Note that `"default"` is parsed into
+    /// `HitWeightingMode::None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode_arg: &str` - The name of the mode, e.g. as passed on the command line.
+    pub fn from_arg(mode_arg: &str) -> Option<HitWeightingMode> {
+        match mode_arg.trim().to_lowercase().as_str() {
+            "default" | "none" => Some(HitWeightingMode::None),
+            "bitscore" => Some(HitWeightingMode::Bitscore),
+            "evalue" => Some(HitWeightingMode::Evalue),
+            _ => None,
+        }
+    }
+
+    /// Returns the name (Blast terminology) of the quality measure this mode requires the Hits to
+    /// have, if any.
+    pub fn required_score(&self) -> Option<&'static str> {
+        match self {
+            HitWeightingMode::None => None,
+            HitWeightingMode::Bitscore => Some("bitscore"),
+            HitWeightingMode::Evalue => Some("evalue"),
+        }
+    }
+}
+
+/// Weights the descriptions of the argument `hits` under the argument `mode`. The raw weights
+/// (see `Hit::raw_weight`) are normalized by the maximum raw weight, so the best Hit's
+/// description has weight one. Hits lacking the required quality measure, or with a raw weight
+/// not above zero, get the minimum weight `default::MIN_HIT_WEIGHT`. Finally, each weight is
+/// multiplied with the Hit's evidence weight, if any (see `EvidenceWeights`). Returns a vector of
+/// description and weight tuples.
+///
+/// # Arguments
+///
+/// * `hits` - The Hits whose descriptions to weight, e.g. the Hits of a single query.
+/// * `mode: &HitWeightingMode` - How to weight the Hits
+pub fn weighted_descriptions<'a, I>(hits: I, mode: &HitWeightingMode) -> Vec<(String, f64)>
+where
+    I: IntoIterator<Item = &'a Hit>,
+{
+    let raw_weights: Vec<(&Hit, Option<f64>)> = hits
+        .into_iter()
+        .map(|hit| (hit, hit.raw_weight(mode)))
+        .collect();
+    let max_raw_weight = raw_weights
+        .iter()
+        .filter_map(|(_, raw)| *raw)
+        .fold(0.0, f64::max);
+    raw_weights
+        .into_iter()
+        .map(|(hit, raw)| {
+            let weight = match raw {
+                Some(w) if w > 0.0 && max_raw_weight > 0.0 => w / max_raw_weight,
+                _ => 0.0,
+            };
+            let evidence_weight = hit.evidence_weight.unwrap_or(1.0);
+            (
+                hit.description.clone(),
+                weight.max(*MIN_HIT_WEIGHT) * evidence_weight,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(description: &str, bitscore: Option<f64>, evalue: Option<f64>) -> Hit {
+        Hit {
+            description: description.to_string(),
+            scores: HitScores {
+                bitscore,
+                evalue,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prefers_hits_of_higher_evidence_weight() {
+        let mut strong = hit("hva protein a", None, None);
+        strong.evidence_weight = Some(1.0);
+        let mut weak = hit("hva protein a", None, None);
+        weak.evidence_weight = Some(0.1);
+        assert!(strong.is_preferred_over(&weak));
+        assert!(!weak.is_preferred_over(&strong));
+        // Unweighted Hits have an evidence weight of one:
+        let unweighted = hit("hva protein b", None, None);
+        assert!(strong.is_preferred_over(&unweighted));
+        assert!(!unweighted.is_preferred_over(&strong));
+        assert!(unweighted.is_preferred_over(&weak));
+        // Hits of lower tiers are preferred regardless of their evidence weight:
+        weak.tier = 1;
+        strong.tier = 2;
+        assert!(weak.is_preferred_over(&strong));
+        assert!(!strong.is_preferred_over(&weak));
+    }
+
+    #[test]
+    fn weights_hit_descriptions() {
+        let hits = vec![
+            hit("strong", Some(500.0), Some(0.0)),
+            hit("weak", Some(50.0), Some(1e-10)),
+            hit("unscored", None, None),
+        ];
+        let unweighted = weighted_descriptions(&hits, &HitWeightingMode::None);
+        assert!(unweighted.iter().all(|(_, w)| *w == 1.0));
+
+        let by_bitscore = weighted_descriptions(&hits, &HitWeightingMode::Bitscore);
+        assert_eq!(("strong".to_string(), 1.0), by_bitscore[0]);
+        assert_eq!(("weak".to_string(), 0.1), by_bitscore[1]);
+        assert_eq!(("unscored".to_string(), *MIN_HIT_WEIGHT), by_bitscore[2]);
+
+        let by_evalue = weighted_descriptions(&hits, &HitWeightingMode::Evalue);
+        assert_eq!(1.0, by_evalue[0].1);
+        assert!(by_evalue[1].1 > 0.03 && by_evalue[1].1 < 0.04);
+        assert_eq!(*MIN_HIT_WEIGHT, by_evalue[2].1);
+    }
+
+    #[test]
+    fn parses_uniprot_header_fields() {
+        let hit = Hit::new(
+            "sp|P15538|C11B1_HUMAN",
+            "sp|P15538|C11B1_HUMAN Cytochrome P450 11B1, mitochondrial OS=Homo sapiens OX=9606 GN=CYP11B1 PE=1 SV=5",
+            "cytochrome p450 11b1, mitochondrial".to_string(),
+            HitScores::default(),
+        );
+        assert_eq!("cytochrome p450 11b1, mitochondrial", hit.description);
+        assert_eq!(Some("Homo sapiens".to_string()), hit.organism);
+        assert_eq!(Some(9606), hit.taxon_id);
+        assert_eq!(Some("CYP11B1".to_string()), hit.gene_name);
+        assert_eq!(Some(1), hit.protein_existence);
+        assert_eq!(Some(5), hit.sequence_version);
+        assert_eq!(Some(SourceDatabase::SwissProt), hit.source_db);
+
+        let trembl_hit = Hit::new(
+            "A0A0V0ITN0",
+            "tr|A0A0V0ITN0|A0A0V0ITN0_SOLCH Protein kinase domain-containing protein OS=Solanum chacoense OX=4108 PE=4 SV=1",
+            "protein kinase domain-containing protein".to_string(),
+            HitScores::default(),
+        );
+        assert_eq!(Some(SourceDatabase::TrEMBL), trembl_hit.source_db);
+        assert_eq!(Some("Solanum chacoense".to_string()), trembl_hit.organism);
+        assert_eq!(None, trembl_hit.gene_name);
+
+        let uniref_hit = Hit::new(
+            "UniRef90_Q6GZX4",
+            "UniRef90_Q6GZX4 Putative transcription factor 001R n=2 Tax=Frog virus 3 TaxID=654924 RepID=001R_FRG3G",
+            "transcription factor 001r".to_string(),
+            HitScores::default(),
+        );
+        assert_eq!(Some(SourceDatabase::UniRef), uniref_hit.source_db);
+        assert_eq!(Some("Frog virus 3".to_string()), uniref_hit.organism);
+        assert_eq!(Some(654924), uniref_hit.taxon_id);
+
+        let other_hit = Hit::new(
+            "PF00069.28",
+            "Protein kinase domain",
+            "protein kinase domain".to_string(),
+            HitScores::default(),
+        );
+        assert_eq!(
+            Hit {
+                description: "protein kinase domain".to_string(),
+                ..Default::default()
+            },
+            other_hit
+        );
+    }
+
+    #[test]
+    fn weights_hit_evidence() {
+        let uniprot = EvidenceWeights::parse(&crate::default::UNIPROT_EVIDENCE_WEIGHTS).unwrap();
+        let reviewed = Hit {
+            source_db: Some(SourceDatabase::SwissProt),
+            protein_existence: Some(1),
+            ..Default::default()
+        };
+        let unreviewed = Hit {
+            source_db: Some(SourceDatabase::TrEMBL),
+            protein_existence: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(1.0, uniprot.weight(&reviewed));
+        assert_eq!(0.5 * 0.2, uniprot.weight(&unreviewed));
+        assert_eq!(1.0, uniprot.weight(&Hit::default()));
+
+        let custom = EvidenceWeights::parse("TR=0.25, pe-missing=0.5").unwrap();
+        assert_eq!(1.0, custom.weight(&reviewed));
+        assert_eq!(0.25, custom.weight(&unreviewed));
+        assert_eq!(0.5, custom.weight(&Hit::default()));
+        for invalid in ["sp", "sp=0", "sp=-1", "pe0=1", "pe6=1", "xyz=1"] {
+            assert_eq!(None, EvidenceWeights::parse(invalid));
+        }
+
+        let mut weighted_hits = vec![reviewed, unreviewed];
+        for hit in weighted_hits.iter_mut() {
+            hit.evidence_weight = Some(uniprot.weight(hit));
+        }
+        let weights: Vec<f64> = weighted_descriptions(&weighted_hits, &HitWeightingMode::None)
+            .into_iter()
+            .map(|(_, w)| w)
+            .collect();
+        assert_eq!(vec![1.0, 0.1], weights);
+    }
+}
@@ -0,0 +1,197 @@
+//! Quality measures of sequence similarity search Hits and the thresholds used to discard Hits of
+//! insufficient quality are implemented in this module.
+use super::hit::Hit;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// The quality measures of a single Hit, i.e. the best HSP of a query and a subject sequence.
+/// Values not provided by the respective sequence similarity search result are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct HitScores {
+    /// The expectation value (`evalue` in Blast terminology).
+    pub evalue: Option<f64>,
+    /// The bit score (`bitscore` in Blast terminology).
+    pub bitscore: Option<f64>,
+    /// The percentage of identical positions (`pident` in Blast terminology).
+    pub pident: Option<f64>,
+    /// The percentage of the query covered by the HSP (`qcovhsp` in Blast terminology).
+    pub qcovhsp: Option<f64>,
+}
+
+/// The thresholds a Hit's quality measures (`HitScores`) must satisfy, before the Hit is used in
+/// the annotation process. Thresholds that are `None` are not applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HitThresholds {
+    /// Hits with a larger `evalue` are discarded.
+    pub max_evalue: Option<f64>,
+    /// Hits with a smaller `bitscore` are discarded.
+    pub min_bitscore: Option<f64>,
+    /// Hits with a smaller `pident` are discarded.
+    pub min_pident: Option<f64>,
+    /// Hits with a smaller `qcovhsp` are discarded.
+    pub min_qcovhsp: Option<f64>,
+    /// Only this many Hits with the highest `bitscore` are retained per query.
+    pub top_n_hits: Option<usize>,
+    /// Only Hits whose `bitscore` lies within this fraction of the query's best Hit's `bitscore`
+    /// are retained, e.g. `0.1` retains Hits with at least 90% of the best `bitscore`.
+    pub within_best_bitscore: Option<f64>,
+}
+
+impl HitThresholds {
+    /// Returns the names (Blast terminology) of the quality measures this instance applies a
+    /// threshold to.
+    pub fn required_scores(&self) -> Vec<&'static str> {
+        let mut names = vec![];
+        if self.max_evalue.is_some() {
+            names.push("evalue");
+        }
+        if self.min_bitscore.is_some()
+            || self.top_n_hits.is_some()
+            || self.within_best_bitscore.is_some()
+        {
+            names.push("bitscore");
+        }
+        if self.min_pident.is_some() {
+            names.push("pident");
+        }
+        if self.min_qcovhsp.is_some() {
+            names.push("qcovhsp");
+        }
+        names
+    }
+
+    /// Returns `true` if and only if the argument `scores` satisfy all thresholds. A Hit lacking a
+    /// quality measure a threshold is set for does _not_ pass.
+    ///
+    /// # Arguments
+    ///
+    /// * `scores: &HitScores` - The quality measures of the Hit to check.
+    pub fn passes(&self, scores: &HitScores) -> bool {
+        passes_max(self.max_evalue, scores.evalue)
+            && passes_min(self.min_bitscore, scores.bitscore)
+            && passes_min(self.min_pident, scores.pident)
+            && passes_min(self.min_qcovhsp, scores.qcovhsp)
+    }
+
+    /// Applies the per query thresholds `within_best_bitscore` and `top_n_hits` to the argument
+    /// `hits` of a single query, i.e. discards all Hits not within the set fraction of the best
+    /// `bitscore` and then all but the `top_n_hits` best Hits. Ties in `bitscore` are resolved by
+    /// the Hit identifiers to ensure reproducible results. Hits lacking a `bitscore` are ranked
+    /// last.
+    ///
+    /// # Arguments
+    ///
+    /// * `hits: &mut HashMap<String, Hit>` - The Hits of a single query, keyed by identifier.
+    pub fn select(&self, hits: &mut HashMap<String, Hit>) {
+        if let Some(fraction) = self.within_best_bitscore {
+            let best_bitscore = hits
+                .values()
+                .filter_map(|hit| hit.scores.bitscore)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let min_bitscore = best_bitscore * (1.0 - fraction);
+            hits.retain(|_, hit| hit.scores.bitscore.is_some_and(|b| b >= min_bitscore));
+        }
+        if let Some(n) = self.top_n_hits {
+            if hits.len() > n {
+                let mut ranked: Vec<(String, f64)> = hits
+                    .iter()
+                    .map(|(sacc, hit)| {
+                        (
+                            sacc.clone(),
+                            hit.scores.bitscore.unwrap_or(f64::NEG_INFINITY),
+                        )
+                    })
+                    .collect();
+                ranked.sort_by(|a, b| {
+                    b.1.partial_cmp(&a.1)
+                        .unwrap_or(Ordering::Equal)
+                        .then_with(|| a.0.cmp(&b.0))
+                });
+                for (sacc, _) in ranked.iter().skip(n) {
+                    hits.remove(sacc);
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if the argument `threshold` is not set, or if the argument `value` is present
+/// and does not exceed it.
+fn passes_max(threshold: Option<f64>, value: Option<f64>) -> bool {
+    match threshold {
+        None => true,
+        Some(t) => value.is_some_and(|v| v <= t),
+    }
+}
+
+/// Returns `true` if the argument `threshold` is not set, or if the argument `value` is present
+/// and reaches it.
+fn passes_min(threshold: Option<f64>, value: Option<f64>) -> bool {
+    match threshold {
+        None => true,
+        Some(t) => value.is_some_and(|v| v >= t),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_thresholds_are_applied() {
+        let scores = HitScores {
+            evalue: Some(1e-10),
+            bitscore: Some(55.0),
+            pident: Some(25.0),
+            qcovhsp: None,
+        };
+        assert!(HitThresholds::default().passes(&scores));
+        let mut thresholds = HitThresholds {
+            max_evalue: Some(1e-5),
+            min_bitscore: Some(50.0),
+            ..Default::default()
+        };
+        assert!(thresholds.passes(&scores));
+        assert_eq!(vec!["evalue", "bitscore"], thresholds.required_scores());
+        thresholds.min_pident = Some(30.0);
+        assert!(!thresholds.passes(&scores));
+        thresholds.min_pident = None;
+        // Missing values do not pass a set threshold:
+        thresholds.min_qcovhsp = Some(50.0);
+        assert!(!thresholds.passes(&scores));
+    }
+
+    #[test]
+    fn hit_thresholds_select_best_hits_per_query() {
+        let mut hits: HashMap<String, Hit> = HashMap::new();
+        for (sacc, bitscore) in [("A", 500.0), ("B", 460.0), ("C", 460.0), ("D", 100.0)] {
+            let mut hit = Hit::default();
+            hit.scores.bitscore = Some(bitscore);
+            hits.insert(sacc.to_string(), hit);
+        }
+        hits.insert("E".to_string(), Hit::default());
+
+        let mut within = hits.clone();
+        HitThresholds {
+            within_best_bitscore: Some(0.1),
+            ..Default::default()
+        }
+        .select(&mut within);
+        let mut retained: Vec<&String> = within.keys().collect();
+        retained.sort();
+        assert_eq!(vec!["A", "B", "C"], retained);
+
+        let mut top = hits.clone();
+        let top_two = HitThresholds {
+            top_n_hits: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(vec!["bitscore"], top_two.required_scores());
+        top_two.select(&mut top);
+        let mut retained: Vec<&String> = top.keys().collect();
+        retained.sort();
+        // Ties are resolved by the Hit identifiers:
+        assert_eq!(vec!["A", "B"], retained);
+    }
+}
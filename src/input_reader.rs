@@ -0,0 +1,147 @@
+//! Opening of input files is implemented in this module. Compressed input is recognized by its
+//! magic bytes and decompressed transparently while it is being streamed.
+use super::default::{GZIP_MAGIC_BYTES, STDIN_INPUT_PATH, ZSTD_MAGIC_BYTES};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// Opens the file found at argument `path` and returns a buffered reader of its _decompressed_
+/// content. Supported compression formats are gzip, bgzip (block gzip, i.e. multiple gzip
+/// members), and zstd. Files that do not start with any of the respective magic bytes are read
+/// as they are, i.e. as plain text. If argument `path` is `default::STDIN_INPUT_PATH` ("-") the
+/// standard input (STDIN) is read instead of a file.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file to open a buffered reader for.
+pub fn open_input_file<P>(path: P) -> io::Result<Box<dyn BufRead + Send>>
+where
+    P: AsRef<Path>,
+{
+    if is_stdin_path(&path) {
+        return decompressing_reader(BufReader::new(io::stdin()));
+    }
+    let file = File::open(path)?;
+    decompressing_reader(BufReader::new(file))
+}
+
+/// Returns `true` if and only if argument `path` indicates to read from standard input (STDIN)
+/// instead of a file, i.e. it equals `default::STDIN_INPUT_PATH` ("-").
+///
+/// # Arguments
+///
+/// * `path` - The input path as provided by the user.
+pub fn is_stdin_path<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    path.as_ref() == Path::new(*STDIN_INPUT_PATH)
+}
+
+/// Inspects the first bytes of argument `reader` _without_ consuming them and wraps the reader
+/// into the matching decoder, if the magic bytes of a supported compression format are found.
+///
+/// # Arguments
+///
+/// * `reader` - The buffered reader of the raw, possibly compressed, input.
+fn decompressing_reader<R>(mut reader: R) -> io::Result<Box<dyn BufRead + Send>>
+where
+    R: BufRead + Send + 'static,
+{
+    let first_bytes = reader.fill_buf()?;
+    if first_bytes.starts_with(&(*GZIP_MAGIC_BYTES)) {
+        // `MultiGzDecoder` continues decoding after the end of the first gzip member, which is
+        // required to read bgzip compressed files completely:
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if first_bytes.starts_with(&(*ZSTD_MAGIC_BYTES)) {
+        Ok(Box::new(BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(reader)?,
+        )))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs::{read_to_string, write};
+    use std::io::Write;
+    use std::path::Path;
+
+    #[test]
+    fn recognizes_stdin_path() {
+        assert!(is_stdin_path("-"));
+        assert!(!is_stdin_path("./-"));
+        assert!(!is_stdin_path("misc/families.txt"));
+    }
+
+    #[test]
+    fn reads_plain_gzip_bgzip_and_zstd_files() {
+        let plain_path = Path::new("misc").join("Twelve_Proteins_vs_Swissprot_blastp.txt");
+        let plain = read_to_string(&plain_path).unwrap();
+        let expected: Vec<String> = plain.lines().map(|x| x.to_string()).collect();
+
+        // Plain text:
+        let lines: Vec<String> = open_input_file(&plain_path)
+            .unwrap()
+            .lines()
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(expected, lines);
+
+        // gzip:
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(plain.as_bytes()).unwrap();
+        let gz_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_input_reader_test.txt.gz",
+            std::process::id()
+        ));
+        write(&gz_path, gz.finish().unwrap()).unwrap();
+        let lines: Vec<String> = open_input_file(&gz_path)
+            .unwrap()
+            .lines()
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(expected, lines);
+
+        // bgzip, i.e. several concatenated gzip members:
+        let (first_half, second_half) = plain.split_at(plain.len() / 2);
+        let mut bgz: Vec<u8> = vec![];
+        for part in [first_half, second_half] {
+            let mut member = GzEncoder::new(vec![], Compression::default());
+            member.write_all(part.as_bytes()).unwrap();
+            bgz.extend(member.finish().unwrap());
+        }
+        let bgz_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_input_reader_test.txt.bgz",
+            std::process::id()
+        ));
+        write(&bgz_path, bgz).unwrap();
+        let lines: Vec<String> = open_input_file(&bgz_path)
+            .unwrap()
+            .lines()
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(expected, lines);
+
+        // zstd:
+        let zst_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_input_reader_test.txt.zst",
+            std::process::id()
+        ));
+        write(&zst_path, zstd::encode_all(plain.as_bytes(), 0).unwrap()).unwrap();
+        let lines: Vec<String> = open_input_file(&zst_path)
+            .unwrap()
+            .lines()
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(expected, lines);
+        for path in [gz_path, bgz_path, zst_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -3,20 +3,34 @@ extern crate lazy_static;
 
 use annotation_process::{run, AnnotationProcess};
 use clap::{Arg, Command};
+use input_reader::is_stdin_path;
 use model_funcs::parse_regex_file;
+use reference_fasta::ReferenceIndexFile;
 use regex::Regex;
 use seq_family_reader::parse_seq_families_file;
+use std::collections::HashMap;
+use uniprot_flat_file::UniProtFlatFile;
 
 /// Declare modules:
 mod annotation_process;
+mod blast_report_reader;
+mod daa_reader;
 mod default;
 mod generate_hrd_associated_funcs;
+mod hit;
+mod hit_thresholds;
+mod input_reader;
 mod model_funcs;
 mod output_writer;
 mod query;
+mod reference_fasta;
 mod seq_family;
+mod seq_family_inference;
 mod seq_family_reader;
 mod seq_sim_table_reader;
+mod taxonomy;
+mod uniprot_flat_file;
+mod unsorted_input;
 
 /// The famous `main` - entry point of `prot-scriber`. It parses the command line arguments, starts
 /// the `prot-scriber` annotation process and writes the results into the respective output file.
@@ -24,7 +38,7 @@ fn main() {
     let matches = Command::new("prot-scriber")
         .version("version 0.1.4")
         .about("\nPLEASE USE '--help' FOR MORE DETAILS!\n\nprot-scriber assigns human readable descriptions (HRD) to query biological sequences or sets of them (a.k.a gene-families).\n")
-        .after_help("\n\nMANUAL\n======\n\n1. Summary\n----------\n'prot-scriber' uses reference descriptions ('stitle' in Blast terminology) from sequence similarity search results (Blast Hits) to assign short human readable descriptions (HRD) to query biological sequences or sets of them (a.k.a gene, or sequence, families). In this, prot-scriber consumes sequence similarity search (Blast, Diamond, or similar) results in tabular format. A customized lexical analysis is carried out on the descriptions ('stitle' in Blast terminology) of these Blast Hits and a resulting HRD is assigned to the query sequences or query families, respectively.\n\n2. prot-scriber input preparation\n---------------------------------\nThis sections explains how to run your favorite sequence similarity search tool, so that it produces tabular results in the format prot-scriber needs them. You can run sequence similarity searches with Blast [McGinnis, S. & Madden, T. L. BLAST: at the core of a powerful and diverse set of sequence analysis tools. Nucleic Acids Res 32, W20–W25 (2004).] or Diamond [Buchfink, B., Xie, C. & Huson, D. H. Fast and sensitive protein alignment using DIAMOND. Nat Meth 12, 59–60 (2015).]. Note that there are other tools to carry out sequence similarity searches which can be used to generate the input for prot-scriber. As long as you have a tabular text file with the three required columns holding the query identifier, the subject ('Hit') identifier, and the subject ('Hit') description ('stitle' in Blast terminology) prot-scriber will accept this as input.\nDepending on the type of your query sequences the search method and searched reference databases vary. For amino acid queries search protein reference databases, for nucleotide query sequences search nucleotide reference databases. If you have protein coding nucleotide query sequences you can choose to either search protein reference databases using translated nucleotide queries with 'blastx' or 'diamond blastx' or search reference nucleotide databases with 'blastn' or 'diamond blastn'. Note, that before carrying out any sequence similarity searches you need to format your reference databases. This is achieved by either the 'makeblastdb' (Blast) or 'makedb' (Diamond) commands, respectively. Please see the respective tool's (Blast or Diamond) manual for details on how to format your reference sequence database.\n\n2.1 A note on TAB characters\n----------------------------\nTAB is often used as a field separator, e.g. by default in Diamond sequence similarity search result tables, or to separate gene-family identifiers from their respective gene-lists. Consequently, prot-scriber has several arguments that could be a TAB, e.g. the --field-separator (-p) or the --seq-family-id-genes-separator (-i) (please see below for more details on these arguments). Unfortunately providing the TAB character as a command line argument can be tricky. It is even more tricky to write it into a manual like this, because it appears as a blank whitespace and cannot easily be distiunguished from other whitespace characters. We thus write '<TAB>' whenever we mean the TAB character. To type it in the command line and provide it as an argument to prot-scriber you can (i) either use $'\\t' (e.g. -p $'\\t') or (ii) hit Ctrl+v and subsequently hit the TAB key on your keyboard (e.g. -p '\t').\n\n2.2 Which reference databases to search\n---------------------------------------\nFor amino acid (protein) or protein coding nucleotide query sequences we recommend searching UniProt's Swissprot and trEMBL. For nucleotide sequences UniRef100 and, or UniParc might be good choices. Note that you can search _any_ database you deem to hold valuable reference sequences. However, you might have to provide custom blacklist, filter, and capture-replace arguments for Blast or Diamond output tables stemming from searches in these non UniProt databases (see section '3. Technical manual' on the arguments --blacklist-regexs (-b), --filter-regexs (-l), and --capture-replace-pairs (-c) for further details). If you want to search any NCBI reference database, please see section 2.2.1 for more details.\n\n2.2.1 NCBI reference databases\n------------------------------\nThe National Center for Biotechnology Information (NCBI) has excellent reference databases to be searched by Blast or Diamond, too. Note that NCBI and UniProt update each other's databases very frequently. So, by searching UniProt only you should not loose information. Anyway, NCBI has e.g. the popular non redundant ('NR') database. However, NCBI has a different description ('stitle' in Blast terminology) format. To make sure prot-scriber parses sequence similarity search result (Blast or Diamond) tables (SSSTs) correctly, you should use a tailored --filter-regexs (-l) argument. A file containing such a list of regular expressions specifically tailored for parsing SSSTs produced by searching NCBI reference databases, e.g. NR, is provided with prot-scriber. You can download it, and edit it if neccessary, here: https://raw.githubusercontent.com/usadellab/prot-scriber/master/misc/filter_stitle_regexs_NCBI_NR.txt\n\n2.2.2 UniRef reference databases\n------------------------------\nThe UniRef databases (UniProt Reference Clusters) provide clustered sets of sequences from the UniProt Knowledgebase and selected UniParc records to obtain complete coverage of sequence space at several resolutions (100%, 90% and 50% identity) while hiding redundant sequences. The UniRef100 database combines identical sequences and subfragments from any source organism into a single UniRef entry (i.e. cluster). UniRef90 and UniRef50 are built by clustering UniRef100 sequences at the 90% or 50% sequence identity levels. To make sure prot-scriber parses sequence similarity search result (Blast or Diamond) tables (SSSTs) correctly, you should use a tailored --filter-regexs (-l) argument. A file containing such a list of regular expressions specifically tailored for parsing SSSTs produced by searching UniRef databases is provided with prot-scriber. You can download it, and edit it if neccessary, here: https://raw.githubusercontent.com/usadellab/prot-scriber/master/misc/filter_stitle_regexs_UniRef.txt\n\n2.3 Example Blast or Diamond commands\n-------------------------------------\nNote that the following instructions on how to execute your sequence similarity searches with Blast or Diamond only include the information - in terms of selected output table columns - absolutely required by 'prot-scriber'. You are welcome, of course, to have more columns in your tabular output, e.g. 'bitscore' or 'evalue' etc. Note that you need to search each of your reference databases with a separate Blast or Diamond command, respectively.\n\n2.3.1 Blast\n-----------\nGenerate prot-scriber input with Blast as follows. The following example uses 'blastp', replace it, if your query sequence type makes that necessary with 'blastn' or 'blastx'.\n\nblastp -db <reference_database.fasta> -query <your_query_sequences.fasta> -num_threads <how-many-do-you-want-to-use> -out <queries_vs_reference_db_name_blastout.txt> -outfmt \"6 delim=<TAB> qacc sacc stitle\"\n\nIt is important to note, that in the above 'outfmt' argument the 'delim' set to '<TAB>' means you need to actually type in a TAB character. (We write '<TAB>' here, so you see something, not only whitespace.) Typically you can type it by hitting Ctrl+Tab in the terminal.\n\n2.3.2 Diamond\n-------------\nGenerate prot-scriber input with Diamond as follows. The following example uses 'blastp', replace it, if your query sequence type makes that necessary with 'blastn' or 'blastx'.\n\ndiamond blastp -p <how-many-threads-do-you-want-to-use> --quiet -d <reference-database.dmnd> -q <your_query_sequences.fasta> -o <queries_vs_reference_db_name_diamondout.txt> -f 6 qseqid sseqid stitle\n\nNote that diamond by default uses the '<TAB>' character as a field-separator for its output tables.\n\n2.4 Gene Family preparation and analysis\n----------------------------------------\nAssume you have the proteomes of eight crucifer plant species and want to cluster the respective amino acid sequences into gene families. Note that the following example provides code to be executed in a BASH Shell (also available on Windows). We provide a very basic procedure to perform the clustering:\n\n(i) \"All versus all\" Blast or Diamond\n\nAssume all amino acid sequences of the eight example proteomes stored in a single file 'all_proteins.fasta'\nRun:\n\ndiamond makedb --in all_proteins.fasta -d all_proteins.fasta\n\ndiamond blastp --quiet -p <how-many-threads-do-you-want-to-use?> -d all_proteins.fasta.dmnd -q all_proteins.fasta -o all_proteins_vs_all.txt -f 6 qseqid sseqid pident\n\n(ii) Run markov clustering\n\nNote that 'mcl' is a command line tool implementing the original Markov Clustering algorithm [Stijn van Dongen, A cluster algorithm for graphs. Technical Report INS-R0010, National Research Institute for Mathematics and Computer Science in the Netherlands, Amsterdam, May 2000]. On most systems you can install the 'mcl' binary using the respective package manager, e.g. 'sudo apt-get update && sudo apt-get install -y mcl' (Debian / Ubuntu).\n\nmcl all_proteins_vs_all.txt -o all_proteins_gene_clusters.txt --abc -I 2.0\n\n(iii) Add gene family names to mcl output and filter out singleton clusters\n\nNote that we use the GNU tools 'sed' and 'awk' to do some basic post-processing of the 'mcl' output.\n\nsed -e 's/\\t/,/g' all_proteins_gene_clusters.txt | awk -F \",\" 'BEGIN{i=1}{if (NF > 1){print \"Seq-Fam_\" i \"\\t\" $0; i=i+1}}' > all_proteins_gene_families.txt\n\nCongratulations! You now have clustered your eight plant crucifer proteomes into gene families (file 'all_proteins_gene_families.txt').\n\n(iv) Run prot-scriber\n\nWe assume that you ran either 'blastp' or 'diamond blastp' (see section 2.3 for details) to search your selected reference databases with the 'all_proteins.fasta' queries. Here, we assume you have searched UniProt's Swissprot and trEMBL databases.\n\nprot-scriber -f all_proteins_gene_families.txt -s all_proteins_vs_Swissprot_blastout.txt -s all_proteins_vs_trEMBL_blastout.txt -o all_proteins_gene_families_HRDs.txt")
+        .after_help("\n\nMANUAL\n======\n\n1. Summary\n----------\n'prot-scriber' uses reference descriptions ('stitle' in Blast terminology) from sequence similarity search results (Blast Hits) to assign short human readable descriptions (HRD) to query biological sequences or sets of them (a.k.a gene, or sequence, families). In this, prot-scriber consumes sequence similarity search (Blast, Diamond, or similar) results in tabular format. A customized lexical analysis is carried out on the descriptions ('stitle' in Blast terminology) of these Blast Hits and a resulting HRD is assigned to the query sequences or query families, respectively.\n\n2. prot-scriber input preparation\n---------------------------------\nThis sections explains how to run your favorite sequence similarity search tool, so that it produces tabular results in the format prot-scriber needs them. You can run sequence similarity searches with Blast [McGinnis, S. & Madden, T. L. BLAST: at the core of a powerful and diverse set of sequence analysis tools. Nucleic Acids Res 32, W20–W25 (2004).] or Diamond [Buchfink, B., Xie, C. & Huson, D. H. Fast and sensitive protein alignment using DIAMOND. Nat Meth 12, 59–60 (2015).]. Note that there are other tools to carry out sequence similarity searches which can be used to generate the input for prot-scriber. As long as you have a tabular text file with the three required columns holding the query identifier, the subject ('Hit') identifier, and the subject ('Hit') description ('stitle' in Blast terminology) prot-scriber will accept this as input.\nDepending on the type of your query sequences the search method and searched reference databases vary. For amino acid queries search protein reference databases, for nucleotide query sequences search nucleotide reference databases. If you have protein coding nucleotide query sequences you can choose to either search protein reference databases using translated nucleotide queries with 'blastx' or 'diamond blastx' or search reference nucleotide databases with 'blastn' or 'diamond blastn'. Note, that before carrying out any sequence similarity searches you need to format your reference databases. This is achieved by either the 'makeblastdb' (Blast) or 'makedb' (Diamond) commands, respectively. Please see the respective tool's (Blast or Diamond) manual for details on how to format your reference sequence database.\n\n2.1 A note on TAB characters\n----------------------------\nTAB is often used as a field separator, e.g. by default in Diamond sequence similarity search result tables, or to separate gene-family identifiers from their respective gene-lists. Consequently, prot-scriber has several arguments that could be a TAB, e.g. the --field-separator (-p) or the --seq-family-id-genes-separator (-i) (please see below for more details on these arguments). Unfortunately providing the TAB character as a command line argument can be tricky. It is even more tricky to write it into a manual like this, because it appears as a blank whitespace and cannot easily be distiunguished from other whitespace characters. We thus write '<TAB>' whenever we mean the TAB character. To type it in the command line and provide it as an argument to prot-scriber you can (i) either use $'\\t' (e.g. -p $'\\t') or (ii) hit Ctrl+v and subsequently hit the TAB key on your keyboard (e.g. -p '\t').\n\n2.2 Which reference databases to search\n---------------------------------------\nFor amino acid (protein) or protein coding nucleotide query sequences we recommend searching UniProt's Swissprot and trEMBL. For nucleotide sequences UniRef100 and, or UniParc might be good choices. Note that you can search _any_ database you deem to hold valuable reference sequences. However, you might have to provide custom blacklist, filter, and capture-replace arguments for Blast or Diamond output tables stemming from searches in these non UniProt databases (see section '3. Technical manual' on the arguments --blacklist-regexs (-b), --filter-regexs (-l), and --capture-replace-pairs (-c) for further details). If you want to search any NCBI reference database, please see section 2.2.1 for more details.\n\n2.2.1 NCBI reference databases\n------------------------------\nThe National Center for Biotechnology Information (NCBI) has excellent reference databases to be searched by Blast or Diamond, too. Note that NCBI and UniProt update each other's databases very frequently. So, by searching UniProt only you should not loose information. Anyway, NCBI has e.g. the popular non redundant ('NR') database. However, NCBI has a different description ('stitle' in Blast terminology) format. To make sure prot-scriber parses sequence similarity search result (Blast or Diamond) tables (SSSTs) correctly, you should use a tailored --filter-regexs (-l) argument. A file containing such a list of regular expressions specifically tailored for parsing SSSTs produced by searching NCBI reference databases, e.g. NR, is provided with prot-scriber. You can download it, and edit it if neccessary, here: https://raw.githubusercontent.com/usadellab/prot-scriber/master/misc/filter_stitle_regexs_NCBI_NR.txt\n\n2.2.2 UniRef reference databases\n------------------------------\nThe UniRef databases (UniProt Reference Clusters) provide clustered sets of sequences from the UniProt Knowledgebase and selected UniParc records to obtain complete coverage of sequence space at several resolutions (100%, 90% and 50% identity) while hiding redundant sequences. The UniRef100 database combines identical sequences and subfragments from any source organism into a single UniRef entry (i.e. cluster). UniRef90 and UniRef50 are built by clustering UniRef100 sequences at the 90% or 50% sequence identity levels. To make sure prot-scriber parses sequence similarity search result (Blast or Diamond) tables (SSSTs) correctly, you should use a tailored --filter-regexs (-l) argument. A file containing such a list of regular expressions specifically tailored for parsing SSSTs produced by searching UniRef databases is provided with prot-scriber. You can download it, and edit it if neccessary, here: https://raw.githubusercontent.com/usadellab/prot-scriber/master/misc/filter_stitle_regexs_UniRef.txt\n\n2.3 Example Blast or Diamond commands\n-------------------------------------\nNote that the following instructions on how to execute your sequence similarity searches with Blast or Diamond only include the information - in terms of selected output table columns - absolutely required by 'prot-scriber'. You are welcome, of course, to have more columns in your tabular output, e.g. 'bitscore' or 'evalue' etc. Note that you need to search each of your reference databases with a separate Blast or Diamond command, respectively.\n\n2.3.1 Blast\n-----------\nGenerate prot-scriber input with Blast as follows. The following example uses 'blastp', replace it, if your query sequence type makes that necessary with 'blastn' or 'blastx'.\n\nblastp -db <reference_database.fasta> -query <your_query_sequences.fasta> -num_threads <how-many-do-you-want-to-use> -out <queries_vs_reference_db_name_blastout.txt> -outfmt \"6 delim=<TAB> qacc sacc stitle\"\n\nIt is important to note, that in the above 'outfmt' argument the 'delim' set to '<TAB>' means you need to actually type in a TAB character. (We write '<TAB>' here, so you see something, not only whitespace.) Typically you can type it by hitting Ctrl+Tab in the terminal.\n\n2.3.2 Diamond\n-------------\nGenerate prot-scriber input with Diamond as follows. The following example uses 'blastp', replace it, if your query sequence type makes that necessary with 'blastn' or 'blastx'.\n\ndiamond blastp -p <how-many-threads-do-you-want-to-use> --quiet -d <reference-database.dmnd> -q <your_query_sequences.fasta> -o <queries_vs_reference_db_name_diamondout.txt> -f 6 qseqid sseqid stitle\n\nNote that diamond by default uses the '<TAB>' character as a field-separator for its output tables.\n\n2.4 Gene Family preparation and analysis\n----------------------------------------\nAssume you have the proteomes of eight crucifer plant species and want to cluster the respective amino acid sequences into gene families. Note that the following example provides code to be executed in a BASH Shell (also available on Windows). We provide a very basic procedure to perform the clustering:\n\n(i) \"All versus all\" Blast or Diamond\n\nAssume all amino acid sequences of the eight example proteomes stored in a single file 'all_proteins.fasta'\nRun:\n\ndiamond makedb --in all_proteins.fasta -d all_proteins.fasta\n\ndiamond blastp --quiet -p <how-many-threads-do-you-want-to-use?> -d all_proteins.fasta.dmnd -q all_proteins.fasta -o all_proteins_vs_all.txt -f 6 qseqid sseqid pident\n\n(ii) Run markov clustering\n\nNote that 'mcl' is a command line tool implementing the original Markov Clustering algorithm [Stijn van Dongen, A cluster algorithm for graphs. Technical Report INS-R0010, National Research Institute for Mathematics and Computer Science in the Netherlands, Amsterdam, May 2000]. On most systems you can install the 'mcl' binary using the respective package manager, e.g. 'sudo apt-get update && sudo apt-get install -y mcl' (Debian / Ubuntu).\n\nmcl all_proteins_vs_all.txt -o all_proteins_gene_clusters.txt --abc -I 2.0\n\n(iii) Add gene family names to mcl output and filter out singleton clusters\n\nNote that we use the GNU tools 'sed' and 'awk' to do some basic post-processing of the 'mcl' output.\n\nsed -e 's/\\t/,/g' all_proteins_gene_clusters.txt | awk -F \",\" 'BEGIN{i=1}{if (NF > 1){print \"Seq-Fam_\" i \"\\t\" $0; i=i+1}}' > all_proteins_gene_families.txt\n\nCongratulations! You now have clustered your eight plant crucifer proteomes into gene families (file 'all_proteins_gene_families.txt'). Alternatively, skip this step and provide the 'mcl' output directly with '--seq-family-format mcl', in which case prot-scriber generates the family identifiers (see --seq-family-format).\n\n(iv) Run prot-scriber\n\nWe assume that you ran either 'blastp' or 'diamond blastp' (see section 2.3 for details) to search your selected reference databases with the 'all_proteins.fasta' queries. Here, we assume you have searched UniProt's Swissprot and trEMBL databases.\n\nprot-scriber -f all_proteins_gene_families.txt -s all_proteins_vs_Swissprot_blastout.txt -s all_proteins_vs_trEMBL_blastout.txt -o all_proteins_gene_families_HRDs.txt")
         .arg(
             Arg::new("output")
             .required(true)
@@ -42,7 +56,7 @@ fn main() {
             .long("seq-sim-table")
             .multiple_occurrences(true)
             .help("File in which to find sequence similarity search results in tabular format")
-            .long_help("File in which to find sequence similarity search results in tabular format (SSST). Use e.g. Blast or Diamond to produce them. Required columns are: 'qacc sacc stitle' (Blast) or 'qseqid sseqid stitle' (Diamond). (See section '2. prot-scriber input preparation' for more details.) If the required columns, or more, appear in different order than shown here you must use the --header (-e) argument. If any of the input SSSTs uses a different field-separator than the '<TAB>' character, you must provide the --field-separator (-p) argument. You can provide multiple SSSTs, simply by repeating the -s argument, e.g. '-s queries_vs_swissprot_diamond_out.txt -s queries_vs_trembl_diamond_out.txt'. Providing multiple --seq-sim-table (-s) arguments might imply the order in which you give other arguments like --header (-e) and --field-separator (-p). See there for more details."),
+            .long_help("File in which to find sequence similarity search results in tabular format (SSST). Use e.g. Blast or Diamond to produce them. Required columns are: 'qacc sacc stitle' (Blast) or 'qseqid sseqid stitle' (Diamond). (See section '2. prot-scriber input preparation' for more details.) If the required columns, or more, appear in different order than shown here you must use the --header (-e) argument. If any of the input SSSTs uses a different field-separator than the '<TAB>' character, you must provide the --field-separator (-p) argument. You can provide multiple SSSTs, simply by repeating the -s argument, e.g. '-s queries_vs_swissprot_diamond_out.txt -s queries_vs_trembl_diamond_out.txt'. Providing multiple --seq-sim-table (-s) arguments might imply the order in which you give other arguments like --header (-e) and --field-separator (-p). See there for more details. Gzip, bgzip, and zstd compressed SSSTs are recognized automatically and decompressed on the fly. Provide '-' to read one of the SSSTs from standard input (STDIN), e.g. to pipe the output of Blast or Diamond into prot-scriber. Thresholds on quality measures and --hit-weighting require a --header (-e) for tabular SSSTs read from STDIN, because these cannot be inspected for a '# Fields:' comment line beforehand."),
         )
         .arg(
             Arg::new("header")
@@ -51,7 +65,131 @@ fn main() {
             .long("header")
             .multiple_occurrences(true)
             .help("Header of the --seq-sim-table (-s) arg.")
-            .long_help("Header of the --seq-sim-table (-s) arg. Separated by space (' ') the names of the columns in order of appearance in the respective table. Required and default columns are 'qacc sacc stitle'. Note that this option only understands Blast terminology, i.e. even if you ran Diamond, please provide 'qacc' instead of 'qseqid' and 'sacc' instead of 'sseqid'. Luckily 'stitle' is 'stitle' in Diamond, too. You can have additional columns that will be ignored, as long as the required columns appear in the correct order. Consider this example: 'qacc sacc evalue bitscore stitle'. If multiple --seq-sim-table (-s) args are provided make sure the --header (-e) args appear in the correct order, e.g. the first -e arg will be used for the first -s arg, the second -e will be used for the second -s and so on. Set to 'default' to use the hard coded default."),
+            .long_help("Header of the --seq-sim-table (-s) arg. Separated by space (' ') the names of the columns in order of appearance in the respective table. Required and default columns are 'qacc sacc stitle'. The optional columns 'evalue', 'bitscore', 'pident', and 'qcovhsp' are used to apply the thresholds --max-evalue, --min-bitscore, --min-pident, and --min-qcovhsp, respectively. Note that this option only understands Blast terminology, i.e. even if you ran Diamond, please provide 'qacc' instead of 'qseqid' and 'sacc' instead of 'sseqid'. Luckily 'stitle' is 'stitle' in Diamond, too. You can have additional columns that will be ignored, as long as the required columns appear in the correct order. Consider this example: 'qacc sacc evalue bitscore stitle'. If multiple --seq-sim-table (-s) args are provided make sure the --header (-e) args appear in the correct order, e.g. the first -e arg will be used for the first -s arg, the second -e will be used for the second -s and so on. Set to 'default' to use the hard coded default. If no --header (-e) args are provided, tabular SSSTs with comment lines ('-outfmt 7') take the column positions from their '# Fields:' comment line, e.g. '# Fields: query acc., subject acc., evalue, bit score, subject title'. Other comment lines, i.e. lines starting with '#', are always skipped."),
+        )
+        .arg(
+            Arg::new("seq-sim-table-format")
+            .short('t')
+            .takes_value(true)
+            .long("seq-sim-table-format")
+            .multiple_occurrences(true)
+            .help("Format of the --seq-sim-table (-s) arg. One of 'tabular', 'blast-xml', 'blast-json', 'hmmer-tblout', 'hmmer-domtblout', or 'diamond-daa'.")
+            .long_help("Format of the --seq-sim-table (-s) arg. The default value is 'tabular', i.e. Blast or Diamond tabular output ('-outfmt 6'). Use 'blast-xml' for Blast XML reports ('-outfmt 5') and 'blast-json' for Blast single file JSON reports ('-outfmt 15'). Use 'hmmer-tblout' and 'hmmer-domtblout' for HMMER per-sequence ('--tblout') and per-domain ('--domtblout') hit tables, e.g. of hmmscan against Pfam, in which case the query name is used as 'qacc', the target accession (or target name, if no accession is given) as 'sacc', and the description of the target as 'stitle'. Use 'diamond-daa' for Diamond binary alignment archives ('--outfmt 100'), which are read directly, i.e. need not be converted with 'diamond view'. Their quality measures are computed from the best alignment of each Hit, the e-value from its bit score and the size of the reference database. The first word of the reference sequence's title is used as 'sacc' and the whole title as 'stitle'. Diamond DAA files cannot be read from standard input. Note that --header (-e) and --field-separator (-p) only apply to tabular SSSTs. If multiple --seq-sim-table (-s) args are provided make sure the --seq-sim-table-format (-t) args appear in the correct order, e.g. the first -t arg will be used for the first -s arg, the second -t will be used for the second -s and so on. You can provide '-t default' to use the hard coded default (tabular)."),
+        )
+        .arg(
+            Arg::new("max-evalue")
+            .takes_value(true)
+            .long("max-evalue")
+            .multiple_occurrences(true)
+            .help("Maximum e-value of Hits in the --seq-sim-table (-s) arg.")
+            .long_help("Maximum e-value ('evalue' in Blast terminology) a Hit in the --seq-sim-table (-s) arg may have. Hits with a larger e-value are discarded before they enter the annotation process. Tabular SSSTs must provide an 'evalue' column in their --header (-e). If multiple --seq-sim-table (-s) args are provided make sure the --max-evalue args appear in the correct order, e.g. the first --max-evalue arg will be used for the first -s arg, the second --max-evalue will be used for the second -s and so on. Set to 'none' or 'default' to not apply this threshold to the respective -s arg."),
+        )
+        .arg(
+            Arg::new("min-bitscore")
+            .takes_value(true)
+            .long("min-bitscore")
+            .multiple_occurrences(true)
+            .help("Minimum bit score of Hits in the --seq-sim-table (-s) arg.")
+            .long_help("Minimum bit score ('bitscore' in Blast terminology) a Hit in the --seq-sim-table (-s) arg must have. Hits with a smaller bit score are discarded before they enter the annotation process. Tabular SSSTs must provide a 'bitscore' column in their --header (-e). If multiple --seq-sim-table (-s) args are provided make sure the --min-bitscore args appear in the correct order, e.g. the first --min-bitscore arg will be used for the first -s arg, the second --min-bitscore will be used for the second -s and so on. Set to 'none' or 'default' to not apply this threshold to the respective -s arg."),
+        )
+        .arg(
+            Arg::new("min-pident")
+            .takes_value(true)
+            .long("min-pident")
+            .multiple_occurrences(true)
+            .help("Minimum percent identity of Hits in the --seq-sim-table (-s) arg.")
+            .long_help("Minimum percentage of identical positions ('pident' in Blast terminology) a Hit in the --seq-sim-table (-s) arg must have. Hits with a smaller percent identity are discarded before they enter the annotation process. Tabular SSSTs must provide a 'pident' column in their --header (-e). Not available for HMMER tables. If multiple --seq-sim-table (-s) args are provided make sure the --min-pident args appear in the correct order, e.g. the first --min-pident arg will be used for the first -s arg, the second --min-pident will be used for the second -s and so on. Set to 'none' or 'default' to not apply this threshold to the respective -s arg."),
+        )
+        .arg(
+            Arg::new("min-qcovhsp")
+            .takes_value(true)
+            .long("min-qcovhsp")
+            .multiple_occurrences(true)
+            .help("Minimum percent query coverage of Hits in the --seq-sim-table (-s) arg.")
+            .long_help("Minimum percentage of the query covered by the Hit's HSP ('qcovhsp' in Blast terminology) a Hit in the --seq-sim-table (-s) arg must have. Hits with a smaller query coverage are discarded before they enter the annotation process. Tabular SSSTs must provide a 'qcovhsp' column in their --header (-e). Not available for HMMER tables. If multiple --seq-sim-table (-s) args are provided make sure the --min-qcovhsp args appear in the correct order, e.g. the first --min-qcovhsp arg will be used for the first -s arg, the second --min-qcovhsp will be used for the second -s and so on. Set to 'none' or 'default' to not apply this threshold to the respective -s arg."),
+        )
+        .arg(
+            Arg::new("top-n-hits")
+            .takes_value(true)
+            .long("top-n-hits")
+            .multiple_occurrences(true)
+            .help("Number of best Hits per query to retain from the --seq-sim-table (-s) arg.")
+            .long_help("Number of best Hits, ranked by bit score ('bitscore' in Blast terminology), to retain per query from the --seq-sim-table (-s) arg. Queries hitting huge protein families can collect hundreds of Hits, which dilutes the signal of the best Hits and slows down the annotation process. Ties in bit score are resolved by Hit identifier. Tabular SSSTs must provide a 'bitscore' column in their --header (-e). If multiple --seq-sim-table (-s) args are provided make sure the --top-n-hits args appear in the correct order, e.g. the first --top-n-hits arg will be used for the first -s arg, the second --top-n-hits will be used for the second -s and so on. Set to 'none' or 'default' to retain all Hits of the respective -s arg."),
+        )
+        .arg(
+            Arg::new("within-best-bitscore")
+            .takes_value(true)
+            .long("within-best-bitscore")
+            .multiple_occurrences(true)
+            .help("Retain only Hits within this fraction of the best Hit's bit score in the --seq-sim-table (-s) arg.")
+            .long_help("A value between zero and one. Only Hits whose bit score ('bitscore' in Blast terminology) lies within this fraction of the bit score of the query's best Hit are retained from the --seq-sim-table (-s) arg, e.g. 0.1 retains Hits with at least 90% of the best bit score. Applied before --top-n-hits. Tabular SSSTs must provide a 'bitscore' column in their --header (-e). If multiple --seq-sim-table (-s) args are provided make sure the --within-best-bitscore args appear in the correct order, e.g. the first --within-best-bitscore arg will be used for the first -s arg, the second --within-best-bitscore will be used for the second -s and so on. Set to 'none' or 'default' to retain all Hits of the respective -s arg."),
+        )
+        .arg(
+            Arg::new("evidence-weights")
+            .takes_value(true)
+            .long("evidence-weights")
+            .multiple_occurrences(true)
+            .help("Weight the Hits of the --seq-sim-table (-s) arg by their database of origin and UniProt protein existence level.")
+            .long_help("Weight the Hits of the --seq-sim-table (-s) arg by the database their sequence was taken from and their UniProt protein existence level ('PE=' in UniProt headers). A Hit's evidence weight is the product of both and scales its influence on the word frequencies and on the choice of the final phrase. Provide a comma separated list of key=weight pairs with positive weights, e.g. 'sp=1.0,tr=0.5,pe5=0.2'. Valid keys are 'sp' (Swiss-Prot), 'tr' (TrEMBL), 'uniref' (UniRef), 'other' (any other or unrecognized database), 'pe1' to 'pe5' (protein existence levels), and 'pe-missing' (Hits without protein existence level). Weights not given default to one. Set to 'uniprot' to prefer reviewed Swiss-Prot Hits with evidence at protein level (sp=1.0,tr=0.5,uniref=0.5,pe1=1.0,pe2=0.8,pe3=0.6,pe4=0.4,pe5=0.2). If multiple --seq-sim-table (-s) args are provided make sure the --evidence-weights args appear in the correct order, e.g. the first --evidence-weights arg will be used for the first -s arg, the second --evidence-weights will be used for the second -s and so on. Set to 'none' or 'default' to not weight the evidence of the respective -s arg."),
+        )
+        .arg(
+            Arg::new("reference-fasta")
+            .takes_value(true)
+            .long("reference-fasta")
+            .multiple_occurrences(true)
+            .help("Reference FASTA, or its index, to look up the Hit titles of the --seq-sim-table (-s) arg in, if it has no 'stitle' column.")
+            .long_help("Path to the (possibly compressed) reference FASTA the --seq-sim-table (-s) arg was searched against, or to the index of its headers built with 'prot-scriber index' (see 'prot-scriber index --help'). Index files are recognized automatically. Use this for tabular SSSTs without 'stitle' column, e.g. produced with Diamond's default '--outfmt 6', or together with --prefer-reference-titles. An index of the FASTA headers is built on every run, unless an index file is given, and the Hit titles ('stitle' in Blast terminology) are looked up by the Hits' identifiers ('sacc' in Blast terminology), either as they are, as accession of pipe separated identifiers (e.g. 'P15538' in 'sp|P15538|C11B1_HUMAN'), or without version suffix. Rows whose Hit is not found in the FASTA, e.g. because it is outdated, are skipped and counted in a summary printed at the end. They are not treated as malformed (see --malformed-rows). Provide the SSST's columns with --header (-e), e.g. 'qacc sacc pident length mismatch gapopen qstart qend sstart send evalue bitscore' for Diamond's default. If multiple --seq-sim-table (-s) args are provided make sure the --reference-fasta args appear in the correct order, e.g. the first --reference-fasta arg will be used for the first -s arg, the second --reference-fasta will be used for the second -s and so on. Set to 'none' or 'default' to read the Hit titles from the 'stitle' column of the respective -s arg."),
+        )
+        .arg(
+            Arg::new("uniprot-dat")
+            .takes_value(true)
+            .long("uniprot-dat")
+            .multiple_occurrences(true)
+            .help("UniProt flat file (.dat), or its index, whose protein names describe the Hits of the --seq-sim-table (-s) arg.")
+            .long_help("Path to the (possibly compressed) UniProtKB flat file (.dat), e.g. 'uniprot_sprot.dat.gz', or to the index of its entries built with 'prot-scriber index --uniprot-dat' (see 'prot-scriber index --help'), describing the Hits of the --seq-sim-table (-s) arg. Index files are recognized automatically. A flat file is read into memory on every run, once per --seq-sim-table (-s) arg, which is only feasible for flat files of the size of Swiss-Prot. Index huge flat files, e.g. 'uniprot_trembl.dat.gz', once and pass the index file instead; it is memory mapped, i.e. only the entries needed are read. Hits found in it by their identifiers ('sacc' in Blast terminology), either as they are, as accession of pipe separated identifiers (e.g. 'P15538' in 'sp|P15538|C11B1_HUMAN'), without version suffix, or by their entry name (e.g. 'C11B1_HUMAN'), are described by the recommended full protein name ('DE   RecName: Full='), or the first submitted one ('DE   SubName: Full='), instead of their titles ('stitle' in Blast terminology). Protein names are blacklisted (see --blacklist-regexs) and normalized (see --capture-replace-pairs), but not filtered (see --filter-regexs). EC numbers ('EC='), organism, taxon, gene name, and protein existence level are taken from the flat file, too. Hits not in the flat file are described by their titles as usual. The 'stitle' column may be omitted, in which case rows whose Hit is not in the flat file are skipped and counted like Hits missing in a --reference-fasta, unless a --reference-fasta is given, too. Only applies to tabular SSSTs. If multiple --seq-sim-table (-s) args are provided make sure the --uniprot-dat args appear in the correct order, e.g. the first --uniprot-dat arg will be used for the first -s arg, the second --uniprot-dat will be used for the second -s and so on. Set to 'none' or 'default' to describe the Hits of the respective -s arg by their titles."),
+        )
+        .arg(
+            Arg::new("prefer-reference-titles")
+            .takes_value(false)
+            .long("prefer-reference-titles")
+            .help("Look up the Hit titles in the --reference-fasta args even if the --seq-sim-table (-s) args have a 'stitle' column.")
+            .long_help("Look up the Hit titles ('stitle' in Blast terminology) in the --reference-fasta args even if the respective --seq-sim-table (-s) args have a 'stitle' column, e.g. because the search tool truncated the titles. Hits not found in the reference FASTA keep the title of the SSST. SSSTs without --reference-fasta are not affected."),
+        )
+        .arg(
+            Arg::new("exclude-taxa")
+            .takes_value(true)
+            .long("exclude-taxa")
+            .multiple_occurrences(true)
+            .help("Exclude Hits of these NCBI taxonomy identifiers, e.g. of the query's own species.")
+            .long_help("Exclude Hits whose NCBI taxonomy identifier ('OX=' in UniProt and 'TaxID=' in UniRef headers) is one of these. Provide either a comma separated list of identifiers, e.g. '4113,4081', or the path to a file listing one identifier per line. Use this e.g. when annotating a newly sequenced genome whose earlier draft, with its unreviewed auto-annotations, is already in trEMBL, to avoid circular annotations. Hits are excluded before --top-n-hits and --within-best-bitscore are applied. Hits without taxonomy identifier are never excluded. Can be given multiple times, the identifiers apply to all --seq-sim-table (-s) args. See also --taxonomy-nodes and --excluded-taxa-weight."),
+        )
+        .arg(
+            Arg::new("taxonomy-nodes")
+            .takes_value(true)
+            .long("taxonomy-nodes")
+            .help("Path to a local NCBI taxonomy 'nodes.dmp' used to also exclude all taxa below the --exclude-taxa and by --query-taxon.")
+            .long_help("Path to a local NCBI taxonomy 'nodes.dmp' (e.g. from https://ftp.ncbi.nih.gov/pub/taxonomy/taxdump.tar.gz), possibly compressed. If given, Hits of all taxa below the --exclude-taxa are excluded, too, e.g. '--exclude-taxa 4107' excludes all Hits of the genus Solanum. Also required by --query-taxon."),
+        )
+        .arg(
+            Arg::new("excluded-taxa-weight")
+            .takes_value(true)
+            .long("excluded-taxa-weight")
+            .help("Down-weight Hits of the --exclude-taxa by this factor instead of excluding them.")
+            .long_help("A value above zero and at most one. Instead of excluding Hits of the --exclude-taxa, their evidence weight (see --evidence-weights) is multiplied with this factor, so they still contribute to, but no longer dominate, the human readable descriptions. Set to 'none' or 'default' to exclude these Hits."),
+        )
+        .arg(
+            Arg::new("query-taxon")
+            .takes_value(true)
+            .long("query-taxon")
+            .help("NCBI taxonomy identifier or scientific name of the queries' taxon. Weights Hits by their phylogenetic distance to it.")
+            .long_help("NCBI taxonomy identifier, e.g. '4113', or scientific name, e.g. 'Solanum tuberosum', of the taxon the query sequences stem from. If given, each Hit is weighted by the fraction of the query taxon's lineage it shares with the Hit's taxon ('OX=' in UniProt and 'TaxID=' in UniRef headers), so that descriptions from close relatives count more than descriptions from distant ones. E.g. for a potato query a tomato Hit shares most of the lineage, a bacterial Hit only 'cellular organisms'. Hits without a taxonomy identifier, or whose taxon is not part of the taxonomy, are not weighted. The phylogenetic weight is combined with the --evidence-weights. Requires --taxonomy-nodes and, if a scientific name is given, --taxonomy-names."),
+        )
+        .arg(
+            Arg::new("taxonomy-names")
+            .takes_value(true)
+            .long("taxonomy-names")
+            .help("Path to a local NCBI taxonomy 'names.dmp' used to look up the --query-taxon by its scientific name.")
+            .long_help("Path to a local NCBI taxonomy 'names.dmp' (e.g. from https://ftp.ncbi.nih.gov/pub/taxonomy/taxdump.tar.gz), possibly compressed. Only required if the --query-taxon is given as scientific name."),
         )
         .arg(
             Arg::new("blacklist-regexs")
@@ -62,6 +200,22 @@ fn main() {
             .help("A file with regular expressions used to exclude matching Blast Hit descriptions.")
             .long_help("A file with regular expressions (Rust syntax), one per line. Any match to any of these regular expressions causes sequence similarity search result descriptions ('stitle' in Blast terminology) to be discarded from the prot-scriber annotation process. If multiple --seq-sim-table (-s) args are provided make sure the --blacklist-regexs (-b) args appear in the correct order, e.g. the first -b arg will be used for the first -s arg, the second -b will be used for the second -s and so on. Set to 'default' to use the hard coded default. An example file can be downloaded here: https://raw.githubusercontent.com/usadellab/prot-scriber/master/misc/blacklist_stitle_regexs.txt - Note that this is an expert option."),
         )
+        .arg(
+            Arg::new("table-weight")
+            .takes_value(true)
+            .long("table-weight")
+            .multiple_occurrences(true)
+            .help("A positive number weighting the Blast Hits of the respective --seq-sim-table (-s) arg.")
+            .long_help("A positive number by which the evidence weight of each Blast Hit of the respective --seq-sim-table (-s) arg is multiplied. This scales each table's contribution to the assigned human readable descriptions, e.g. use '--table-weight 1 --table-weight 0.25' to let a small table of Swiss-Prot Hits outweigh a large table of trEMBL Hits. The weight is combined with the --evidence-weights of the table. If multiple --seq-sim-table (-s) args are provided make sure the --table-weight args appear in the correct order, e.g. the first --table-weight arg will be used for the first -s arg, the second --table-weight will be used for the second -s and so on. Set to 'default' to use the default weight of 1.0."),
+        )
+        .arg(
+            Arg::new("table-tier")
+            .takes_value(true)
+            .long("table-tier")
+            .multiple_occurrences(true)
+            .help("A positive integer assigning the respective --seq-sim-table (-s) arg to a priority tier; Blast Hits of lower tiers are used first.")
+            .long_help("A positive integer assigning the respective --seq-sim-table (-s) arg to a priority tier. If given, queries and sequence families are annotated in tiers: first only the Blast Hits of the tables of the lowest tier are used; only if these yield no human readable description, i.e. 'unknown protein' or 'unknown sequence family', the Hits of the next tier are used, and so on. E.g. use '-s swissprot.txt --table-tier 1 -s trembl.txt --table-tier 2 -s nr.txt --table-tier 3' to annotate from Swiss-Prot first and fall back to trEMBL and then NR only if needed. Tables of the same tier are used together. The output table gets an additional column holding the tier that produced each description. If multiple --seq-sim-table (-s) args are provided make sure the --table-tier args appear in the correct order, e.g. the first --table-tier arg will be used for the first -s arg, the second --table-tier will be used for the second -s and so on. Set to 'default' to use the default tier 1. Without any --table-tier the Hits of all tables are used together."),
+        )
         .arg(
             Arg::new("filter-regexs")
             .short('l')
@@ -95,7 +249,28 @@ fn main() {
             .takes_value(true)
             .long("seq-families")
             .help("A file in which families of biological sequences are stored, one family per line.")
-            .long_help("A file in which families of biological sequences are stored, one family per line. Each line must have format 'fam-name TAB gene1,gene2,gene3'. Make sure no gene appears in more than one family."),
+            .long_help("A file in which families of biological sequences are stored, one family per line. Each line must have format 'fam-name TAB gene1,gene2,gene3'. A gene may appear in more than one family, e.g. when using fuzzy, or overlapping, clustering; its Blast Hits then contribute to the descriptions of all of its families. Gzip, bgzip, and zstd compressed files are recognized automatically and decompressed on the fly. Provide '-' to read the families from standard input (STDIN), in which case no --seq-sim-table (-s) can be read from STDIN."),
+        )
+        .arg(
+            Arg::new("seq-family-format")
+            .takes_value(true)
+            .long("seq-family-format")
+            .help("One of 'default', 'orthofinder', 'orthofinder-hog', or 'mcl'. The format of the --seq-families (-f) file.")
+            .long_help("The format of the --seq-families (-f) file. With 'default' each line must have format 'fam-name TAB gene1,gene2,gene3' (see --seq-family-id-genes-separator and --seq-family-gene-ids-separator). With 'orthofinder' the file is read as OrthoFinder's 'Orthogroups.tsv', i.e. a header row 'Orthogroup TAB species1 TAB species2 ...' followed by one orthogroup per row with a column of comma separated genes for each species; empty species columns are allowed. The species of each family member is recorded and the output table gets an additional column listing the species of each family. With 'orthofinder-hog' the file is read as OrthoFinder's hierarchical orthogroups (HOGs), e.g. 'Phylogenetic_Hierarchical_Orthogroups/N0.tsv', i.e. a header row 'HOG TAB OG TAB Gene Tree Parent Clade TAB species1 TAB species2 ...' followed by one HOG per row. Species are recorded as with 'orthofinder'. Each HOG is annotated as subfamily of its orthogroup ('OG' column), and each orthogroup is annotated, too, using the Blast Hits of all its HOGs' members. The output table gets an additional column holding the parent family (orthogroup) of each subfamily (HOG). With 'mcl' the file is read as raw MCL output, i.e. one cluster of genes per line without family identifier (see --seq-family-gene-ids-separator). Family identifiers are generated from the --seq-family-id-prefix and the line number of the cluster, zero-padded to --seq-family-id-digits, e.g. 'Seq-Fam_0000001' for the first line. The generated identifiers are used in the output table, so the descriptions can be matched back to the clusters by their line numbers."),
+        )
+        .arg(
+            Arg::new("seq-family-id-prefix")
+            .takes_value(true)
+            .long("seq-family-id-prefix")
+            .help("The prefix of the family identifiers generated for --seq-family-format 'mcl'.")
+            .long_help("The prefix of the family identifiers generated for the clusters of a --seq-families (-f) file in --seq-family-format 'mcl'. Default is 'Seq-Fam_'."),
+        )
+        .arg(
+            Arg::new("seq-family-id-digits")
+            .takes_value(true)
+            .long("seq-family-id-digits")
+            .help("The number of digits the index in family identifiers generated for --seq-family-format 'mcl' is zero-padded to.")
+            .long_help("The number of digits the line number in family identifiers generated for the clusters of a --seq-families (-f) file in --seq-family-format 'mcl' is zero-padded to. Longer line numbers are not truncated. Default is 7, e.g. 'Seq-Fam_0000001'."),
         )
         .arg(
             Arg::new("seq-family-id-genes-separator")
@@ -113,6 +288,22 @@ fn main() {
             .help("A regular expression used to split the list of gene-IDs in a gene-family file.")
             .long_help("A regular expression (Rust syntax) used to split the list of gene-identifiers in the argument --seq-families (-f) gene families file. Default is '(\\s*,\\s*|\\s+)'."),
         )
+        .arg(
+            Arg::new("infer-families")
+            .takes_value(true)
+            .long("infer-families")
+            .conflicts_with("seq-families")
+            .help("Infer sequence families from the Blast Hits queries share and annotate these, instead of reading a --seq-families (-f) file. Provide the minimum Jaccard similarity or 'default'.")
+            .long_help("Infer sequence families from the Blast Hits queries share and annotate these families, instead of reading a --seq-families (-f) file. The argument is the minimum Jaccard similarity, a number between zero and one, of the subject identifiers ('sacc' in Blast terminology) of two queries' Hits to link the queries, provided they share at least one Hit. Families are the connected components of the linked queries, i.e. with '0' all queries sharing any Hit end up in the same family. Set to 'default' to use 0.5. Queries not linked to any other query are not grouped, but can be annotated with --annotate-non-family-queries (-a). Family identifiers are generated from the --seq-family-id-prefix and the index of the family, zero-padded to --seq-family-id-digits; families are ordered by their alphabetically first member. The output table gets an additional column listing the members of each inferred family. Hits of more queries than --infer-families-max-queries-per-hit are ignored. Note that all parsed queries and their Hits are kept in memory until all --seq-sim-table (-s) args have been read and the families have been inferred, i.e. memory grows with the size of the input, unlike when annotating queries or given families, whose data is freed as soon as they are annotated."),
+        )
+        .arg(
+            Arg::new("infer-families-max-queries-per-hit")
+            .takes_value(true)
+            .long("infer-families-max-queries-per-hit")
+            .requires("infer-families")
+            .help("Ignore Hits of more queries than this when inferring families with --infer-families.")
+            .long_help("The maximum number of queries a Blast Hit is used to link into families inferred with --infer-families. Hub Hits found by more queries, e.g. proteins with ubiquitous domains, are ignored, as if the queries had not found them. All queries sharing a Hit are compared pairwise, so this bounds the time spent per Hit. Set to 'default' to use 1000."),
+        )
         .arg(
             Arg::new("annotate-non-family-queries")
             .short('a')
@@ -137,6 +328,27 @@ fn main() {
             .help("Either a number element [0,1] or 50. The quantile or mean to be used for centering.")
             .long_help("The quantile (percentile) to be subtracted from calculated inverse word information content to center these values. Consequently, this must be a value between zero and one or literal 50, which is interpreted as mean instead of a quantile. Default is 5o, implying centering at the mean. Note that this is an expert option."),
         )
+        .arg(
+            Arg::new("hit-weighting")
+            .takes_value(true)
+            .long("hit-weighting")
+            .help("One of 'none', 'bitscore', or 'evalue'. How to weight Blast Hit descriptions.")
+            .long_help("How to weight the Blast Hit descriptions when generating human readable descriptions. With 'none' (the default) every Hit description contributes equally to the word frequencies and thus to the inverse word information content. With 'bitscore' each Hit description contributes its bit score, and with 'evalue' it contributes -log10(evalue), both normalized by the respective value of the query's best Hit. This way a few strong Hits can outweigh many weak ones. Tabular SSSTs must provide a 'bitscore' or 'evalue' column, respectively, in their --header (-e). Note that this is an expert option."),
+        )
+        .arg(
+            Arg::new("unsorted-input")
+            .takes_value(true)
+            .long("unsorted-input")
+            .help("One of 'none', 'in-memory', or 'on-disk'. How to read --seq-sim-table (-s) args not sorted by query identifiers.")
+            .long_help("How to read --seq-sim-table (-s) args that are not sorted by query identifiers ('qacc' in Blast terminology). With 'none' (the default) the SSSTs are expected to be sorted and queries are annotated as soon as they have been parsed completely, which is fastest and uses the least memory. With 'in-memory' the Hits of each query are grouped in memory, before queries are annotated. With 'on-disk' the parsed queries are first partitioned by their identifiers into temporary files, which are then grouped in memory one at a time. Use this for huge SSSTs. Temporary files are written into the system's temporary directory, which can be set with the environment variable TMPDIR, and are deleted afterwards."),
+        )
+        .arg(
+            Arg::new("malformed-rows")
+            .takes_value(true)
+            .long("malformed-rows")
+            .help("One of 'skip' or 'strict'. How to handle malformed rows in the --seq-sim-table (-s) args.")
+            .long_help("How to handle malformed rows in tabular and HMMER --seq-sim-table (-s) args, i.e. rows with too few columns, an empty 'qacc' or 'sacc', quality measures that are not numbers, or invalid UTF-8. Such rows are e.g. written by a search job that got killed. With 'skip' (the default) each malformed row is reported with the file name and line number and skipped, and a summary of the skipped rows is printed at the end. With 'strict' the first malformed row causes prot-scriber to fail."),
+        )
         .arg(
             Arg::new("verbose")
             .short('v')
@@ -172,8 +384,69 @@ fn main() {
             .long("exclude-not-annotated-queries")
             .help("Exclude results from the output table that could not be annotated.")
             .long_help("Exclude results from the output table that could not be annotated, i.e. 'unknown protein' or 'unknown sequence family', respectively."),
+        )
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("index")
+            .about("Index the headers of a reference FASTA, or the entries of a UniProt flat file, once, to look up Hits across runs.")
+            .long_about("Index the headers of a (possibly compressed) reference FASTA, e.g. UniRef100, into a compact index file. Pass the index file as --reference-fasta to look up the Hit titles ('stitle' in Blast terminology) of the --seq-sim-table (-s) args in it. Alternatively index the entries of a (possibly compressed) UniProtKB flat file, e.g. 'uniprot_trembl.dat.gz', with --uniprot-dat and pass the index file as --uniprot-dat. Unlike a reference FASTA or flat file, which is read on every run, the index file is memory mapped, i.e. it is available instantly and only the parts needed are read. Hits are looked up by their identifiers ('sacc' in Blast terminology), either as they are, as accession of pipe separated identifiers (e.g. 'P15538' in 'sp|P15538|C11B1_HUMAN'), or without version suffix.")
+            .arg(
+                Arg::new("reference-fasta")
+                .required_unless_present("uniprot-dat")
+                .conflicts_with("uniprot-dat")
+                .takes_value(true)
+                .short('r')
+                .long("reference-fasta")
+                .help("The (possibly compressed) reference FASTA to index."),
+            )
+            .arg(
+                Arg::new("uniprot-dat")
+                .takes_value(true)
+                .short('u')
+                .long("uniprot-dat")
+                .help("The (possibly compressed) UniProtKB flat file (.dat) to index."),
+            )
+            .arg(
+                Arg::new("output")
+                .required(true)
+                .takes_value(true)
+                .short('o')
+                .long("output")
+                .help("Filename in which to write the index."),
+            ),
         ).get_matches();
 
+    // Index a reference FASTA, or a UniProt flat file, instead of annotating queries?
+    if let Some(index_matches) = matches.subcommand_matches("index") {
+        let output = index_matches.value_of("output").unwrap();
+        if let Some(uniprot_dat) = index_matches.value_of("uniprot-dat") {
+            match UniProtFlatFile::build_index(uniprot_dat, output) {
+                Ok((n_entries, n_accessions)) => println!(
+                    "Indexed {} entries with {} accessions of {:?} into {:?}.",
+                    n_entries, n_accessions, uniprot_dat, output
+                ),
+                Err(e) => panic!(
+                    "\n\nCould not index UniProt flat file {:?} into {:?}:\n{:?}\n\n",
+                    uniprot_dat, output, e
+                ),
+            }
+            return;
+        }
+        let reference_fasta = index_matches.value_of("reference-fasta").unwrap();
+        match ReferenceIndexFile::build(reference_fasta, output) {
+            Ok((n_headers, n_accessions)) => println!(
+                "Indexed {} headers with {} accessions of {:?} into {:?}.",
+                n_headers, n_accessions, reference_fasta, output
+            ),
+            Err(e) => panic!(
+                "\n\nCould not index reference FASTA {:?} into {:?}:\n{:?}\n\n",
+                reference_fasta, output, e
+            ),
+        }
+        return;
+    }
+
     // Create a new AnnotationProcess instance and provide it with the necessary input data:
     let mut annotation_process = AnnotationProcess::new();
 
@@ -197,6 +470,27 @@ fn main() {
         .build_global()
         .expect("Could not set the number of parallel processes to be used to generate human readable descriptions (AnnotationProcess::process_rest_data).");
 
+    // How are the identifiers of families in raw MCL output, or of inferred families, generated?
+    if let Some(seq_family_id_prefix) = matches.value_of("seq-family-id-prefix") {
+        annotation_process.seq_family_id_prefix = seq_family_id_prefix.trim().to_string();
+    }
+    if let Some(seq_family_id_digits) = matches.value_of("seq-family-id-digits") {
+        annotation_process.set_seq_family_id_digits(seq_family_id_digits);
+    }
+
+    // Shall non family queries also be annotated?
+    if matches.is_present("annotate-non-family-queries") {
+        annotation_process.annotate_lonely_queries = true;
+    }
+
+    // Shall sequence families be inferred from the Hits queries share?
+    if let Some(infer_families) = matches.value_of("infer-families") {
+        annotation_process.set_infer_families(infer_families);
+    }
+    if let Some(max_queries_per_hit) = matches.value_of("infer-families-max-queries-per-hit") {
+        annotation_process.set_infer_families_max_queries_per_hit(max_queries_per_hit);
+    }
+
     // Add biological sequence families information, if provided as input by the user:
     if let Some(seq_families) = matches.value_of("seq-families") {
         // What is the character that separates a gene-family-identifier from its list of
@@ -219,9 +513,19 @@ fn main() {
                 .to_string();
         }
 
-        // Shall non family queries also be annotated?
-        if matches.is_present("annotate-non-family-queries") {
-            annotation_process.annotate_lonely_queries = true;
+        // In which format are the gene families stored?
+        if let Some(seq_family_format) = matches.value_of("seq-family-format") {
+            annotation_process.set_seq_family_format(seq_family_format);
+        }
+
+        // Standard input can only be read once, i.e. either for the families or a table:
+        if is_stdin_path(seq_families)
+            && matches
+                .values_of("seq-sim-table")
+                .unwrap()
+                .any(is_stdin_path)
+        {
+            panic!("\n\nCannot read both --seq-families (-f) and a --seq-sim-table (-s) from standard input ('-'). Please provide at most one of them as '-'.\n\n");
         }
 
         parse_seq_families_file(seq_families, &mut annotation_process);
@@ -257,6 +561,52 @@ fn main() {
         }
     }
 
+    // For each of the above to be parsed SSSR tables set their respective format, if given by the
+    // user:
+    if matches.is_present("seq-sim-table-format") {
+        for format_arg in matches.values_of("seq-sim-table-format").unwrap() {
+            annotation_process.add_ssst_format(format_arg);
+        }
+    }
+
+    // For each of the above to be parsed SSSR tables set the thresholds Hits must pass, if given
+    // by the user:
+    if matches.is_present("max-evalue") {
+        for max_evalue_arg in matches.values_of("max-evalue").unwrap() {
+            annotation_process.add_ssst_max_evalue(max_evalue_arg);
+        }
+    }
+    if matches.is_present("min-bitscore") {
+        for min_bitscore_arg in matches.values_of("min-bitscore").unwrap() {
+            annotation_process.add_ssst_min_bitscore(min_bitscore_arg);
+        }
+    }
+    if matches.is_present("min-pident") {
+        for min_pident_arg in matches.values_of("min-pident").unwrap() {
+            annotation_process.add_ssst_min_pident(min_pident_arg);
+        }
+    }
+    if matches.is_present("min-qcovhsp") {
+        for min_qcovhsp_arg in matches.values_of("min-qcovhsp").unwrap() {
+            annotation_process.add_ssst_min_qcovhsp(min_qcovhsp_arg);
+        }
+    }
+    if matches.is_present("top-n-hits") {
+        for top_n_hits_arg in matches.values_of("top-n-hits").unwrap() {
+            annotation_process.add_ssst_top_n_hits(top_n_hits_arg);
+        }
+    }
+    if matches.is_present("within-best-bitscore") {
+        for within_best_bitscore_arg in matches.values_of("within-best-bitscore").unwrap() {
+            annotation_process.add_ssst_within_best_bitscore(within_best_bitscore_arg);
+        }
+    }
+    if matches.is_present("evidence-weights") {
+        for evidence_weights_arg in matches.values_of("evidence-weights").unwrap() {
+            annotation_process.add_ssst_evidence_weights(evidence_weights_arg);
+        }
+    }
+
     // For each of the above to be parsed SSSR tables set the blacklist filter, i.e. vectors of
     // regular expressions:
     if matches.is_present("blacklist-regexs") {
@@ -265,6 +615,20 @@ fn main() {
         }
     }
 
+    // For each of the above to be parsed SSSR tables set the weight of their Hits:
+    if matches.is_present("table-weight") {
+        for weight_arg in matches.values_of("table-weight").unwrap() {
+            annotation_process.add_ssst_weight(weight_arg);
+        }
+    }
+
+    // For each of the above to be parsed SSSR tables set their priority tier:
+    if matches.is_present("table-tier") {
+        for tier_arg in matches.values_of("table-tier").unwrap() {
+            annotation_process.add_ssst_tier(tier_arg);
+        }
+    }
+
     // For each of the above to be parsed SSSR tables set the filter regexs, i.e. vectors of
     // regular expressions:
     if matches.is_present("filter-regexs") {
@@ -305,6 +669,50 @@ fn main() {
 
     // Did the user supply a custom quantile (percentile) value to be used to center inverse word
     // information content scores?
+    if matches.is_present("hit-weighting") {
+        annotation_process.set_hit_weighting(matches.value_of("hit-weighting").unwrap());
+    }
+
+    if matches.is_present("unsorted-input") {
+        annotation_process.set_unsorted_input(matches.value_of("unsorted-input").unwrap());
+    }
+
+    if matches.is_present("malformed-rows") {
+        annotation_process.set_malformed_rows(matches.value_of("malformed-rows").unwrap());
+    }
+
+    if matches.is_present("reference-fasta") {
+        for reference_fasta_arg in matches.values_of("reference-fasta").unwrap() {
+            annotation_process.add_ssst_reference_fasta(reference_fasta_arg);
+        }
+    }
+    if matches.is_present("uniprot-dat") {
+        for uniprot_dat_arg in matches.values_of("uniprot-dat").unwrap() {
+            annotation_process.add_ssst_uniprot_dat(uniprot_dat_arg);
+        }
+    }
+    if matches.is_present("prefer-reference-titles") {
+        annotation_process.prefer_reference_titles = true;
+    }
+    if matches.is_present("exclude-taxa") {
+        for excluded_taxa_arg in matches.values_of("exclude-taxa").unwrap() {
+            annotation_process.add_excluded_taxa(excluded_taxa_arg);
+        }
+    }
+    if matches.is_present("taxonomy-nodes") {
+        annotation_process.set_taxonomy_nodes(matches.value_of("taxonomy-nodes").unwrap());
+    }
+    if matches.is_present("excluded-taxa-weight") {
+        annotation_process
+            .set_excluded_taxa_weight(matches.value_of("excluded-taxa-weight").unwrap());
+    }
+    if matches.is_present("query-taxon") {
+        annotation_process.set_query_taxon(matches.value_of("query-taxon").unwrap());
+    }
+    if matches.is_present("taxonomy-names") {
+        annotation_process.set_taxonomy_names(matches.value_of("taxonomy-names").unwrap());
+    }
+
     if matches.is_present("center-inverse-word-information-content-at-quantile") {
         annotation_process.center_iic_at_quantile = matches
             .value_of("center-inverse-word-information-content-at-quantile")
@@ -329,11 +737,51 @@ fn main() {
     // Execute the Annotation-Process:
     annotation_process = run(annotation_process);
 
-    // Save output:
+    // Save output, including the tiers that produced the descriptions in tiered annotation, the
+    // parents of subfamilies in hierarchical families, the members of inferred families, and the
+    // species of the sequence families, if known:
     if let Some(o) = matches.value_of("output") {
+        let mut additional_columns: Vec<(&str, HashMap<String, String>)> = vec![];
+        if !annotation_process.ssst_tiers.is_empty() {
+            additional_columns.push((
+                "Annotation-Tier",
+                annotation_process
+                    .annotation_tiers
+                    .iter()
+                    .map(|(annotee, tier)| (annotee.clone(), tier.to_string()))
+                    .collect(),
+            ));
+        }
+        if !annotation_process.seq_family_parents.is_empty() {
+            additional_columns.push((
+                "Parent-Family",
+                annotation_process.seq_family_parents.clone(),
+            ));
+        }
+        if !annotation_process.inferred_seq_families.is_empty() {
+            additional_columns.push((
+                "Family-Members",
+                annotation_process
+                    .inferred_seq_families
+                    .iter()
+                    .map(|(annotee, members)| (annotee.clone(), members.join(",")))
+                    .collect(),
+            ));
+        }
+        if !annotation_process.seq_family_species.is_empty() {
+            additional_columns.push((
+                "Species",
+                annotation_process
+                    .seq_family_species
+                    .iter()
+                    .map(|(annotee, species)| (annotee.clone(), species.join(",")))
+                    .collect(),
+            ));
+        }
         match output_writer::write_output_table(
             o.to_string(),
             annotation_process.human_readable_descriptions,
+            additional_columns,
         ) {
             Ok(()) => {
                 if annotation_process.verbose {
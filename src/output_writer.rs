@@ -7,16 +7,27 @@ use std::fs::write;
 ///
 /// * `file_path: String` - The file path for saving output.
 /// * `human_readable_descriptions: HashMap<String, String>` - The generated human readable descriptions.
+/// * `additional_columns: Vec<(&str, HashMap<String, String>)>` - Further columns, each given by
+///   its header and its values keyed by annotee, e.g. the tiers that produced the human readable
+///   descriptions in tiered annotation. Annotees without value get an empty field.
 pub fn write_output_table(
     file_path: String,
     human_readable_descriptions: HashMap<String, String>,
+    additional_columns: Vec<(&str, HashMap<String, String>)>,
 ) -> std::io::Result<()> {
     if human_readable_descriptions.keys().len() > 0 {
         let mut output = String::from("Annotee-Identifier\tHuman-Readable-Description");
+        for (header, _) in &additional_columns {
+            output.push_str(&(format!("\t{}", header)));
+        }
         // stream write line after line
         // iterate over entries in argument human_readable_descriptions
         for (annotee_name, annotation) in human_readable_descriptions {
             output.push_str(&(format!("\n{}\t{}", annotee_name, annotation)));
+            for (_, values) in &additional_columns {
+                let value = values.get(&annotee_name).map_or("", |v| v.as_str());
+                output.push_str(&(format!("\t{}", value)));
+            }
         }
         // add trailing newline for the last annotation
         output.push_str("\n");
@@ -41,13 +52,17 @@ mod tests {
             "Protein-123".to_string(),
             "human devouring protein".to_string(),
         );
+        let path =
+            std::env::temp_dir().join(format!("prot-scriber_{}_result.txt", std::process::id()));
         assert_eq!(
             write_output_table(
-                "./target/result.txt".to_string(),
-                human_readable_descriptions
+                path.to_str().unwrap().to_string(),
+                human_readable_descriptions,
+                vec![]
             )
             .is_ok(),
             true
         );
+        std::fs::remove_file(path).unwrap();
     }
 }
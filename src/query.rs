@@ -1,12 +1,17 @@
-use crate::generate_hrd_associated_funcs::generate_human_readable_description;
+use crate::generate_hrd_associated_funcs::{
+    generate_human_readable_description, generate_weighted_human_readable_description,
+};
+use crate::hit::{weighted_descriptions, Hit, HitWeightingMode};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
 /// A sequence similarity search is executed for a query sequence, which is represented by `Query`.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Query {
-    /// The sequence similarity search results (Blast Hits)
-    pub hits: HashMap<String, String>,
+    /// The sequence similarity search results (Blast Hits), keyed by their identifier (`sacc`)
+    pub hits: HashMap<String, Hit>,
     /// A counter of how many times this query was parsed in sequence similarity search results
     pub n_parsed_from_sssr_tables: u16,
 }
@@ -16,7 +21,7 @@ impl Query {
     /// Returns a new and initialized instance of struct `Query`.
     pub fn new() -> Query {
         Query {
-            hits: HashMap::<String, String>::new(),
+            hits: HashMap::<String, Hit>::new(),
             n_parsed_from_sssr_tables: 0,
         }
     }
@@ -33,26 +38,114 @@ impl Query {
     /// to identify non informative words, that receive only a minimum score.
     /// * `center_at_quantile` - A real value between zero and one used to center the inverse
     /// information content scores.
+    /// * `hit_weighting` - How to weight the Hit descriptions (see `hit::weighted_descriptions`).
+    ///   Hits whose evidence has been weighted (see `hit::EvidenceWeights`) are weighted
+    ///   accordingly, even if `hit_weighting` is `HitWeightingMode::None`.
     pub fn annotate(
         &self,
         split_regex: &Regex,
         non_informative_words_regexs: &Vec<Regex>,
         center_at_quantile: &f64,
+        hit_weighting: &HitWeightingMode,
     ) -> Option<String> {
         if self.hits.len() > 0 {
-            let hit_descriptions = self
-                .hits
-                .values()
-                .map(|hit_desc| (*hit_desc).clone())
-                .collect();
-            generate_human_readable_description(
-                &hit_descriptions,
-                split_regex,
-                non_informative_words_regexs,
-                center_at_quantile,
-            )
+            if *hit_weighting == HitWeightingMode::None
+                && self
+                    .hits
+                    .values()
+                    .all(|hit| hit.evidence_weight.unwrap_or(1.0) == 1.0)
+            {
+                let hit_descriptions = self
+                    .hits
+                    .values()
+                    .map(|hit| hit.description.clone())
+                    .collect();
+                generate_human_readable_description(
+                    &hit_descriptions,
+                    split_regex,
+                    non_informative_words_regexs,
+                    center_at_quantile,
+                )
+            } else {
+                let (descriptions, evidence_weights): (Vec<(String, f64)>, Vec<f64>) = self
+                    .weighted_descriptions(hit_weighting)
+                    .into_iter()
+                    .map(|(desc, weight, evidence_weight)| ((desc, weight), evidence_weight))
+                    .unzip();
+                generate_weighted_human_readable_description(
+                    &descriptions,
+                    Some(&evidence_weights),
+                    split_regex,
+                    non_informative_words_regexs,
+                    center_at_quantile,
+                )
+            }
         } else {
             None
         }
     }
+
+    /// Merges the argument `hits`, parsed for this query from another table, into this query's
+    /// Hits. Of two Hits with the same identifier (`sacc`) the one preferred by
+    /// `Hit::is_preferred_over` is kept, so that the result does not depend on the order in which
+    /// the tables have been parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A mutable reference to self, this instance of Query
+    /// * `hits: HashMap<String, Hit>` - The Hits to merge, keyed by their identifier (`sacc`)
+    pub fn merge_hits(&mut self, hits: HashMap<String, Hit>) {
+        for (sacc, hit) in hits {
+            match self.hits.entry(sacc) {
+                Entry::Occupied(mut entry) => {
+                    if hit.is_preferred_over(entry.get()) {
+                        entry.insert(hit);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(hit);
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of this query retaining only the Hits of the argument priority `tier` (see
+    /// `Hit::tier`).
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to self, this instance of Query
+    /// * `tier: usize` - The priority tier of the Hits to retain
+    pub fn hits_of_tier(&self, tier: usize) -> Query {
+        Query {
+            hits: self
+                .hits
+                .iter()
+                .filter(|(_, hit)| hit.tier == tier)
+                .map(|(sacc, hit)| (sacc.clone(), hit.clone()))
+                .collect(),
+            n_parsed_from_sssr_tables: self.n_parsed_from_sssr_tables,
+        }
+    }
+
+    /// Returns the descriptions of this query's Hits with their respective weight under the
+    /// argument `hit_weighting` mode and their evidence weight, which is one for Hits whose
+    /// evidence has not been weighted. Weights are normalized among the Hits of this query (see
+    /// `hit::weighted_descriptions`) and include the evidence weight.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to self, this instance of Query
+    /// * `hit_weighting` - How to weight the Hit descriptions.
+    pub fn weighted_descriptions(
+        &self,
+        hit_weighting: &HitWeightingMode,
+    ) -> Vec<(String, f64, f64)> {
+        let hits: Vec<&Hit> = self.hits.values().collect();
+        weighted_descriptions(hits.iter().copied(), hit_weighting)
+            .into_iter()
+            .zip(hits)
+            .map(|((desc, weight), hit)| (desc, weight, hit.evidence_weight.unwrap_or(1.0)))
+            .collect()
+    }
 }
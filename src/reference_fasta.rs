@@ -0,0 +1,612 @@
+//! Looking up Hit titles (`stitle` in Blast terminology) in the reference FASTA a sequence
+//! similarity search was run against is implemented in this module. It is used for tables that
+//! lack a `stitle` column, e.g. Diamond's default `--outfmt 6`, or whose titles are truncated.
+//! The FASTA headers are either indexed in memory on every run, or once into an index file (see
+//! `ReferenceIndexFile::build`), which is memory mapped and can be reused across runs. The same
+//! index file format holds the entries of UniProt flat files (see
+//! `uniprot_flat_file::UniProtFlatFile::build_index`).
+use super::input_reader::open_input_file;
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Write};
+
+/// The first bytes of every index file written by `ReferenceIndexFile::build`.
+pub const INDEX_FILE_MAGIC: &[u8; 8] = b"PSRIDX01";
+
+/// The length of the fixed size part of an index file, i.e. the magic followed by the number of
+/// headers, the number of accessions, and the length of the headers in bytes.
+const INDEX_FILE_PREAMBLE_LEN: usize = 32;
+
+/// An accession and the index of the header it is found in.
+type AccessionHeader = (String, u64);
+
+/// The Hit titles of a reference, either indexed in memory or in an index file.
+#[derive(Debug)]
+pub enum ReferenceTitles {
+    /// The headers of a reference FASTA, indexed on reading it.
+    InMemory(ReferenceIndex),
+    /// A memory mapped index file built with `ReferenceIndexFile::build`.
+    OnDisk(ReferenceIndexFile),
+}
+
+impl ReferenceTitles {
+    /// Opens the argument `path`, which is either an index file built with
+    /// `ReferenceIndexFile::build` or a (possibly compressed) reference FASTA. Index files are
+    /// recognized by their first bytes (see `INDEX_FILE_MAGIC`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path: &str` - The path to the index file or to the reference FASTA
+    pub fn open(path: &str) -> ReferenceTitles {
+        if is_index_file(path) {
+            ReferenceTitles::OnDisk(ReferenceIndexFile::open(path))
+        } else {
+            ReferenceTitles::InMemory(ReferenceIndex::from_fasta(path))
+        }
+    }
+
+    /// Returns the Hit title (`stitle`) of the argument `sacc`, if any (see
+    /// `ReferenceIndex::get`).
+    ///
+    /// # Arguments
+    ///
+    /// * `sacc: &str` - The Hit's identifier (`sacc` in Blast terminology)
+    pub fn get(&self, sacc: &str) -> Option<&str> {
+        match self {
+            ReferenceTitles::InMemory(index) => index.get(sacc),
+            ReferenceTitles::OnDisk(index) => index.get(sacc),
+        }
+    }
+}
+
+/// An index of the headers of a reference FASTA, keyed by the accessions of the respective
+/// sequences.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReferenceIndex {
+    /// The FASTA headers without the leading `>`, i.e. the Hit titles (`stitle`).
+    pub headers: Vec<String>,
+    /// The indices of the `headers`, keyed by the accessions they are found with (see
+    /// `header_accessions`).
+    pub accessions: HashMap<String, usize>,
+}
+
+impl ReferenceIndex {
+    /// Reads the (possibly compressed) reference FASTA found at argument `path` and indexes its
+    /// headers. Sequences are not retained. Panics if the file cannot be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `path: &str` - The path to the reference FASTA
+    pub fn from_fasta(path: &str) -> ReferenceIndex {
+        let reader = open_input_file(path).unwrap_or_else(|e| {
+            panic!(
+                "\n\nCould not read reference FASTA {:?}:\n{:?}\n\n",
+                path, e
+            )
+        });
+        let mut index = ReferenceIndex::default();
+        for line in reader.lines() {
+            let line = line.unwrap_or_else(|e| {
+                panic!(
+                    "\n\nCould not read reference FASTA {:?}:\n{:?}\n\n",
+                    path, e
+                )
+            });
+            if let Some(header) = line.strip_prefix('>') {
+                index.insert(header.trim());
+            }
+        }
+        index
+    }
+
+    /// Adds the argument `header` to this index. If an accession is found in more than one
+    /// header, the first header is retained.
+    ///
+    /// # Arguments
+    ///
+    /// * `header: &str` - A FASTA header without the leading `>`
+    pub fn insert(&mut self, header: &str) {
+        let i = self.headers.len();
+        for accession in header_accessions(header) {
+            self.accessions.entry(accession).or_insert(i);
+        }
+        self.headers.push(header.to_string());
+    }
+
+    /// Returns the header, i.e. the Hit title (`stitle`), of the argument `sacc`, if any. The
+    /// argument is looked up as it is, then as the accession of a pipe separated identifier,
+    /// e.g. `P15538` in `sp|P15538|C11B1_HUMAN`, and finally without its version suffix.
+    ///
+    /// # Arguments
+    ///
+    /// * `sacc: &str` - The Hit's identifier (`sacc` in Blast terminology)
+    pub fn get(&self, sacc: &str) -> Option<&str> {
+        lookup_accessions(sacc)
+            .iter()
+            .find_map(|accession| self.accessions.get(*accession))
+            .map(|i| self.headers[*i].as_str())
+    }
+}
+
+/// A memory mapped index file of the headers of a reference FASTA. Unlike `ReferenceIndex` it is
+/// built once, see `build`, and opening it reads nothing but its preamble, so even indices of huge
+/// references, e.g. UniRef100, are available instantly. Other records than FASTA headers can be
+/// indexed with `build_from_records`, e.g. the serialized entries of a UniProt flat file. The file
+/// consists of:
+///
+/// 1. The preamble: the magic identifying the kind of index, e.g. `INDEX_FILE_MAGIC`, the number
+///    of headers, the number of accessions, and the length of all headers in bytes.
+/// 2. The offsets of the headers, one more than there are headers.
+/// 3. The offsets of the accessions, one more than there are accessions.
+/// 4. The index of the header of each accession.
+/// 5. The concatenated headers.
+/// 6. The concatenated accessions in lexicographical order.
+///
+/// All numbers are little endian `u64`. Accessions are looked up by binary search.
+#[derive(Debug)]
+pub struct ReferenceIndexFile {
+    /// The path the index was opened from, used in error messages.
+    pub path: String,
+    /// The memory mapped index file.
+    mmap: Mmap,
+    /// The number of indexed headers.
+    pub n_headers: usize,
+    /// The number of indexed accessions.
+    pub n_accessions: usize,
+    /// The byte offset of the concatenated headers.
+    headers_start: usize,
+    /// The byte offset of the concatenated accessions.
+    accessions_start: usize,
+}
+
+impl ReferenceIndexFile {
+    /// Indexes the headers of the (possibly compressed) reference FASTA found at the argument
+    /// `fasta_path` and writes the index file to the argument `index_path`. The headers are
+    /// written into a temporary file next to the index while the FASTA is read, so only the
+    /// accessions are held in memory. If an accession is found in more than one header, the first
+    /// header is retained. Returns the number of indexed headers and accessions.
+    ///
+    /// # Arguments
+    ///
+    /// * `fasta_path: &str` - The path to the reference FASTA
+    /// * `index_path: &str` - The path to write the index file to
+    pub fn build(fasta_path: &str, index_path: &str) -> io::Result<(usize, usize)> {
+        let headers = open_input_file(fasta_path)?
+            .lines()
+            .filter_map(|line| match line {
+                Ok(line) => line.strip_prefix('>').map(|header| {
+                    let header = header.trim();
+                    Ok((header.to_string(), header_accessions(header)))
+                }),
+                Err(e) => Some(Err(e)),
+            });
+        ReferenceIndexFile::build_from_records(headers, INDEX_FILE_MAGIC, index_path)
+    }
+
+    /// Writes the index file of the argument `records` to the argument `index_path`. Each record
+    /// is stored as a header and can be looked up by each of its accessions. The headers are
+    /// written into a temporary file next to the index while the records are consumed, so only
+    /// the accessions are held in memory. If an accession is found in more than one record, the
+    /// first record is retained. Returns the number of indexed records and accessions. If a record
+    /// cannot be read or the index cannot be written, neither the temporary file nor a partially
+    /// written index is left behind.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The records, i.e. pairs of a header and its accessions, to index
+    /// * `magic: &[u8; 8]` - The first bytes of the index file, identifying the kind of records
+    /// * `index_path: &str` - The path to write the index file to
+    pub fn build_from_records<I>(
+        records: I,
+        magic: &[u8; 8],
+        index_path: &str,
+    ) -> io::Result<(usize, usize)>
+    where
+        I: IntoIterator<Item = io::Result<(String, Vec<String>)>>,
+    {
+        let headers_path = format!("{}.headers.tmp", index_path);
+        let indexed = ReferenceIndexFile::write_headers(records, &headers_path).and_then(
+            |(header_offsets, accessions)| {
+                ReferenceIndexFile::write_index(
+                    &header_offsets,
+                    &accessions,
+                    magic,
+                    &headers_path,
+                    index_path,
+                )
+                .inspect_err(|_| {
+                    let _ = fs::remove_file(index_path);
+                })?;
+                Ok((header_offsets.len() - 1, accessions.len()))
+            },
+        );
+        if indexed.is_ok() {
+            fs::remove_file(&headers_path)?;
+        } else {
+            let _ = fs::remove_file(&headers_path);
+        }
+        indexed
+    }
+
+    /// Writes the headers of the argument `records` into the temporary file `headers_path`.
+    /// Returns the offsets of the headers, one more than there are headers, and the accessions
+    /// with the index of their header, sorted and without duplicates (see `build_from_records`).
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The records, i.e. pairs of a header and its accessions, to index
+    /// * `headers_path: &str` - The path to write the concatenated headers to
+    fn write_headers<I>(
+        records: I,
+        headers_path: &str,
+    ) -> io::Result<(Vec<u64>, Vec<AccessionHeader>)>
+    where
+        I: IntoIterator<Item = io::Result<(String, Vec<String>)>>,
+    {
+        let mut headers_writer = BufWriter::new(File::create(headers_path)?);
+        let mut header_offsets: Vec<u64> = vec![0];
+        let mut accessions: Vec<AccessionHeader> = vec![];
+        for record in records {
+            let (header, header_accessions) = record?;
+            let i = (header_offsets.len() - 1) as u64;
+            for accession in header_accessions {
+                accessions.push((accession, i));
+            }
+            headers_writer.write_all(header.as_bytes())?;
+            header_offsets.push(header_offsets[header_offsets.len() - 1] + header.len() as u64);
+        }
+        headers_writer.flush()?;
+        drop(headers_writer);
+        // The stable sort keeps the first header of duplicated accessions in front:
+        accessions.sort_by(|a, b| a.0.cmp(&b.0));
+        accessions.dedup_by(|a, b| a.0 == b.0);
+        Ok((header_offsets, accessions))
+    }
+
+    /// Writes the index file to the argument `index_path` (see `ReferenceIndexFile`), copying the
+    /// concatenated headers from the temporary file `headers_path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_offsets: &[u64]` - The offsets of the headers, one more than there are headers
+    /// * `accessions: &[AccessionHeader]` - The sorted accessions with the index of their header
+    /// * `magic: &[u8; 8]` - The first bytes of the index file, identifying the kind of records
+    /// * `headers_path: &str` - The path to the concatenated headers
+    /// * `index_path: &str` - The path to write the index file to
+    fn write_index(
+        header_offsets: &[u64],
+        accessions: &[AccessionHeader],
+        magic: &[u8; 8],
+        headers_path: &str,
+        index_path: &str,
+    ) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(index_path)?);
+        writer.write_all(magic)?;
+        for n in [
+            (header_offsets.len() - 1) as u64,
+            accessions.len() as u64,
+            header_offsets[header_offsets.len() - 1],
+        ] {
+            writer.write_all(&n.to_le_bytes())?;
+        }
+        for offset in header_offsets {
+            writer.write_all(&offset.to_le_bytes())?;
+        }
+        let mut accession_offset: u64 = 0;
+        writer.write_all(&accession_offset.to_le_bytes())?;
+        for (accession, _) in accessions {
+            accession_offset += accession.len() as u64;
+            writer.write_all(&accession_offset.to_le_bytes())?;
+        }
+        for (_, i) in accessions {
+            writer.write_all(&i.to_le_bytes())?;
+        }
+        io::copy(&mut File::open(headers_path)?, &mut writer)?;
+        for (accession, _) in accessions {
+            writer.write_all(accession.as_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// Memory maps the index file found at the argument `path`. Panics if the file cannot be read
+    /// or is not an index file written by `build`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path: &str` - The path to the index file
+    pub fn open(path: &str) -> ReferenceIndexFile {
+        ReferenceIndexFile::open_with_magic(path, INDEX_FILE_MAGIC)
+    }
+
+    /// Memory maps the index file found at the argument `path`. Panics if the file cannot be read
+    /// or does not start with the argument `magic`, i.e. is not the expected kind of index.
+    ///
+    /// # Arguments
+    ///
+    /// * `path: &str` - The path to the index file
+    /// * `magic: &[u8; 8]` - The first bytes of the expected kind of index file
+    pub fn open_with_magic(path: &str, magic: &[u8; 8]) -> ReferenceIndexFile {
+        let file = File::open(path).unwrap_or_else(|e| {
+            panic!(
+                "\n\nCould not read reference index {:?}:\n{:?}\n\n",
+                path, e
+            )
+        });
+        // Safety: The index is only read. Modifying it while prot-scriber runs is not supported.
+        let mmap = unsafe { Mmap::map(&file) }.unwrap_or_else(|e| {
+            panic!(
+                "\n\nCould not memory map reference index {:?}:\n{:?}\n\n",
+                path, e
+            )
+        });
+        if mmap.len() < INDEX_FILE_PREAMBLE_LEN || &mmap[..8] != magic {
+            panic!("\n\nFile {:?} is not the expected kind of index. Please build it with 'prot-scriber index'.\n\n", path);
+        }
+        let read_u64 = |i: usize| u64::from_le_bytes(mmap[8 * i..8 * (i + 1)].try_into().unwrap());
+        let n_headers = read_u64(1) as usize;
+        let n_accessions = read_u64(2) as usize;
+        let headers_len = read_u64(3) as usize;
+        // The preamble is not trusted: The offset tables and headers must fit into the file,
+        // before any offset is read from them:
+        let headers_start = n_accessions
+            .checked_mul(2)
+            .and_then(|n| n.checked_add(n_headers))
+            .and_then(|n| n.checked_add(2))
+            .and_then(|n| n.checked_mul(8))
+            .and_then(|n| n.checked_add(INDEX_FILE_PREAMBLE_LEN));
+        let (headers_start, accessions_start) = match headers_start
+            .and_then(|start| Some((start, start.checked_add(headers_len)?)))
+        {
+            Some((headers_start, accessions_start)) if accessions_start <= mmap.len() => {
+                (headers_start, accessions_start)
+            }
+            _ => panic!("\n\nReference index {:?} is truncated or corrupt. Please rebuild it with 'prot-scriber index'.\n\n", path),
+        };
+        let index = ReferenceIndexFile {
+            path: path.to_string(),
+            mmap,
+            n_headers,
+            n_accessions,
+            headers_start,
+            accessions_start,
+        };
+        if index.mmap.len() - accessions_start != index.accession_offset(n_accessions) {
+            panic!("\n\nReference index {:?} is truncated or corrupt. Please rebuild it with 'prot-scriber index'.\n\n", path);
+        }
+        index
+    }
+
+    /// Returns the header, i.e. the Hit title (`stitle`), of the argument `sacc`, if any. The
+    /// argument is looked up like in `ReferenceIndex::get`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sacc: &str` - The Hit's identifier (`sacc` in Blast terminology)
+    pub fn get(&self, sacc: &str) -> Option<&str> {
+        lookup_accessions(sacc)
+            .iter()
+            .find_map(|accession| self.find(accession))
+            .map(|i| self.header(i))
+    }
+
+    /// Returns the index of the header of the argument `accession`, if it is indexed.
+    fn find(&self, accession: &str) -> Option<usize> {
+        let (mut low, mut high) = (0, self.n_accessions);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.accession(mid).cmp(accession.as_bytes()) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => {
+                    return Some(self.read_u64(self.n_headers + 1 + self.n_accessions + 1 + mid))
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the `i`-th header of this index.
+    fn header(&self, i: usize) -> &str {
+        let start = self.headers_start + self.read_u64(i);
+        let end = self.headers_start + self.read_u64(i + 1);
+        std::str::from_utf8(&self.mmap[start..end]).unwrap_or_else(|_| {
+            panic!(
+                "\n\nReference index {:?} is corrupt. Please rebuild it with 'prot-scriber index'.\n\n",
+                self.path
+            )
+        })
+    }
+
+    /// Returns the `i`-th accession of this index in lexicographical order.
+    fn accession(&self, i: usize) -> &[u8] {
+        let start = self.accessions_start + self.accession_offset(i);
+        let end = self.accessions_start + self.accession_offset(i + 1);
+        &self.mmap[start..end]
+    }
+
+    /// Returns the offset of the `i`-th accession relative to the concatenated accessions.
+    fn accession_offset(&self, i: usize) -> usize {
+        self.read_u64(self.n_headers + 1 + i)
+    }
+
+    /// Reads the `i`-th number following the preamble of this index.
+    fn read_u64(&self, i: usize) -> usize {
+        let start = INDEX_FILE_PREAMBLE_LEN + 8 * i;
+        u64::from_le_bytes(self.mmap[start..start + 8].try_into().unwrap()) as usize
+    }
+}
+
+/// Returns `true` if the file found at the argument `path` starts with `INDEX_FILE_MAGIC`, i.e. is
+/// an index file written by `ReferenceIndexFile::build`.
+///
+/// # Arguments
+///
+/// * `path: &str` - The path to the file
+pub fn is_index_file(path: &str) -> bool {
+    starts_with_magic(path, INDEX_FILE_MAGIC)
+}
+
+/// Returns `true` if the file found at the argument `path` starts with the argument `magic`.
+///
+/// # Arguments
+///
+/// * `path: &str` - The path to the file
+/// * `magic: &[u8; 8]` - The first bytes of a kind of index file, e.g. `INDEX_FILE_MAGIC`
+pub fn starts_with_magic(path: &str, magic: &[u8; 8]) -> bool {
+    let mut first_bytes = [0u8; 8];
+    File::open(path)
+        .and_then(|mut file| io::Read::read_exact(&mut file, &mut first_bytes))
+        .map(|_| &first_bytes == magic)
+        .unwrap_or(false)
+}
+
+/// Returns the accessions the argument `sacc` is looked up with, in order. These are the argument
+/// as it is, the accession of a pipe separated identifier, e.g. `P15538` in
+/// `sp|P15538|C11B1_HUMAN`, and the latter without its version suffix.
+pub fn lookup_accessions(sacc: &str) -> [&str; 3] {
+    let sacc = sacc.trim();
+    let pipe_accession = sacc.split('|').nth(1).unwrap_or(sacc);
+    [sacc, pipe_accession, strip_version(pipe_accession)]
+}
+
+/// Returns the accessions the argument `header` can be looked up with. These are the first word of
+/// the header, the accession of a pipe separated identifier, e.g. `P15538` in
+/// `sp|P15538|C11B1_HUMAN`, and the accessions without their version suffix, e.g. `XP_006345678`
+/// for `XP_006345678.1`.
+///
+/// # Arguments
+///
+/// * `header: &str` - A FASTA header without the leading `>`
+pub fn header_accessions(header: &str) -> Vec<String> {
+    let identifier = header.split_whitespace().next().unwrap_or("");
+    let mut accessions: Vec<String> = vec![];
+    for accession in [
+        identifier,
+        identifier.split('|').nth(1).unwrap_or(identifier),
+    ] {
+        for key in [accession, strip_version(accession)] {
+            if !key.is_empty() && !accessions.iter().any(|a| a == key) {
+                accessions.push(key.to_string());
+            }
+        }
+    }
+    accessions
+}
+
+/// Returns the argument `accession` without its version suffix, e.g. `XP_006345678` for
+/// `XP_006345678.1`. Accessions without a numeric version are returned unchanged.
+fn strip_version(accession: &str) -> &str {
+    match accession.rsplit_once('.') {
+        Some((stem, version))
+            if !stem.is_empty()
+                && !version.is_empty()
+                && version.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            stem
+        }
+        _ => accession,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn indexes_reference_fasta_headers() {
+        let fasta = Path::new("misc").join("reference_proteins.fasta");
+        let index = ReferenceIndex::from_fasta(fasta.to_str().unwrap());
+        assert_eq!(2, index.headers.len());
+        let human = "sp|P15538|C11B1_HUMAN Cytochrome P450 11B1, mitochondrial OS=Homo sapiens OX=9606 GN=CYP11B1 PE=1 SV=5";
+        for sacc in ["sp|P15538|C11B1_HUMAN", "P15538", "tr|P15538|"] {
+            assert_eq!(Some(human), index.get(sacc));
+        }
+        let potato = "XP_006345678.1 receptor kinase [Solanum tuberosum]";
+        for sacc in ["XP_006345678.1", "XP_006345678", "XP_006345678.2"] {
+            assert_eq!(Some(potato), index.get(sacc));
+        }
+        assert_eq!(None, index.get("Q1MX30"));
+    }
+
+    #[test]
+    fn looks_up_hit_titles_in_reference_index_file() {
+        let fasta = Path::new("misc").join("reference_proteins_duplicated_accessions.fasta");
+        let index_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_reference_index_file_test.psri",
+            std::process::id()
+        ));
+        let (n_headers, n_accessions) =
+            ReferenceIndexFile::build(fasta.to_str().unwrap(), index_path.to_str().unwrap())
+                .unwrap();
+        assert_eq!(4, n_headers);
+        assert_eq!(7, n_accessions);
+        assert!(is_index_file(index_path.to_str().unwrap()));
+        assert!(!is_index_file(fasta.to_str().unwrap()));
+        let index = ReferenceTitles::open(index_path.to_str().unwrap());
+        let in_memory = ReferenceTitles::open(fasta.to_str().unwrap());
+        assert!(matches!(index, ReferenceTitles::OnDisk(_)));
+        assert!(matches!(in_memory, ReferenceTitles::InMemory(_)));
+        for sacc in [
+            "sp|P15538|C11B1_HUMAN",
+            "P15538",
+            "XP_006345678.1",
+            "XP_006345678",
+            "XP_006345678.2",
+            "A0A0B4J2F0",
+            "Q1MX30",
+        ] {
+            assert_eq!(in_memory.get(sacc), index.get(sacc));
+        }
+        assert_eq!(
+            Some("XP_006345678.1 receptor kinase [Solanum tuberosum]"),
+            index.get("XP_006345678")
+        );
+        assert_eq!(
+            Some("XP_006345678.2 Duplicated accession"),
+            index.get("XP_006345678.2")
+        );
+        assert_eq!(None, index.get("Q1MX30"));
+        std::fs::remove_file(index_path).unwrap();
+    }
+
+    #[test]
+    fn rejects_index_files_with_corrupt_preambles() {
+        let fasta = Path::new("misc").join("reference_proteins.fasta");
+        let index_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_corrupt_reference_index_file_test.psri",
+            std::process::id()
+        ));
+        let index_path = index_path.to_str().unwrap();
+        ReferenceIndexFile::build(fasta.to_str().unwrap(), index_path).unwrap();
+        let index = fs::read(index_path).unwrap();
+        // An overflowing number of accessions, and a number of headers exceeding the file:
+        for (i, n) in [(2, u64::MAX), (1, 1 << 40)] {
+            let mut corrupt = index.clone();
+            corrupt[8 * i..8 * (i + 1)].copy_from_slice(&n.to_le_bytes());
+            fs::write(index_path, &corrupt).unwrap();
+            let error = std::panic::catch_unwind(|| ReferenceIndexFile::open(index_path))
+                .unwrap_err()
+                .downcast::<String>()
+                .unwrap();
+            assert!(error.contains("is truncated or corrupt"));
+        }
+        fs::remove_file(index_path).unwrap();
+    }
+
+    #[test]
+    fn leaves_no_files_behind_on_unreadable_records() {
+        // The compressed stream ends prematurely:
+        let fasta = Path::new("misc").join("truncated_blast_table.txt.gz");
+        let index_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_truncated_reference_index_file_test.psri",
+            std::process::id()
+        ));
+        let index_path = index_path.to_str().unwrap();
+        assert!(ReferenceIndexFile::build(fasta.to_str().unwrap(), index_path).is_err());
+        assert!(!Path::new(index_path).exists());
+        assert!(!Path::new(&format!("{}.headers.tmp", index_path)).exists());
+    }
+}
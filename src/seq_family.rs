@@ -1,5 +1,8 @@
+use super::hit::HitWeightingMode;
 use super::query::Query;
-use crate::generate_hrd_associated_funcs::generate_human_readable_description;
+use crate::generate_hrd_associated_funcs::{
+    generate_human_readable_description, generate_weighted_human_readable_description,
+};
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -9,6 +12,13 @@ use std::collections::HashMap;
 pub struct SeqFamily {
     /// The biological sequence identifiers this SeqFamily comprises:
     pub query_ids: Vec<String>,
+    /// The species each of the above `query_ids` stems from, in the same order, e.g. as given by
+    /// OrthoFinder's `Orthogroups.tsv`. Empty, if the species are not known.
+    pub species: Vec<String>,
+    /// The identifier of the family this family is a subfamily of, if any, e.g. the orthogroup of
+    /// an OrthoFinder hierarchical orthogroup (HOG). The members of a subfamily are members of
+    /// its parent family, too (see `AnnotationProcess::insert_seq_family`).
+    pub parent_id: Option<String>,
     /// Those Query-Identifiers for which all input sequence similarity search result files have
     /// produced data. So in other words those Query-IDs that are ready to be used as input for the
     /// generation of a human readable description:
@@ -52,6 +62,18 @@ impl SeqFamily {
         self.query_ids_with_complete_data.push(query_indx);
     }
 
+    /// Returns the distinct species of this family's members in alphabetical order.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - a reference to an instance of SeqFamily
+    pub fn distinct_species(&self) -> Vec<String> {
+        let mut species = self.species.clone();
+        species.sort();
+        species.dedup();
+        species
+    }
+
     /// Generates and returns a human readable description (`String`) for this set (family) of
     /// biological query sequences.
     ///
@@ -66,31 +88,59 @@ impl SeqFamily {
     /// to identify non informative words, that receive only a minimum score.
     /// * `center_at_quantile` - A real value between zero and one used to center the inverse
     /// information content scores.
+    /// * `hit_weighting` - How to weight the Hit descriptions. Note that weights are normalized
+    ///   per query (see `Query::weighted_descriptions`) and include the Hits' evidence weights.
     pub fn annotate(
         &self,
         queries: &HashMap<String, Query>,
         split_regex: &Regex,
         non_informative_words_regexs: &Vec<Regex>,
         center_at_quantile: &f64,
+        hit_weighting: &HitWeightingMode,
     ) -> Option<String> {
-        let mut hit_descriptions: Vec<String> = vec![];
+        let mut hit_descriptions: Vec<(String, f64, f64)> = vec![];
         // Gather all Hit descriptions of all queries belonging to this sequence family. This
         // means collecting all queries' hit-descriptions:
         for qid in self.query_ids.iter() {
             // If the searches found hits of significant similarity for the query sequence:
             if queries.contains_key(qid) {
-                for (_, hit_desc) in &queries.get(qid).unwrap().hits {
-                    hit_descriptions.push(hit_desc.clone());
-                }
+                hit_descriptions.extend(
+                    queries
+                        .get(qid)
+                        .unwrap()
+                        .weighted_descriptions(hit_weighting),
+                );
             }
         }
         if hit_descriptions.len() > 0 {
-            generate_human_readable_description(
-                &hit_descriptions,
-                split_regex,
-                non_informative_words_regexs,
-                center_at_quantile,
-            )
+            if *hit_weighting == HitWeightingMode::None
+                && hit_descriptions
+                    .iter()
+                    .all(|(_, _, evidence)| *evidence == 1.0)
+            {
+                generate_human_readable_description(
+                    &hit_descriptions
+                        .into_iter()
+                        .map(|(desc, _, _)| desc)
+                        .collect(),
+                    split_regex,
+                    non_informative_words_regexs,
+                    center_at_quantile,
+                )
+            } else {
+                let (descriptions, evidence_weights): (Vec<(String, f64)>, Vec<f64>) =
+                    hit_descriptions
+                        .into_iter()
+                        .map(|(desc, weight, evidence_weight)| ((desc, weight), evidence_weight))
+                        .unzip();
+                generate_weighted_human_readable_description(
+                    &descriptions,
+                    Some(&evidence_weights),
+                    split_regex,
+                    non_informative_words_regexs,
+                    center_at_quantile,
+                )
+            }
         } else {
             None
         }
@@ -116,6 +166,22 @@ mod tests {
         assert!(sf1.all_query_data_complete());
     }
 
+    #[test]
+    fn distinct_species_works() {
+        let mut sf1 = SeqFamily::new();
+        sf1.query_ids = vec!["At1".to_string(), "At2".to_string(), "Os1".to_string()];
+        assert!(sf1.distinct_species().is_empty());
+        sf1.species = vec![
+            "Arabidopsis_thaliana".to_string(),
+            "Oryza_sativa".to_string(),
+            "Arabidopsis_thaliana".to_string(),
+        ];
+        assert_eq!(
+            vec!["Arabidopsis_thaliana", "Oryza_sativa"],
+            sf1.distinct_species()
+        );
+    }
+
     #[test]
     fn mark_query_id_with_complete_data_works() {
         let mut sf1 = SeqFamily::new();
@@ -0,0 +1,178 @@
+//! Inferring sequence families from the Hits queries share, for when no families file is given,
+//! is implemented in this module.
+use super::query::Query;
+use std::collections::HashMap;
+
+/// Groups the argument `queries` into families by the subject accessions (`sacc`) of their Hits.
+/// Two queries are linked, if they share at least one Hit and the Jaccard similarity of their
+/// Hits' accessions is at least the argument `min_jaccard`. Families are the connected components
+/// of the resulting graph, i.e. a `min_jaccard` of zero links all queries sharing any Hit. Queries
+/// not linked to any other query are not grouped. Returns the families' members sorted
+/// alphabetically, the families ordered by their first member.
+///
+/// Hub Hits, i.e. Hits of more queries than the argument `max_queries_per_hit`, e.g. of a
+/// ubiquitous domain, are ignored, as if the queries had not found them. Comparing all queries
+/// sharing a Hit takes time quadratic in the number of these queries, so ignoring hubs bounds the
+/// time per Hit. Note that the caller has to hold all queries with their Hits in memory until the
+/// families are inferred.
+///
+/// # Arguments
+///
+/// * `queries: &HashMap<String, Query>` - The queries with their Hits, keyed by identifier
+/// * `min_jaccard: f64` - The minimum Jaccard similarity of two queries' Hits to link them
+/// * `max_queries_per_hit: usize` - The maximum number of queries a Hit is used to link
+pub fn infer_seq_families(
+    queries: &HashMap<String, Query>,
+    min_jaccard: f64,
+    max_queries_per_hit: usize,
+) -> Vec<Vec<String>> {
+    let mut query_ids: Vec<&String> = queries.keys().collect();
+    query_ids.sort();
+    // Index the queries by the accessions of their Hits, so only queries sharing Hits are
+    // compared:
+    let mut queries_by_sacc: HashMap<&String, Vec<usize>> = HashMap::new();
+    for (indx, query_id) in query_ids.iter().enumerate() {
+        for sacc in queries[*query_id].hits.keys() {
+            queries_by_sacc.entry(sacc).or_default().push(indx);
+        }
+    }
+    queries_by_sacc.retain(|_, sacc_query_indxs| sacc_query_indxs.len() <= max_queries_per_hit);
+    // The number of each query's Hits that are not hubs:
+    let n_hits: Vec<usize> = query_ids
+        .iter()
+        .map(|query_id| {
+            queries[*query_id]
+                .hits
+                .keys()
+                .filter(|sacc| queries_by_sacc.contains_key(sacc))
+                .count()
+        })
+        .collect();
+    let mut components = DisjointSets::new(query_ids.len());
+    let mut linked = vec![false; query_ids.len()];
+    for (indx, query_id) in query_ids.iter().enumerate() {
+        // Count the Hits shared with each query of a higher index:
+        let mut n_shared: HashMap<usize, usize> = HashMap::new();
+        for sacc in queries[*query_id].hits.keys() {
+            for other_indx in queries_by_sacc.get(sacc).into_iter().flatten() {
+                if *other_indx > indx {
+                    *n_shared.entry(*other_indx).or_default() += 1;
+                }
+            }
+        }
+        for (other_indx, shared) in n_shared {
+            let jaccard = shared as f64 / (n_hits[indx] + n_hits[other_indx] - shared) as f64;
+            if jaccard >= min_jaccard {
+                components.union(indx, other_indx);
+                linked[indx] = true;
+                linked[other_indx] = true;
+            }
+        }
+    }
+    let mut families: HashMap<usize, Vec<String>> = HashMap::new();
+    for (indx, query_id) in query_ids.iter().enumerate() {
+        if linked[indx] {
+            families
+                .entry(components.find(indx))
+                .or_default()
+                .push((*query_id).clone());
+        }
+    }
+    // Members are sorted, because the queries were:
+    let mut families: Vec<Vec<String>> = families.into_values().collect();
+    families.sort();
+    families
+}
+
+/// Disjoint sets of indices (union-find), used to find the connected components of the graph of
+/// linked queries.
+struct DisjointSets {
+    /// The parent of each index, roots being their own parent.
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    /// Returns `n` sets, each holding a single index.
+    fn new(n: usize) -> DisjointSets {
+        DisjointSets {
+            parents: (0..n).collect(),
+        }
+    }
+
+    /// Returns the root of the set holding the argument `indx`, compressing the path to it.
+    fn find(&mut self, indx: usize) -> usize {
+        let mut root = indx;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut node = indx;
+        while self.parents[node] != root {
+            let parent = self.parents[node];
+            self.parents[node] = root;
+            node = parent;
+        }
+        root
+    }
+
+    /// Merges the sets holding the arguments `a` and `b`.
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parents[root_b] = root_a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hit;
+
+    fn query(saccs: &[&str]) -> Query {
+        let mut query = Query::new();
+        for sacc in saccs {
+            query.hits.insert(sacc.to_string(), Hit::default());
+        }
+        query
+    }
+
+    #[test]
+    fn infers_seq_families_from_shared_hits() {
+        let mut queries = HashMap::new();
+        queries.insert("Query1".to_string(), query(&["A", "B", "C"]));
+        queries.insert("Query2".to_string(), query(&["A", "B"]));
+        queries.insert("Query3".to_string(), query(&["C", "D", "E", "F"]));
+        queries.insert("Query4".to_string(), query(&["X"]));
+        queries.insert("Query5".to_string(), query(&["X", "Y"]));
+        queries.insert("Query6".to_string(), query(&["Z"]));
+        // Any shared Hit links queries:
+        assert_eq!(
+            vec![vec!["Query1", "Query2", "Query3"], vec!["Query4", "Query5"]],
+            infer_seq_families(&queries, 0.0, usize::MAX)
+        );
+        // Query1 and Query3 share one of six Hits, Query4 and Query5 one of two:
+        assert_eq!(
+            vec![vec!["Query1", "Query2"], vec!["Query4", "Query5"]],
+            infer_seq_families(&queries, 0.5, usize::MAX)
+        );
+        assert!(infer_seq_families(&queries, 1.0, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn ignores_hub_hits() {
+        let mut queries = HashMap::new();
+        queries.insert("Query1".to_string(), query(&["Hub", "A"]));
+        queries.insert("Query2".to_string(), query(&["Hub", "A"]));
+        queries.insert("Query3".to_string(), query(&["Hub", "B"]));
+        queries.insert("Query4".to_string(), query(&["Hub"]));
+        assert_eq!(
+            vec![vec!["Query1", "Query2", "Query3", "Query4"]],
+            infer_seq_families(&queries, 0.0, 4)
+        );
+        // Without the hub, Query1 and Query2 share all of their Hits:
+        assert_eq!(
+            vec![vec!["Query1", "Query2"]],
+            infer_seq_families(&queries, 1.0, 3)
+        );
+    }
+}
@@ -1,13 +1,52 @@
 use crate::annotation_process::AnnotationProcess;
+use crate::input_reader::open_input_file;
 use crate::seq_family::SeqFamily;
 use regex::Regex;
 use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
+
+/// The formats of files holding sets of biological sequence identifiers (a.k.a. gene families).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeqFamilyFormat {
+    /// One family per line, its identifier separated from the list of its genes, e.g.
+    /// `fam-name<TAB>gene1,gene2,gene3`.
+    Default,
+    /// OrthoFinder's `Orthogroups.tsv`, i.e. a header row `Orthogroup<TAB>species1<TAB>...` and
+    /// one orthogroup per row with a column of comma separated genes for each species.
+    OrthoFinder,
+    /// OrthoFinder's hierarchical orthogroups (HOGs), e.g. `Phylogenetic_Hierarchical_Orthogroups/
+    /// N0.tsv`, i.e. a header row `HOG<TAB>OG<TAB>Gene Tree Parent Clade<TAB>species1<TAB>...` and
+    /// one HOG per row. Each HOG is read as subfamily of its orthogroup (`OG` column).
+    OrthoFinderHog,
+    /// Raw MCL output, i.e. one cluster of genes per line without identifier. Identifiers are
+    /// generated from a prefix and the zero-padded line number of the cluster (see
+    /// `generate_seq_family_id`).
+    Mcl,
+}
+
+impl SeqFamilyFormat {
+    /// Parses the argument `format_arg` into a `SeqFamilyFormat` (case insensitive). Returns
+    /// `None` if the argument does not name a known format.
+    ///
+    /// # Arguments
+    ///
+    /// * `format_arg: &str` - The name of the format, e.g. as passed on the command line.
+    pub fn from_arg(format_arg: &str) -> Option<SeqFamilyFormat> {
+        match format_arg.trim().to_lowercase().as_str() {
+            "default" => Some(SeqFamilyFormat::Default),
+            "orthofinder" => Some(SeqFamilyFormat::OrthoFinder),
+            "orthofinder-hog" => Some(SeqFamilyFormat::OrthoFinderHog),
+            "mcl" => Some(SeqFamilyFormat::Mcl),
+            _ => None,
+        }
+    }
+}
 
 /// Parses line by line of the argument file `path` in which sets of biological sequence
-/// identifiers (a.k.a. gene families) are stored; one family per line. Each parsed family is
-/// stored in the argument `annotation_process`.
+/// identifiers (a.k.a. gene families) are stored; one family per line. The file is read in the
+/// argument `annotation_process`'s `seq_family_format`. Each parsed family is stored in the
+/// argument `annotation_process`. Gzip, bgzip, and zstd compressed files are decompressed on the
+/// fly.
 ///
 /// # Arguments
 ///
@@ -16,20 +55,154 @@ use std::io::{BufRead, BufReader};
 pub fn parse_seq_families_file(path: &str, annotation_process: &mut AnnotationProcess) {
     // Open stream to the gene families input file
     let file_path = path.to_string();
-    let file = File::open(path).unwrap();
-    let reader = BufReader::new(file);
+    let reader = open_input_file(path).unwrap();
+    // The species of the columns of OrthoFinder's `Orthogroups.tsv` or `N0.tsv`, read from its
+    // header row:
+    let mut species: Option<Vec<String>> = None;
     // read file line by line
     for (i, line) in reader.lines().enumerate() {
         let family_line = line.unwrap();
 
         // parse line. panic if malformatted, add to the annotation_process if OK
-        match parse_seq_family(family_line, &annotation_process.seq_family_id_genes_separator, &annotation_process.seq_family_gene_ids_separator) {
+        let parsed = match annotation_process.seq_family_format {
+            SeqFamilyFormat::Default => parse_seq_family(
+                family_line,
+                &annotation_process.seq_family_id_genes_separator,
+                &annotation_process.seq_family_gene_ids_separator,
+            ),
+            SeqFamilyFormat::OrthoFinder => match &species {
+                None => {
+                    species = Some(parse_orthofinder_header(&family_line, 1));
+                    continue;
+                }
+                Some(species) => parse_orthofinder_family(&family_line, species),
+            },
+            SeqFamilyFormat::OrthoFinderHog => match &species {
+                None => {
+                    species = Some(parse_orthofinder_header(&family_line, 3));
+                    continue;
+                }
+                Some(species) => parse_orthofinder_hog(&family_line, species),
+            },
+            SeqFamilyFormat::Mcl => {
+                // Skip empty lines, but keep counting them, so identifiers match line numbers:
+                if family_line.trim().is_empty() {
+                    continue;
+                }
+                let seq_fam_name = generate_seq_family_id(
+                    &annotation_process.seq_family_id_prefix,
+                    i + 1,
+                    annotation_process.seq_family_id_digits,
+                );
+                parse_seq_family(
+                    format!("{}\t{}", seq_fam_name, family_line),
+                    &"\t".to_string(),
+                    &annotation_process.seq_family_gene_ids_separator,
+                )
+            }
+        };
+        match parsed {
             Ok((seq_fam_name, seq_fam_instance)) => {
                 annotation_process.insert_seq_family(seq_fam_name, seq_fam_instance)
             }
-            Err(e) => panic!("\n\n{:?} in file {:?} line <{:?}>. The expected format is \"<family-name>TABgene1,gene2,gene3,...\"\n\n", e, file_path, i),
+            Err(e) => match annotation_process.seq_family_format {
+                SeqFamilyFormat::Default => panic!("\n\n{:?} in file {:?} line <{:?}>. The expected format is \"<family-name>TABgene1,gene2,gene3,...\"\n\n", e, file_path, i),
+                SeqFamilyFormat::Mcl => panic!("\n\n{:?} in file {:?} line <{:?}>. The expected format is MCL output, i.e. \"gene1TABgene2TABgene3...\"\n\n", e, file_path, i),
+                SeqFamilyFormat::OrthoFinderHog => panic!("\n\n{:?} in file {:?} line <{:?}>. The expected format is OrthoFinder's hierarchical orthogroups N0.tsv, i.e. a header row \"HOGTABOGTABGene Tree Parent CladeTAB<species1>TAB<species2>...\" followed by rows \"<HOG>TAB<orthogroup>TAB<clade>TABgene1, gene2TABgene3...\"\n\n", e, file_path, i),
+                SeqFamilyFormat::OrthoFinder => panic!("\n\n{:?} in file {:?} line <{:?}>. The expected format is OrthoFinder's Orthogroups.tsv, i.e. a header row \"OrthogroupTAB<species1>TAB<species2>...\" followed by rows \"<orthogroup>TABgene1, gene2TABgene3...\"\n\n", e, file_path, i),
+            },
+        }
+    }
+}
+
+/// Parses a single row of OrthoFinder's hierarchical orthogroups `N0.tsv` (see
+/// `SeqFamilyFormat::OrthoFinderHog`) into the HOG's identifier and a `SeqFamily`, whose parent
+/// is the HOG's orthogroup. The species columns are parsed like in `parse_orthofinder_family`.
+/// Returns an error, if the row has less than four columns.
+///
+/// # Arguments
+///
+/// * `family` - The single row holding the HOG
+/// * `species` - The species of the gene columns as parsed by `parse_orthofinder_header`
+fn parse_orthofinder_hog(
+    family: &str,
+    species: &[String],
+) -> Result<(String, SeqFamily), Box<dyn Error>> {
+    let family_cols: Vec<&str> = family.splitn(4, '\t').collect();
+    if family_cols.len() < 4 {
+        return Err("Malformatted hierarchical orthogroup".into());
+    }
+    let (seq_fam_name, mut seq_fam_instance) =
+        parse_orthofinder_family(&format!("{}\t{}", family_cols[0], family_cols[3]), species)?;
+    let parent_id = family_cols[1].trim();
+    if !parent_id.is_empty() && parent_id != seq_fam_name {
+        seq_fam_instance.parent_id = Some(parent_id.to_string());
+    }
+    Ok((seq_fam_name, seq_fam_instance))
+}
+
+/// Returns the identifier of the sequence family found at the argument `line_number` of raw MCL
+/// output (see `SeqFamilyFormat::Mcl`), i.e. the argument `prefix` followed by the line number
+/// zero-padded to the argument `digits`, e.g. `Seq-Fam_0000001`. Line numbers with more digits
+/// are not truncated.
+///
+/// # Arguments
+///
+/// * `prefix` - The prefix of the identifier
+/// * `line_number` - The line number, starting with one, of the family's cluster
+/// * `digits` - The minimum number of digits of the line number
+pub fn generate_seq_family_id(prefix: &str, line_number: usize, digits: usize) -> String {
+    format!("{}{:0digits$}", prefix, line_number, digits = digits)
+}
+
+/// Parses the header row of OrthoFinder's `Orthogroups.tsv` (see `SeqFamilyFormat::OrthoFinder`)
+/// or `N0.tsv` (see `SeqFamilyFormat::OrthoFinderHog`) and returns the species of its gene
+/// columns, i.e. all but the leading identifier columns.
+///
+/// # Arguments
+///
+/// * `header` - The header row, e.g. `Orthogroup<TAB>Arabidopsis_thaliana<TAB>Oryza_sativa`
+/// * `n_id_columns` - The number of leading columns not holding genes, e.g. one for
+///   `Orthogroups.tsv`
+fn parse_orthofinder_header(header: &str, n_id_columns: usize) -> Vec<String> {
+    header
+        .trim_end_matches(['\r', '\n'])
+        .split('\t')
+        .skip(n_id_columns)
+        .map(|species| species.trim().to_string())
+        .collect()
+}
+
+/// Parses a single orthogroup row of OrthoFinder's `Orthogroups.tsv` (see
+/// `SeqFamilyFormat::OrthoFinder`) into the orthogroup's identifier and a `SeqFamily`, whose
+/// members are annotated with the species of the column they were found in. Empty species
+/// columns are skipped. Returns an error, if the row has more columns than the header or no
+/// members at all.
+///
+/// # Arguments
+///
+/// * `family` - The single row holding the orthogroup
+/// * `species` - The species of the gene columns as parsed by `parse_orthofinder_header`
+fn parse_orthofinder_family(
+    family: &str,
+    species: &[String],
+) -> Result<(String, SeqFamily), Box<dyn Error>> {
+    let family_cols: Vec<&str> = family.trim_end_matches(['\r', '\n']).split('\t').collect();
+    if family_cols.len() > species.len() + 1 {
+        return Err("Orthogroup with more columns than species in the header".into());
+    }
+    let seq_fam_name = family_cols[0].trim().to_string();
+    let mut seq_fam_instance = SeqFamily::new();
+    for (genes, species_i) in family_cols[1..].iter().zip(species) {
+        for gene in genes.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+            seq_fam_instance.query_ids.push(gene.to_string());
+            seq_fam_instance.species.push(species_i.clone());
         }
     }
+    if seq_fam_name.is_empty() || seq_fam_instance.query_ids.is_empty() {
+        return Err("Malformatted orthogroup".into());
+    }
+    Ok((seq_fam_name, seq_fam_instance))
 }
 
 /// Parses a single line read from a respective "gene family input file" (see
@@ -143,6 +316,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_orthofinder_orthogroups() {
+        let mut ap = AnnotationProcess::new();
+        ap.seq_family_format = SeqFamilyFormat::OrthoFinder;
+        let p = Path::new("misc").join("Orthogroups.tsv");
+        parse_seq_families_file(p.to_str().unwrap(), &mut ap);
+        assert_eq!(ap.seq_families.len(), 3);
+        let og0 = ap.seq_families.get("OG0000000").unwrap();
+        assert_eq!(
+            og0.query_ids,
+            vec!["AT1G01010.1", "AT1G01020.1", "Os01g0100100"]
+        );
+        assert_eq!(
+            og0.species,
+            vec![
+                "Arabidopsis_thaliana",
+                "Arabidopsis_thaliana",
+                "Oryza_sativa"
+            ]
+        );
+        // Empty species columns are skipped:
+        let og1 = ap.seq_families.get("OG0000001").unwrap();
+        assert_eq!(og1.species, vec!["Oryza_sativa", "Oryza_sativa"]);
+        let og2 = ap.seq_families.get("OG0000002").unwrap();
+        assert_eq!(og2.query_ids, vec!["AT1G01030.1"]);
+        // Rows with more columns than species are malformatted:
+        let species = parse_orthofinder_header("Orthogroup\tArabidopsis_thaliana", 1);
+        assert!(
+            parse_orthofinder_family("OG0000003\tAT1G01040.1\tOs01g0100400", &species).is_err()
+        );
+        assert!(parse_orthofinder_family("OG0000003\t", &species).is_err());
+    }
+
+    #[test]
+    fn parses_orthofinder_hierarchical_orthogroups() {
+        let mut ap = AnnotationProcess::new();
+        ap.set_seq_family_format("orthofinder-hog");
+        let p = Path::new("misc").join("N0.tsv");
+        parse_seq_families_file(p.to_str().unwrap(), &mut ap);
+        // Each HOG is a subfamily of its orthogroup:
+        let hog1 = ap.seq_families.get("N0.HOG0000001").unwrap();
+        assert_eq!(hog1.query_ids, vec!["Os01g0100100"]);
+        assert_eq!(hog1.parent_id, Some("OG0000000".to_string()));
+        let og0 = ap.seq_families.get("OG0000000").unwrap();
+        assert_eq!(
+            og0.query_ids,
+            vec!["AT1G01010.1", "AT1G01020.1", "Os01g0100100"]
+        );
+        assert_eq!(
+            og0.species,
+            vec![
+                "Arabidopsis_thaliana",
+                "Arabidopsis_thaliana",
+                "Oryza_sativa"
+            ]
+        );
+        assert_eq!(5, ap.seq_families.len());
+        assert!(parse_orthofinder_hog("N0.HOG0000003\tOG0000002", &[]).is_err());
+    }
+
+    #[test]
+    fn parses_mcl_clusters() {
+        let mut ap = AnnotationProcess::new();
+        ap.set_seq_family_format("mcl");
+        let p = Path::new("misc")
+            .join("test_mcl_out_3.txt")
+            .to_str()
+            .unwrap()
+            .to_string();
+        parse_seq_families_file(&p, &mut ap);
+        assert_eq!(ap.seq_families.len(), 2);
+        assert_eq!(
+            ap.seq_families.get("Seq-Fam_0000001").unwrap().query_ids,
+            vec!["A", "B", "C"]
+        );
+        assert_eq!(
+            ap.seq_families.get("Seq-Fam_0000002").unwrap().query_ids,
+            vec!["D", "E"]
+        );
+        assert_eq!("OG01", generate_seq_family_id("OG", 1, 2));
+        assert_eq!("OG123", generate_seq_family_id("OG", 123, 2));
+    }
+
     #[test]
     fn parse_faulty_line_malformatted() {
         let line = "OG0023619|VFABAHed036490,VFABAHed036491".to_string();
@@ -1,100 +1,1040 @@
 //! Code used to parse sequence similarity search result tables is implemented in this module.
+use super::default::{
+    BLAST_FIELDS_COMMENT_LABELS, BLAST_FIELDS_COMMENT_PREFIX, HMMER_DOMTBLOUT_COLUMNS,
+    HMMER_MISSING_VALUE, HMMER_TBLOUT_COLUMNS, SEQ_SIM_TABLE_COLUMNS,
+};
+use super::hit::{EvidenceWeights, Hit};
+use super::hit_thresholds::{HitScores, HitThresholds};
+use super::input_reader::open_input_file;
 use super::model_funcs::{filter_stitle, matches_blacklist};
 use super::query::*;
+use super::reference_fasta::ReferenceTitles;
+use super::taxonomy::TaxonFilter;
+use super::uniprot_flat_file::UniProtEntries;
 use regex::Regex;
-use std::fs::File;
+use std::collections::HashMap;
 use std::io::{self, BufRead};
 use std::path::Path;
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+/// The formats of sequence similarity search results prot-scriber can read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeqSimTableFormat {
+    /// Tabular output, e.g. Blast `-outfmt 6` or Diamond `--outfmt 6`. See `parse_table`.
+    Tabular,
+    /// Blast XML output (`-outfmt 5`). See `blast_report_reader::parse_blast_xml`.
+    BlastXml,
+    /// Blast single file JSON output (`-outfmt 15`). See
+    /// `blast_report_reader::parse_blast_json`.
+    BlastJson,
+    /// HMMER per-sequence hit table (`--tblout`), e.g. of `hmmscan` against Pfam. See
+    /// `parse_hmmer_table`.
+    HmmerTblout,
+    /// HMMER per-domain hit table (`--domtblout`), e.g. of `hmmscan` against Pfam. See
+    /// `parse_hmmer_table`.
+    HmmerDomtblout,
+    /// Diamond binary alignment archive (`--outfmt 100`). See `daa_reader::parse_daa`.
+    DiamondDaa,
+}
+
+impl SeqSimTableFormat {
+    /// Parses the argument `format_arg` into a `SeqSimTableFormat` (case insensitive). Returns
+    /// `None` if the argument does not name a known format. Note that `"default"` is parsed into
+    /// `SeqSimTableFormat::Tabular`.
+    ///
+    /// # Arguments
+    ///
+    /// * `format_arg: &str` - The name of the format, e.g. as passed on the command line.
+    pub fn from_arg(format_arg: &str) -> Option<SeqSimTableFormat> {
+        match format_arg.trim().to_lowercase().as_str() {
+            "default" | "tabular" => Some(SeqSimTableFormat::Tabular),
+            "blast-xml" => Some(SeqSimTableFormat::BlastXml),
+            "blast-json" => Some(SeqSimTableFormat::BlastJson),
+            "hmmer-tblout" => Some(SeqSimTableFormat::HmmerTblout),
+            "hmmer-domtblout" => Some(SeqSimTableFormat::HmmerDomtblout),
+            "diamond-daa" => Some(SeqSimTableFormat::DiamondDaa),
+            _ => None,
+        }
+    }
+}
+
+/// The ways to handle malformed rows in sequence similarity search result tables, e.g. rows
+/// with too few columns as written by a killed search job.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MalformedRowsMode {
+    /// Malformed rows are skipped with a warning.
+    #[default]
+    Skip,
+    /// The first malformed row causes the annotation process to fail.
+    Strict,
+}
+
+impl MalformedRowsMode {
+    /// Parses the argument `mode_arg` into a `MalformedRowsMode` (case insensitive). Returns
+    /// `None` if the argument does not name a known mode. Note that `"default"` is parsed into
+    /// `MalformedRowsMode::Skip`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode_arg: &str` - The name of the mode, e.g. as passed on the command line.
+    pub fn from_arg(mode_arg: &str) -> Option<MalformedRowsMode> {
+        match mode_arg.trim().to_lowercase().as_str() {
+            "default" | "skip" => Some(MalformedRowsMode::Skip),
+            "strict" => Some(MalformedRowsMode::Strict),
+            _ => None,
+        }
+    }
+}
+
+/// The settings a single sequence similarity search result table is parsed with. These are shared
+/// by all readers of sequence similarity search results, i.e. `parse_table`, `parse_hmmer_table`,
+/// `blast_report_reader::parse_blast_xml`, `blast_report_reader::parse_blast_json`, and
+/// `daa_reader::parse_daa`. Settings a format does not support, e.g. looking up the Hit titles of
+/// a Blast report in a reference, are `None` for tables of that format (see
+/// `AnnotationProcess::validate_fields`). The default settings neither filter nor weight Hits.
+#[derive(Debug, Default)]
+pub struct TableOptions {
+    /// Hits not passing these thresholds are discarded.
+    pub hit_thresholds: HitThresholds,
+    /// The weights of the Hits' evidence, `None` meaning evidence is not weighted.
+    pub evidence_weights: Option<EvidenceWeights>,
+    /// The taxa whose Hits to exclude or down-weight, if any. Shared by all tables.
+    pub taxon_filter: Option<Arc<TaxonFilter>>,
+    /// The headers of the reference the Hits are looked up in, if the table has no `stitle`
+    /// column.
+    pub reference_titles: Option<ReferenceTitles>,
+    /// Whether to look up the Hits in the `reference_titles` even if the table has a `stitle`
+    /// column, e.g. because its titles are truncated. Hits not found in the reference keep the
+    /// title of the table.
+    pub prefer_reference_titles: bool,
+    /// The UniProt flat file entries, or their index, whose protein names are used as the
+    /// descriptions of the Hits, if any (see `insert_hit`).
+    pub uniprot_flat_file: Option<UniProtEntries>,
+    /// The list of regular expressions used to identify to be discarded descriptions (`stitle`).
+    pub blacklist_regexs: Vec<Regex>,
+    /// The list of regular expressions used to identify to be deleted matching sub-strings in
+    /// the descriptions (`stitle`).
+    pub filter_regexs: Vec<Regex>,
+    /// An `Option` of a vector of tuples, pairing a regular expression and the capture-group
+    /// replacement string (see `model_funcs::filter_stitle` for details).
+    pub capture_replace_pairs: Option<Vec<(fancy_regex::Regex, String)>>,
+    /// Whether to skip malformed rows or to fail.
+    pub malformed_rows_mode: MalformedRowsMode,
+}
+
+impl TableOptions {
+    /// Sends the completely parsed argument `query` using the argument `transmitter`, after
+    /// applying these options' per query thresholds, taxon filter, and evidence weights (see
+    /// `send_query`).
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the options the query's table is parsed with
+    /// * `transmitter: &Sender<(String, Query)>` - Used to send the query to any receiver.
+    /// * `qacc: String` - The query's identifier.
+    /// * `query: Query` - The completely parsed query.
+    pub fn send_query(&self, transmitter: &Sender<(String, Query)>, qacc: String, query: Query) {
+        send_query(
+            transmitter,
+            qacc,
+            query,
+            &self.hit_thresholds,
+            self.evidence_weights.as_ref(),
+            self.taxon_filter.as_deref(),
+        );
+    }
+}
+
+/// The numbers of rows skipped when parsing a single sequence similarity search result table.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SkippedRows {
+    /// The number of malformed rows, handled according to the `MalformedRowsMode` (see
+    /// `MalformedRows`).
+    pub n_malformed: usize,
+    /// The number of rows whose Hit is found neither in the table's reference nor in its UniProt
+    /// flat file, e.g. because these are outdated. Such rows are well formed, and thus always
+    /// skipped, whatever the `MalformedRowsMode`.
+    pub n_missing_hits: usize,
+}
+
+/// Counts and reports the malformed rows found in a single sequence similarity search result
+/// table.
+#[derive(Debug)]
+pub struct MalformedRows<'a> {
+    /// The path to the parsed table
+    pub path: &'a str,
+    /// How to handle malformed rows
+    pub mode: MalformedRowsMode,
+    /// The number of malformed rows found so far
+    pub n_rows: usize,
+}
+
+impl<'a> MalformedRows<'a> {
+    /// Returns a new and initialized instance of struct `MalformedRows`.
+    pub fn new(path: &'a str, mode: &MalformedRowsMode) -> MalformedRows<'a> {
+        MalformedRows {
+            path,
+            mode: *mode,
+            n_rows: 0,
+        }
+    }
+
+    /// Reports the malformed row in line `line_number` (one based) of the parsed table. Panics in
+    /// mode `MalformedRowsMode::Strict`, otherwise prints a warning and counts the row.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A mutable reference to this instance of MalformedRows
+    /// * `line_number: usize` - The number of the malformed line, starting with one
+    /// * `reason: &str` - Why the row is malformed
+    pub fn report(&mut self, line_number: usize, reason: &str) {
+        self.n_rows += 1;
+        match self.mode {
+            MalformedRowsMode::Strict => panic!("\n\nLine {} of sequence similarity search result table {:?} is malformed: {}. Use --malformed-rows skip to skip malformed rows.\n\n", line_number, self.path, reason),
+            MalformedRowsMode::Skip => eprintln!(
+                "\nSkipping malformed line {} of {:?}: {}\n",
+                line_number, self.path, reason
+            ),
+        }
+    }
+
+    /// Handles the argument `error` that occurred while reading line `line_number` (one based) of
+    /// the parsed table. Invalid UTF-8 (`io::ErrorKind::InvalidData`) makes the line a malformed
+    /// row (see `report`). Any other error, e.g. of a truncated or corrupt compressed table,
+    /// panics, because reading on would fail again.
+    ///
+    /// # Arguments
+    ///
+    /// * `&mut self` - A mutable reference to this instance of MalformedRows
+    /// * `line_number: usize` - The number of the line that could not be read, starting with one
+    /// * `error: &io::Error` - The error reading the line
+    pub fn report_read_error(&mut self, line_number: usize, error: &io::Error) {
+        if error.kind() == io::ErrorKind::InvalidData {
+            self.report(line_number, &error.to_string());
+        } else {
+            panic!(
+                "\n\nCould not read line {} of sequence similarity search result table {:?}:\n{:?}\n\n",
+                line_number, self.path, error
+            );
+        }
+    }
+}
 
 /// Finds a tabular file (`path`) and parses it in a stream approach, i.e. line by line. Every time
 /// an instance of Query is successfully and completely parsed it is send using the argument
-/// `transmitter` to the respective registered receiver.
+/// `transmitter` to the respective registered receiver. Gzip, bgzip, and zstd compressed tables
+/// are decompressed on the fly (see `input_reader::open_input_file`). Comment lines, i.e. lines
+/// starting with `#` as written by Blast `-outfmt 7`, are skipped. If no `columns` are given, the
+/// column positions are taken from the table's `# Fields:` comment line (see
+/// `parse_fields_comment`), or `default::SEQ_SIM_TABLE_COLUMNS` are used if there is none.
+/// Malformed rows, i.e. rows with too few columns, empty `qacc` or `sacc`, unparsable quality
+/// measures, or invalid UTF-8, are handled according to the `malformed_rows_mode` of the argument
+/// `options`. Rows whose Hit title has to be looked up, but is found neither in the
+/// `reference_titles` nor in the `uniprot_flat_file`, are skipped. Returns the numbers of both
+/// kinds of skipped rows.
 ///
 /// # Arguments
 ///
 /// * `path: String` - The path to the tabular sequence similarity search result file to parse
 /// * `field_separator: char` - The separator to use to split a line into an array of columns
-/// * `qacc_col: &usize` - The column index in which to find the `qacc`
-/// * `sacc_col: &usize` - The column index in which to find the `sacc`
-/// * `stitle_col: &usize` - The column index in which to find the `stitle`
-/// * `blacklist_regexs: &Vec<Regex>` - The list of regular expressions used to identify to be
-/// discarded descriptions (`stitle`) parsed from the argument `path` sequence similarity search
-/// result table.
-/// * `filter_regexs: &Vec<Regex>` - The list of regular expressions used to identify to be deleted
-/// matching sub-strings in the descriptions (`stitle`) parsed from the argument `path` sequence
-/// similarity search result table.
-/// * `capture_replace_pairs` - An `Option` of a vector of tuples, pairing a regular expression
-/// and the capture-group replacement string. These are iteratively applied and the argument
-/// descriptions to prepare it for final splitting into words (see `split_descriptions` for
-/// details).
+/// * `columns: Option<&HashMap<String, usize>>` - The column indices in which to find the `qacc`,
+///   `sacc`, and `stitle`, and optionally the Hit's quality measures `evalue`, `bitscore`,
+///   `pident`, and `qcovhsp`. `None`, if the user did not provide them.
+/// * `options: &TableOptions` - The settings to parse the table with, e.g. the thresholds Hits
+///   must pass, or the reference to look up the Hit titles in.
 /// * `transmitter: Sender<Query>` - Used to send instances of `Query` to any receiver.
 pub fn parse_table(
     path: &String,
     field_separator: &char,
-    qacc_col: &usize,
-    sacc_col: &usize,
-    stitle_col: &usize,
-    blacklist_regexs: &Vec<Regex>,
-    filter_regexs: &Vec<Regex>,
-    capture_replace_pairs: Option<&Vec<(fancy_regex::Regex, String)>>,
+    columns: Option<&HashMap<String, usize>>,
+    options: &TableOptions,
     transmitter: Sender<(String, Query)>,
-) {
+) -> SkippedRows {
     let lines =
         read_lines(&path).expect(format!("An error occurred reading file {:?}", &path).as_str());
+    let auto_columns = columns.is_none();
+    let mut columns: HashMap<String, usize> = columns.unwrap_or(&SEQ_SIM_TABLE_COLUMNS).clone();
+    let hit_thresholds = &options.hit_thresholds;
+    let reference_titles = options.reference_titles.as_ref();
+    let uniprot_flat_file = options.uniprot_flat_file.as_ref();
+    let mut malformed_rows = MalformedRows::new(path, &options.malformed_rows_mode);
+    let mut n_missing_hits = 0;
     let mut last_qacc = String::new();
     let mut curr_query = Query::new();
-    for line_rslt in lines {
+    for (line_no, line_rslt) in lines.enumerate() {
         match line_rslt {
+            Ok(line) if line.starts_with('#') || line.trim().is_empty() => {
+                if auto_columns {
+                    if let Some(fields_columns) = parse_fields_comment(&line) {
+                        validate_fields_columns(
+                            &fields_columns,
+                            hit_thresholds,
+                            reference_titles.is_some() || uniprot_flat_file.is_some(),
+                            path,
+                        );
+                        columns = fields_columns;
+                    }
+                }
+            }
             Ok(line) => {
                 let cols: Vec<&str> = line.trim().split(*field_separator).collect();
-                let qacc = cols[*qacc_col];
-                let sacc = cols[*sacc_col];
-                let stitle = cols[*stitle_col];
+                let scores = match validate_row(&cols, &columns)
+                    .and_then(|_| parse_hit_scores(&cols, &columns))
+                {
+                    Ok(scores) => scores,
+                    Err(reason) => {
+                        malformed_rows.report(line_no + 1, &reason);
+                        continue;
+                    }
+                };
+                let qacc = cols[columns["qacc"]];
+                let sacc = cols[columns["sacc"]];
+                // Hits described by their UniProt flat file entries need no title:
+                let in_flat_file = uniprot_flat_file.is_some_and(|f| f.get(sacc).is_some());
+                // Tables without `stitle` column have their Hits looked up in the reference:
+                let stitle = match (columns.get("stitle"), reference_titles) {
+                    (Some(i), Some(titles)) if options.prefer_reference_titles => {
+                        titles.get(sacc).unwrap_or(cols[*i])
+                    }
+                    (Some(i), _) => cols[*i],
+                    (None, Some(titles)) => match titles.get(sacc) {
+                        Some(header) => header,
+                        None if in_flat_file => "",
+                        None => {
+                            n_missing_hits += 1;
+                            continue;
+                        }
+                    },
+                    (None, None) if in_flat_file => "",
+                    (None, None) if uniprot_flat_file.is_some() => {
+                        n_missing_hits += 1;
+                        continue;
+                    }
+                    (None, None) => panic!("\n\nSequence similarity search result table {:?} has no 'stitle' column. Please provide the reference FASTA with --reference-fasta, or the UniProt flat file with --uniprot-dat, to look up the Hit titles.\n\n", path),
+                };
 
                 if qacc != last_qacc && !last_qacc.is_empty() {
-                    transmitter.send((last_qacc, curr_query)).unwrap();
+                    options.send_query(&transmitter, last_qacc, curr_query);
                     curr_query = Query::new();
                 }
 
-                if !curr_query.hits.contains_key(&sacc.to_string())
-                    && !matches_blacklist(stitle, blacklist_regexs)
-                {
-                    let desc = filter_stitle(stitle, filter_regexs, capture_replace_pairs)
-                        .trim()
-                        .to_lowercase();
-                    if !desc.is_empty() {
-                        curr_query.hits.insert(sacc.to_string(), desc);
-                    }
+                if hit_thresholds.passes(&scores) {
+                    insert_hit(&mut curr_query, sacc, stitle, scores, options);
                 }
 
                 last_qacc = qacc.to_string();
             }
-            Err(e) => {
-                eprintln!(
-                    "\nAn error occurred while parsing {:?}:\n{:?}\nContinuing anyway!\n",
-                    path, e
-                );
-            }
+            Err(e) => malformed_rows.report_read_error(line_no + 1, &e),
         }
     }
 
     // Send last parsed query:
     if curr_query.hits.len() > 0 && !last_qacc.is_empty() {
-        transmitter.send((last_qacc, curr_query)).unwrap();
+        options.send_query(&transmitter, last_qacc, curr_query);
+    }
+
+    SkippedRows {
+        n_malformed: malformed_rows.n_rows,
+        n_missing_hits,
+    }
+}
+
+/// Checks that the argument `cols` of a tabular sequence similarity search result table row
+/// provide all argument `columns` and non empty `qacc` and `sacc`. Returns the reason, if the row
+/// is malformed.
+///
+/// # Arguments
+///
+/// * `cols: &[&str]` - The fields of the row
+/// * `columns: &HashMap<String, usize>` - The column indices, as e.g. provided with `--header`
+pub fn validate_row(cols: &[&str], columns: &HashMap<String, usize>) -> Result<(), String> {
+    let n_required_cols = columns.values().max().map_or(0, |i| i + 1);
+    if cols.len() < n_required_cols {
+        return Err(format!(
+            "expected at least {} columns, but found {}",
+            n_required_cols,
+            cols.len()
+        ));
+    }
+    for col_name in ["qacc", "sacc"].iter() {
+        if cols[columns[*col_name]].trim().is_empty() {
+            return Err(format!("empty {:?}", col_name));
+        }
+    }
+    Ok(())
+}
+
+/// Parses a Blast `# Fields:` comment line (`-outfmt 7`) into column positions. The column labels
+/// are translated into the column names prot-scriber uses (see
+/// `default::BLAST_FIELDS_COMMENT_LABELS`), unknown labels are ignored. Returns `None` if the
+/// argument `line` is not a `# Fields:` comment line.
+///
+/// # Arguments
+///
+/// * `line: &str` - The comment line to parse
+pub fn parse_fields_comment(line: &str) -> Option<HashMap<String, usize>> {
+    let labels: Vec<&str> = line
+        .strip_prefix(*BLAST_FIELDS_COMMENT_PREFIX)?
+        .split(',')
+        .map(|label| label.trim())
+        .collect();
+    let mut columns = HashMap::new();
+    for (label, col_name) in BLAST_FIELDS_COMMENT_LABELS.iter() {
+        if columns.contains_key(*col_name) {
+            continue;
+        }
+        if let Some(i) = labels.iter().position(|l| l == label) {
+            columns.insert(col_name.to_string(), i);
+        }
+    }
+    Some(columns)
+}
+
+/// Reads the leading comment lines of the sequence similarity search result table `path` and
+/// returns the column positions parsed from its `# Fields:` comment line, if any (see
+/// `parse_fields_comment`). Returns `None` if the table cannot be read or has no such line.
+///
+/// # Arguments
+///
+/// * `path: &str` - The path to the table
+pub fn read_fields_comment(path: &str) -> Option<HashMap<String, usize>> {
+    read_lines(path)
+        .ok()?
+        .map_while(Result::ok)
+        .take_while(|line| line.starts_with('#'))
+        .find_map(|line| parse_fields_comment(&line))
+}
+
+/// Panics if the column positions parsed from the `# Fields:` comment line of table `path` lack
+/// any of the columns `qacc`, `sacc`, and `stitle`, or a quality measure the argument
+/// `hit_thresholds` apply to. The `stitle` is not required, if the argument `has_reference_fasta`
+/// is `true`.
+fn validate_fields_columns(
+    columns: &HashMap<String, usize>,
+    hit_thresholds: &HitThresholds,
+    has_reference_fasta: bool,
+    path: &str,
+) {
+    for col_name in vec!["qacc", "sacc", "stitle"]
+        .into_iter()
+        .filter(|col_name| !(has_reference_fasta && *col_name == "stitle"))
+        .chain(hit_thresholds.required_scores())
+    {
+        if !columns.contains_key(col_name) {
+            panic!("\n\nThe '# Fields:' comment line of sequence similarity search result table {:?} does not list the required column {:?}. Please provide the column positions with the --header (-e) argument.\n\n", path, col_name);
+        }
+    }
+}
+
+/// Finds a HMMER hit table (`path`), i.e. a per-sequence (`--tblout`) or per-domain
+/// (`--domtblout`) table, and parses it in a stream approach, i.e. line by line. HMMER tables are
+/// whitespace-delimited, their last column holds the free-text description of the target, and
+/// lines starting with `#` are comments. The query (`qacc`) is the HMMER query name, the Hit is
+/// identified by the target accession or the target name, if no accession is given. Every time
+/// an instance of Query is successfully and completely parsed it is send using the argument
+/// `transmitter` to the respective registered receiver. Note that the queries are expected to be
+/// the protein sequences to annotate, as is the case when running `hmmscan` against e.g. Pfam.
+/// Malformed rows are handled according to the `malformed_rows_mode` of the argument `options`.
+/// Returns the number of malformed rows.
+///
+/// # Arguments
+///
+/// * `path: String` - The path to the HMMER hit table to parse
+/// * `format: &SeqSimTableFormat` - Either `HmmerTblout` or `HmmerDomtblout`
+/// * `options: &TableOptions` - The settings to parse the table with. Note that HMMER tables
+///   provide the `evalue` and `bitscore` of a Hit, only.
+/// * `transmitter: Sender<Query>` - Used to send instances of `Query` to any receiver.
+pub fn parse_hmmer_table(
+    path: &String,
+    format: &SeqSimTableFormat,
+    options: &TableOptions,
+    transmitter: Sender<(String, Query)>,
+) -> usize {
+    let columns: &HashMap<String, usize> = match format {
+        SeqSimTableFormat::HmmerTblout => &HMMER_TBLOUT_COLUMNS,
+        SeqSimTableFormat::HmmerDomtblout => &HMMER_DOMTBLOUT_COLUMNS,
+        _ => panic!(
+            "\n\nFormat {:?} is not a HMMER hit table format!\n\n",
+            format
+        ),
+    };
+    let target_name_col = columns["target_name"];
+    let sacc_col = columns["sacc"];
+    let qacc_col = columns["qacc"];
+    let stitle_col = columns["stitle"];
+
+    let lines =
+        read_lines(path).unwrap_or_else(|_| panic!("An error occurred reading file {:?}", path));
+    let hit_thresholds = &options.hit_thresholds;
+    let mut malformed_rows = MalformedRows::new(path, &options.malformed_rows_mode);
+    let mut last_qacc = String::new();
+    let mut curr_query = Query::new();
+    for (line_no, line_rslt) in lines.enumerate() {
+        match line_rslt {
+            Ok(line) => {
+                if line.starts_with('#') || line.trim().is_empty() {
+                    continue;
+                }
+                let cols = match split_hmmer_row(&line, stitle_col) {
+                    Some(cols) => cols,
+                    None => {
+                        malformed_rows.report(
+                            line_no + 1,
+                            &format!("expected at least {} columns", stitle_col),
+                        );
+                        continue;
+                    }
+                };
+                let scores = match parse_hit_scores(&cols, columns) {
+                    Ok(scores) => scores,
+                    Err(reason) => {
+                        malformed_rows.report(line_no + 1, &reason);
+                        continue;
+                    }
+                };
+                let qacc = cols[qacc_col];
+                let sacc = if cols[sacc_col] == *HMMER_MISSING_VALUE {
+                    cols[target_name_col]
+                } else {
+                    cols[sacc_col]
+                };
+                let stitle = if cols[stitle_col] == *HMMER_MISSING_VALUE {
+                    ""
+                } else {
+                    cols[stitle_col]
+                };
+
+                if qacc != last_qacc && !last_qacc.is_empty() {
+                    options.send_query(&transmitter, last_qacc, curr_query);
+                    curr_query = Query::new();
+                }
+
+                if hit_thresholds.passes(&scores) {
+                    insert_hit(&mut curr_query, sacc, stitle, scores, options);
+                }
+
+                last_qacc = qacc.to_string();
+            }
+            Err(e) => malformed_rows.report_read_error(line_no + 1, &e),
+        }
+    }
+
+    // Send last parsed query:
+    if !curr_query.hits.is_empty() && !last_qacc.is_empty() {
+        options.send_query(&transmitter, last_qacc, curr_query);
+    }
+
+    malformed_rows.n_rows
+}
+
+/// Parses the quality measures of a Hit from the argument `cols` of a sequence similarity search
+/// result table row. Measures without a column in the argument `columns` are `None`. Returns an
+/// error message, if a value cannot be parsed into a number.
+///
+/// # Arguments
+///
+/// * `cols: &[&str]` - The fields of the row
+/// * `columns: &HashMap<String, usize>` - The column indices, as e.g. provided with `--header`
+pub fn parse_hit_scores(
+    cols: &[&str],
+    columns: &HashMap<String, usize>,
+) -> Result<HitScores, String> {
+    let score = |col_name: &str| -> Result<Option<f64>, String> {
+        columns
+            .get(col_name)
+            .map(|i| {
+                cols[*i]
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("{:?} value {:?} is not a number", col_name, cols[*i]))
+            })
+            .transpose()
+    };
+    Ok(HitScores {
+        evalue: score("evalue")?,
+        bitscore: score("bitscore")?,
+        pident: score("pident")?,
+        qcovhsp: score("qcovhsp")?,
+    })
+}
+
+/// Splits a row of a whitespace-delimited HMMER hit table into its columns. The first
+/// `n_fixed_cols` columns are separated by whitespace, everything following them is the last
+/// column, i.e. the free-text description, which itself may contain whitespace. Returns `None`
+/// if the row has less than `n_fixed_cols` columns. Rows without description get an empty last
+/// column.
+///
+/// # Arguments
+///
+/// * `line: &str` - The row to split
+/// * `n_fixed_cols: usize` - The number of columns preceding the free-text description
+pub fn split_hmmer_row(line: &str, n_fixed_cols: usize) -> Option<Vec<&str>> {
+    let mut cols: Vec<&str> = Vec::with_capacity(n_fixed_cols + 1);
+    let mut rest = line.trim();
+    while cols.len() < n_fixed_cols {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        cols.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    cols.push(rest);
+    Some(cols)
+}
+
+/// Sends the completely parsed argument `query` using the argument `transmitter`, after the per
+/// query thresholds (`top_n_hits` and `within_best_bitscore`) have been applied to its Hits (see
+/// `HitThresholds::select`) and the retained Hits' evidence has been weighted, if the argument
+/// `evidence_weights` are given (see `EvidenceWeights::weight`). Hits of the taxa of the argument
+/// `taxon_filter` are discarded, before the per query thresholds are applied, or down-weighted
+/// (see `TaxonFilter::apply`). This function is shared by all readers of sequence similarity search
+/// results.
+///
+/// # Arguments
+///
+/// * `transmitter: &Sender<(String, Query)>` - Used to send the query to any receiver.
+/// * `qacc: String` - The query's identifier.
+/// * `query: Query` - The completely parsed query.
+/// * `hit_thresholds: &HitThresholds` - The thresholds to select the query's Hits with.
+/// * `evidence_weights: Option<&EvidenceWeights>` - The weights of the Hits' evidence, if any.
+/// * `taxon_filter: Option<&TaxonFilter>` - The taxa whose Hits to exclude or down-weight, if
+///   any.
+pub fn send_query(
+    transmitter: &Sender<(String, Query)>,
+    qacc: String,
+    mut query: Query,
+    hit_thresholds: &HitThresholds,
+    evidence_weights: Option<&EvidenceWeights>,
+    taxon_filter: Option<&TaxonFilter>,
+) {
+    if let Some(filter) = taxon_filter {
+        filter.apply(&mut query.hits);
+    }
+    hit_thresholds.select(&mut query.hits);
+    if let Some(weights) = evidence_weights {
+        for hit in query.hits.values_mut() {
+            hit.evidence_weight = Some(hit.evidence_weight.unwrap_or(1.0) * weights.weight(hit));
+        }
+    }
+    transmitter.send((qacc, query)).unwrap();
+}
+
+/// Inserts the Hit identified by argument `sacc` into the argument `query`, unless the query
+/// already has a Hit of that identifier or the argument `stitle` matches any of the
+/// `blacklist_regexs` of the argument `options`. The Hit's description is obtained by filtering
+/// the `stitle` (see `model_funcs::filter_stitle`) and only inserted, if it is not empty after
+/// filtering. The fields of UniProt headers, e.g. the organism, are parsed from the unfiltered
+/// `stitle` (see `Hit::new`). If the Hit has an entry in the `uniprot_flat_file` of the argument
+/// `options`, the entry's protein name is used instead of the `stitle`. It is not filtered by the
+/// `filter_regexs`, because it needs no cleaning, but blacklisted and normalized like any other
+/// description. This function is shared by all readers of sequence similarity search results.
+///
+/// # Arguments
+///
+/// * `query: &mut Query` - The query the Hit was found for.
+/// * `sacc: &str` - The Hit's identifier (`sacc` in Blast terminology).
+/// * `stitle: &str` - The Hit's title (`stitle` in Blast terminology).
+/// * `scores: HitScores` - The Hit's quality measures.
+/// * `options: &TableOptions` - The settings the Hit's table is parsed with, i.e. its UniProt flat
+///   file, blacklist and filter regexs, and capture-replace-pairs.
+pub fn insert_hit(
+    query: &mut Query,
+    sacc: &str,
+    stitle: &str,
+    scores: HitScores,
+    options: &TableOptions,
+) {
+    if query.hits.contains_key(sacc) {
+        return;
+    }
+    let blacklist_regexs = &options.blacklist_regexs;
+    let capture_replace_pairs = options.capture_replace_pairs.as_ref();
+    if let Some(entry) = options.uniprot_flat_file.as_ref().and_then(|f| f.get(sacc)) {
+        if !matches_blacklist(&entry.name, blacklist_regexs) {
+            let desc = filter_stitle(&entry.name, &Vec::new(), capture_replace_pairs);
+            if !desc.is_empty() {
+                query.hits.insert(
+                    sacc.to_string(),
+                    Hit::from_uniprot_entry(&entry, desc, scores),
+                );
+            }
+        }
+    } else if !matches_blacklist(stitle, blacklist_regexs) {
+        let desc = filter_stitle(stitle, &options.filter_regexs, capture_replace_pairs)
+            .trim()
+            .to_lowercase();
+        if !desc.is_empty() {
+            query
+                .hits
+                .insert(sacc.to_string(), Hit::new(sacc, stitle, desc, scores));
+        }
     }
 }
 
 /// The output is wrapped in a Result to allow matching on errors Returns an Iterator to the Reader
-/// of the lines of the file.
+/// of the lines of the (decompressed) file.
 ///
 /// # Arguments
 ///
 /// * `filename` The path to the file to open a `BufReader` for.
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<Box<dyn BufRead + Send>>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    Ok(open_input_file(filename)?.lines())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default::{BLACKLIST_STITLE_REGEXS, FILTER_REGEXS};
+    use crate::reference_fasta::ReferenceIndex;
+    use crate::uniprot_flat_file::{UniProtEntries, UniProtFlatFile};
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn splits_hmmer_rows() {
+        let row = "LRR_8    PF13855.9  Soltu.DM.02G015700.1  -   1e-10  40.1  Leucine rich  repeat";
+        assert_eq!(
+            Some(vec![
+                "LRR_8",
+                "PF13855.9",
+                "Soltu.DM.02G015700.1",
+                "-",
+                "1e-10",
+                "40.1",
+                "Leucine rich  repeat"
+            ]),
+            split_hmmer_row(row, 6)
+        );
+        assert_eq!(
+            Some(vec!["LRR_8", "PF13855.9", ""]),
+            split_hmmer_row("LRR_8 PF13855.9", 2)
+        );
+        assert_eq!(None, split_hmmer_row("LRR_8 PF13855.9", 3));
+    }
+
+    #[test]
+    fn parses_hmmer_tblout_and_domtblout() {
+        for (file, format) in [
+            (
+                "Two_Proteins_vs_Pfam_hmmscan.tblout",
+                SeqSimTableFormat::HmmerTblout,
+            ),
+            (
+                "Two_Proteins_vs_Pfam_hmmscan.domtblout",
+                SeqSimTableFormat::HmmerDomtblout,
+            ),
+        ] {
+            let path = Path::new("misc").join(file).to_str().unwrap().to_string();
+            let (tx, rx) = channel();
+            parse_hmmer_table(
+                &path,
+                &format,
+                &TableOptions {
+                    malformed_rows_mode: MalformedRowsMode::Strict,
+                    ..Default::default()
+                },
+                tx,
+            );
+            let queries: Vec<(String, Query)> = rx.iter().collect();
+            assert_eq!(2, queries.len());
+            let (qacc_1, query_1) = &queries[0];
+            assert_eq!("Soltu.DM.02G015700.1", qacc_1);
+            assert_eq!(4, query_1.hits.len());
+            assert_eq!(
+                "protein tyrosine and serine/threonine kinase",
+                query_1.hits.get("PF07714.20").unwrap().description
+            );
+            assert_eq!(
+                "leucine rich repeat",
+                query_1.hits.get("PF00560.36").unwrap().description
+            );
+            let (qacc_2, query_2) = &queries[1];
+            assert_eq!("Soltu.DM.01G045390.1", qacc_2);
+            assert_eq!(1, query_2.hits.len());
+            // Targets without accession are identified by their name:
+            assert_eq!(
+                "tb2/dp1, hva22 family",
+                query_2.hits.get("TB2_DP1_HVA22").unwrap().description
+            );
+        }
+    }
+
+    #[test]
+    fn selects_best_hits_per_query_before_sending() {
+        let path = Path::new("misc")
+            .join("Two_Proteins_vs_Pfam_hmmscan.tblout")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let hit_thresholds = HitThresholds {
+            top_n_hits: Some(2),
+            ..Default::default()
+        };
+        let (tx, rx) = channel();
+        parse_hmmer_table(
+            &path,
+            &SeqSimTableFormat::HmmerTblout,
+            &TableOptions {
+                hit_thresholds,
+                malformed_rows_mode: MalformedRowsMode::Strict,
+                ..Default::default()
+            },
+            tx,
+        );
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        let mut retained: Vec<&String> = queries[0].1.hits.keys().collect();
+        retained.sort();
+        assert_eq!(vec!["PF00069.28", "PF07714.20"], retained);
+        assert_eq!(1, queries[1].1.hits.len());
+    }
+
+    #[test]
+    fn parses_fields_comment() {
+        assert_eq!(None, parse_fields_comment("# Query: Soltu.DM.02G015700.1"));
+        let columns = parse_fields_comment(
+            "# Fields: query id, query acc.ver, subject acc.ver, % identity, evalue, bit score, subject title",
+        )
+        .unwrap();
+        let mut expected = HashMap::new();
+        for (col_name, i) in [
+            ("qacc", 1),
+            ("sacc", 2),
+            ("pident", 3),
+            ("evalue", 4),
+            ("bitscore", 5),
+            ("stitle", 6),
+        ] {
+            expected.insert(col_name.to_string(), i);
+        }
+        assert_eq!(expected, columns);
+    }
+
+    #[test]
+    fn parses_tables_with_comment_lines() {
+        let path = Path::new("misc")
+            .join("Two_Potato_Proteins_vs_Swissprot_blastp_outfmt7.txt")
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(Some(&12), read_fields_comment(&path).unwrap().get("stitle"));
+        let hit_thresholds = HitThresholds {
+            max_evalue: Some(1e-100),
+            ..Default::default()
+        };
+        let (tx, rx) = channel();
+        parse_table(
+            &path,
+            &'\t',
+            None,
+            &TableOptions {
+                hit_thresholds,
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                malformed_rows_mode: MalformedRowsMode::Strict,
+                ..Default::default()
+            },
+            tx,
+        );
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        assert_eq!(1, queries.len());
+        assert_eq!("Soltu.DM.02G015700.1", queries[0].0);
+        let hit = &queries[0].1.hits["sp|C0LGQ5|GSO1_ARATH"];
+        assert_eq!(
+            "lrr receptor serine/threonine-protein kinase gso1",
+            hit.description
+        );
+        assert_eq!(Some(578.0), hit.scores.bitscore);
+        assert_eq!(Some(33.75), hit.scores.pident);
+        // The 'Probable ...' Hit sp|C0LGP4|Y3475_ARATH is blacklisted:
+        assert_eq!(1, queries[0].1.hits.len());
+    }
+
+    #[test]
+    fn skips_and_counts_malformed_rows() {
+        // An empty sacc, an invalid bitscore, invalid UTF-8, and a partially written last line:
+        let path = Path::new("misc").join("malformed_rows_table.txt");
+        let mut columns = HashMap::new();
+        for (i, col_name) in ["qacc", "sacc", "bitscore", "stitle"].iter().enumerate() {
+            columns.insert(col_name.to_string(), i);
+        }
+        let (tx, rx) = channel();
+        let skipped_rows = parse_table(
+            &path.to_str().unwrap().to_string(),
+            &'\t',
+            Some(&columns),
+            &TableOptions::default(),
+            tx,
+        );
+        assert_eq!(4, skipped_rows.n_malformed);
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        assert_eq!(2, queries.len());
+        assert_eq!(
+            vec!["A"],
+            queries[0].1.hits.keys().collect::<Vec<&String>>()
+        );
+        assert_eq!(
+            vec!["D"],
+            queries[1].1.hits.keys().collect::<Vec<&String>>()
+        );
+    }
+
+    #[test]
+    fn looks_up_hit_titles_in_reference_fasta() {
+        // Diamond's default output format lacks the `stitle` column:
+        let path = Path::new("misc").join("Two_Queries_vs_Reference_diamond.txt");
+        let mut columns = HashMap::new();
+        for (i, col_name) in ["qacc", "sacc", "pident", "length", "evalue", "bitscore"]
+            .iter()
+            .enumerate()
+        {
+            columns.insert(col_name.to_string(), i);
+        }
+        let mut index = ReferenceIndex::default();
+        index.insert("sp|Q1MX30|XA21_ORYSI Receptor kinase-like protein Xa21 OS=Oryza sativa subsp. indica OX=39946 GN=XA21 PE=1 SV=1");
+        index.insert("sp|P15538|C11B1_HUMAN Cytochrome P450 11B1, mitochondrial OS=Homo sapiens OX=9606 GN=CYP11B1 PE=1 SV=5");
+        let (tx, rx) = channel();
+        let skipped_rows = parse_table(
+            &path.to_str().unwrap().to_string(),
+            &'\t',
+            Some(&columns),
+            &TableOptions {
+                reference_titles: Some(ReferenceTitles::InMemory(index)),
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                malformed_rows_mode: MalformedRowsMode::Strict,
+                ..Default::default()
+            },
+            tx,
+        );
+        // The Hit missing in the reference FASTA is skipped, but the row is not malformed:
+        assert_eq!(
+            SkippedRows {
+                n_malformed: 0,
+                n_missing_hits: 1
+            },
+            skipped_rows
+        );
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        assert_eq!(1, queries.len());
+        let hit = &queries[0].1.hits["sp|Q1MX30|XA21_ORYSI"];
+        assert_eq!("receptor kinase protein xa21", hit.description);
+        assert_eq!(Some(39946), hit.taxon_id);
+        assert_eq!(Some(1200.0), hit.scores.bitscore);
+        assert_eq!(2, queries[0].1.hits.len());
+    }
+
+    #[test]
+    fn prefers_reference_titles_over_truncated_stitles() {
+        let path = Path::new("misc").join("truncated_stitles_table.txt");
+        let mut index = ReferenceIndex::default();
+        index.insert("sp|Q1MX30|XA21_ORYSI Receptor kinase-like protein Xa21 OS=Oryza sativa subsp. indica OX=39946 GN=XA21 PE=1 SV=1");
+        let (tx, rx) = channel();
+        let skipped_rows = parse_table(
+            &path.to_str().unwrap().to_string(),
+            &'\t',
+            None,
+            &TableOptions {
+                reference_titles: Some(ReferenceTitles::InMemory(index)),
+                prefer_reference_titles: true,
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                ..Default::default()
+            },
+            tx,
+        );
+        assert_eq!(SkippedRows::default(), skipped_rows);
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        let hits = &queries[0].1.hits;
+        assert_eq!(
+            "receptor kinase protein xa21",
+            hits["sp|Q1MX30|XA21_ORYSI"].description
+        );
+        // Hits not found in the reference keep the title of the table:
+        assert_eq!("cytochrome p450", hits["UNKNOWN"].description);
+    }
+
+    #[test]
+    fn describes_hits_by_uniprot_flat_file_entries() {
+        let path = Path::new("misc").join("uniprot_flat_file_lookup_table.txt");
+        let mut columns = HashMap::new();
+        for (i, col_name) in ["qacc", "sacc", "evalue"].iter().enumerate() {
+            columns.insert(col_name.to_string(), i);
+        }
+        let mut flat_file = UniProtFlatFile::default();
+        flat_file.insert(
+            &[
+                "ID   C11B1_HUMAN             Reviewed;         503 AA.",
+                "AC   P15538;",
+                "DE   RecName: Full=Cytochrome P450 11B1, mitochondrial;",
+                "DE            EC=1.14.15.4;",
+                "OX   NCBI_TaxID=9606;",
+            ]
+            .map(|line| line.to_string()),
+        );
+        let (tx, rx) = channel();
+        let skipped_rows = parse_table(
+            &path.to_str().unwrap().to_string(),
+            &'\t',
+            Some(&columns),
+            &TableOptions {
+                uniprot_flat_file: Some(UniProtEntries::InMemory(flat_file)),
+                blacklist_regexs: BLACKLIST_STITLE_REGEXS.clone(),
+                filter_regexs: FILTER_REGEXS.clone(),
+                malformed_rows_mode: MalformedRowsMode::Strict,
+                ..Default::default()
+            },
+            tx,
+        );
+        // The Hit missing in the UniProt flat file:
+        assert_eq!(
+            SkippedRows {
+                n_malformed: 0,
+                n_missing_hits: 1
+            },
+            skipped_rows
+        );
+        let queries: Vec<(String, Query)> = rx.iter().collect();
+        let hit = &queries[0].1.hits["sp|P15538|C11B1_HUMAN"];
+        assert_eq!("cytochrome p450 11b1, mitochondrial", hit.description);
+        assert_eq!(vec!["1.14.15.4".to_string()], hit.ec_numbers);
+        assert_eq!(Some(9606), hit.taxon_id);
+        assert_eq!(1, queries[0].1.hits.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_on_malformed_rows_in_strict_mode() {
+        let path = Path::new("misc").join("truncated_blast_table.txt");
+        let (tx, _rx) = channel();
+        parse_table(
+            &path.to_str().unwrap().to_string(),
+            &'\t',
+            None,
+            &TableOptions {
+                malformed_rows_mode: MalformedRowsMode::Strict,
+                ..Default::default()
+            },
+            tx,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not read line")]
+    fn fails_on_truncated_compressed_tables() {
+        // Reading a truncated gzip stream fails on every attempt, so it must not be skipped like
+        // a malformed row:
+        let path = Path::new("misc").join("truncated_blast_table.txt.gz");
+        let (tx, _rx) = channel();
+        parse_table(
+            &path.to_str().unwrap().to_string(),
+            &'\t',
+            None,
+            &TableOptions::default(),
+            tx,
+        );
+    }
 }
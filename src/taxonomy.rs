@@ -0,0 +1,349 @@
+//! Excluding, or down-weighting, Hits by the taxon their sequence stems from is implemented in
+//! this module. Taxa can be given as NCBI taxonomy identifiers (`OX=` in UniProt headers) and,
+//! with the help of a local copy of the NCBI taxonomy's `nodes.dmp`, extended to all taxa below
+//! them. The same taxonomy is used to weight Hits by their phylogenetic distance to the query's
+//! taxon.
+use super::default::MIN_HIT_WEIGHT;
+use super::hit::Hit;
+use super::input_reader::open_input_file;
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+
+/// The taxa whose Hits are excluded or down-weighted, e.g. the query's own species, whose earlier
+/// and unreviewed auto-annotations would otherwise make the annotation circular.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaxonFilter {
+    /// The NCBI taxonomy identifiers of the excluded taxa, including their descendants if a
+    /// taxonomy has been provided (see `TaxonFilter::new`).
+    pub excluded_taxa: HashSet<u64>,
+    /// `None` if Hits of the excluded taxa are discarded, otherwise their evidence weight is
+    /// multiplied with this value (see `hit::EvidenceWeights`).
+    pub weight: Option<f64>,
+    /// If set, the evidence weight of every Hit is multiplied with its phylogenetic weight.
+    pub phylogenetic_weights: Option<PhylogeneticWeights>,
+}
+
+impl TaxonFilter {
+    /// Returns a new instance of `TaxonFilter` for the argument `taxa`. If the path to an NCBI
+    /// taxonomy `nodes.dmp` is given, all taxa below the argument `taxa` are excluded, too, and
+    /// Hits are weighted by their phylogenetic distance to the argument `query_taxon`, if given
+    /// (see `PhylogeneticWeights`).
+    ///
+    /// # Arguments
+    ///
+    /// * `taxa: &[u64]` - The NCBI taxonomy identifiers of the taxa to exclude
+    /// * `nodes_dmp: Option<&str>` - The path to the NCBI taxonomy `nodes.dmp`, if any
+    /// * `weight: Option<f64>` - `None` to discard Hits of the excluded taxa, or the factor to
+    ///   down-weight them with
+    /// * `query_taxon: Option<u64>` - The NCBI taxonomy identifier of the queries' taxon, if
+    ///   Hits are to be weighted phylogenetically. Requires the argument `nodes_dmp`.
+    pub fn new(
+        taxa: &[u64],
+        nodes_dmp: Option<&str>,
+        weight: Option<f64>,
+        query_taxon: Option<u64>,
+    ) -> TaxonFilter {
+        let parents = nodes_dmp.map(parse_nodes_dmp);
+        let excluded_taxa = match &parents {
+            Some(parents) => descendant_taxa(taxa, parents),
+            None => taxa.iter().copied().collect(),
+        };
+        let phylogenetic_weights = query_taxon.map(|taxon_id| {
+            PhylogeneticWeights::new(
+                taxon_id,
+                parents.expect("Phylogenetic weighting requires a taxonomy."),
+            )
+        });
+        TaxonFilter {
+            excluded_taxa,
+            weight,
+            phylogenetic_weights,
+        }
+    }
+
+    /// Returns `true` if and only if the argument `hit` stems from an excluded taxon. Hits
+    /// without taxon identifier are never excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `hit: &Hit` - The Hit to check
+    pub fn excludes(&self, hit: &Hit) -> bool {
+        hit.taxon_id
+            .is_some_and(|taxon_id| self.excluded_taxa.contains(&taxon_id))
+    }
+
+    /// Discards the Hits of excluded taxa from the argument `hits` or, if a `weight` is set,
+    /// multiplies their evidence weight with it. If `phylogenetic_weights` are set, the evidence
+    /// weight of all remaining Hits is multiplied with their phylogenetic weight.
+    ///
+    /// # Arguments
+    ///
+    /// * `hits: &mut HashMap<String, Hit>` - The Hits of a single query, keyed by identifier.
+    pub fn apply(&self, hits: &mut HashMap<String, Hit>) {
+        match self.weight {
+            None => hits.retain(|_, hit| !self.excludes(hit)),
+            Some(weight) => {
+                for hit in hits.values_mut().filter(|hit| self.excludes(hit)) {
+                    hit.evidence_weight = Some(hit.evidence_weight.unwrap_or(1.0) * weight);
+                }
+            }
+        }
+        if let Some(phylogenetic_weights) = &self.phylogenetic_weights {
+            for hit in hits.values_mut() {
+                if let Some(weight) = hit.taxon_id.and_then(|t| phylogenetic_weights.weight(t)) {
+                    hit.evidence_weight = Some(hit.evidence_weight.unwrap_or(1.0) * weight);
+                }
+            }
+        }
+    }
+}
+
+/// Weights taxa by their phylogenetic distance to the queries' taxon. The weight of a taxon is
+/// the fraction of the query taxon's lineage it shares, i.e. the depth of their last common
+/// ancestor divided by the depth of the query taxon, counting the root as depth zero. So a Hit of
+/// a close relative, e.g. of the same plant family, receives a weight close to one, while a Hit
+/// of a bacterium, sharing only `cellular organisms` with a plant, receives a weight close to
+/// zero. Weights never fall below `default::MIN_HIT_WEIGHT`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhylogeneticWeights {
+    /// The depth of each taxon in the query taxon's lineage, keyed by taxon identifier.
+    pub query_lineage_depths: HashMap<u64, usize>,
+    /// The depth of the query taxon.
+    pub query_depth: usize,
+    /// Each taxon's identifier mapped to its parent's identifier (see `parse_nodes_dmp`).
+    pub parents: HashMap<u64, u64>,
+}
+
+impl PhylogeneticWeights {
+    /// Returns a new instance of `PhylogeneticWeights` for the argument `query_taxon`. Panics if
+    /// the query taxon is not part of the taxonomy.
+    ///
+    /// # Arguments
+    ///
+    /// * `query_taxon: u64` - The NCBI taxonomy identifier of the queries' taxon
+    /// * `parents: HashMap<u64, u64>` - The taxonomy (see `parse_nodes_dmp`)
+    pub fn new(query_taxon: u64, parents: HashMap<u64, u64>) -> PhylogeneticWeights {
+        let query_lineage = lineage(query_taxon, &parents).unwrap_or_else(|| {
+            panic!(
+                "\n\nThe query taxon {} is not part of the provided NCBI taxonomy.\n\n",
+                query_taxon
+            )
+        });
+        let query_depth = query_lineage.len() - 1;
+        let query_lineage_depths = query_lineage
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(depth, taxon_id)| (*taxon_id, depth))
+            .collect();
+        PhylogeneticWeights {
+            query_lineage_depths,
+            query_depth,
+            parents,
+        }
+    }
+
+    /// Returns the phylogenetic weight of the argument `taxon_id`, or `None` if the taxon is not
+    /// part of the taxonomy.
+    ///
+    /// # Arguments
+    ///
+    /// * `taxon_id: u64` - The NCBI taxonomy identifier of a Hit's taxon
+    pub fn weight(&self, taxon_id: u64) -> Option<f64> {
+        let common_depth = lineage(taxon_id, &self.parents)?
+            .iter()
+            .find_map(|t| self.query_lineage_depths.get(t))
+            .copied()
+            .unwrap_or(0);
+        if self.query_depth == 0 {
+            return Some(1.0);
+        }
+        Some((common_depth as f64 / self.query_depth as f64).max(*MIN_HIT_WEIGHT))
+    }
+}
+
+/// Returns the lineage of the argument `taxon_id`, i.e. the taxon itself followed by its
+/// ancestors up to the root, or `None` if the taxon is not part of the argument `parents`
+/// taxonomy.
+///
+/// # Arguments
+///
+/// * `taxon_id: u64` - The NCBI taxonomy identifier of the taxon
+/// * `parents: &HashMap<u64, u64>` - The taxonomy (see `parse_nodes_dmp`)
+pub fn lineage(taxon_id: u64, parents: &HashMap<u64, u64>) -> Option<Vec<u64>> {
+    let mut lineage = vec![taxon_id];
+    let mut parent_id = *parents.get(&taxon_id)?;
+    // The root is its own parent. The length check guards against cycles in corrupt taxonomies:
+    while parent_id != *lineage.last().unwrap() && lineage.len() <= parents.len() {
+        lineage.push(parent_id);
+        parent_id = *parents.get(&parent_id)?;
+    }
+    Some(lineage)
+}
+
+/// Parses the NCBI taxonomy `names.dmp` found at argument `path` and returns the identifier of the
+/// taxon whose scientific name equals the argument `name` (case insensitive), if any. Fields of
+/// `names.dmp` are separated by `\t|\t`, holding the taxon's identifier, a name, a unique
+/// variant of the name, and the name's class, e.g. `scientific name`.
+///
+/// # Arguments
+///
+/// * `path: &str` - The path to the (possibly compressed) `names.dmp`
+/// * `name: &str` - The scientific name to look up, e.g. `Solanum tuberosum`
+pub fn find_taxon_by_name(path: &str, name: &str) -> Option<u64> {
+    let reader = open_input_file(path).unwrap_or_else(|e| {
+        panic!(
+            "\n\nCould not read NCBI taxonomy names file {:?}:\n{:?}\n\n",
+            path, e
+        )
+    });
+    let name = name.trim().to_lowercase();
+    reader.lines().map_while(Result::ok).find_map(|line| {
+        let fields: Vec<&str> = line.split('|').map(|field| field.trim()).collect();
+        match fields.as_slice() {
+            [taxon_id, name_txt, _, "scientific name", ..] if name_txt.to_lowercase() == name => {
+                taxon_id.parse().ok()
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Parses the NCBI taxonomy `nodes.dmp` found at argument `path` and returns a map of each
+/// taxon's identifier to its parent's identifier. Fields of `nodes.dmp` are separated
+/// by `\t|\t`, the first holds the taxon's and the second its parent's identifier.
+///
+/// # Arguments
+///
+/// * `path: &str` - The path to the (possibly compressed) `nodes.dmp`
+pub fn parse_nodes_dmp(path: &str) -> HashMap<u64, u64> {
+    let reader = open_input_file(path).unwrap_or_else(|e| {
+        panic!(
+            "\n\nCould not read NCBI taxonomy nodes file {:?}:\n{:?}\n\n",
+            path, e
+        )
+    });
+    let mut parents: HashMap<u64, u64> = HashMap::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.unwrap_or_else(|e| {
+            panic!(
+                "\n\nCould not read NCBI taxonomy nodes file {:?}:\n{:?}\n\n",
+                path, e
+            )
+        });
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.split('|').map(|field| field.trim().parse::<u64>());
+        match (fields.next(), fields.next()) {
+            (Some(Ok(taxon_id)), Some(Ok(parent_id))) => {
+                parents.insert(taxon_id, parent_id);
+            }
+            _ => panic!(
+                "\n\nLine {} of NCBI taxonomy nodes file {:?} does not start with a taxon and its parent's identifier.\n\n",
+                line_number + 1,
+                path
+            ),
+        }
+    }
+    parents
+}
+
+/// Returns the argument `taxa` and all taxa below them in the taxonomy given by the argument
+/// `parents` (see `parse_nodes_dmp`).
+///
+/// # Arguments
+///
+/// * `taxa: &[u64]` - The identifiers of the taxa whose subtrees to collect
+/// * `parents: &HashMap<u64, u64>` - Each taxon's identifier mapped to its parent's identifier
+pub fn descendant_taxa(taxa: &[u64], parents: &HashMap<u64, u64>) -> HashSet<u64> {
+    let mut children: HashMap<u64, Vec<u64>> = HashMap::new();
+    for (taxon_id, parent_id) in parents {
+        // The root is its own parent:
+        if taxon_id != parent_id {
+            children.entry(*parent_id).or_default().push(*taxon_id);
+        }
+    }
+    let mut descendants: HashSet<u64> = HashSet::new();
+    let mut to_visit: Vec<u64> = taxa.to_vec();
+    while let Some(taxon_id) = to_visit.pop() {
+        if descendants.insert(taxon_id) {
+            if let Some(child_taxa) = children.get(&taxon_id) {
+                to_visit.extend(child_taxa);
+            }
+        }
+    }
+    descendants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excludes_hits_of_taxa_and_their_descendants() {
+        let mut hits: HashMap<String, Hit> = HashMap::new();
+        for (sacc, taxon_id) in [
+            ("potato", Some(4113)),
+            ("tomato", Some(4081)),
+            ("human", Some(9606)),
+            ("unknown", None),
+        ] {
+            hits.insert(
+                sacc.to_string(),
+                Hit {
+                    taxon_id,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut listed = hits.clone();
+        TaxonFilter::new(&[4113], None, None, None).apply(&mut listed);
+        let mut retained: Vec<&String> = listed.keys().collect();
+        retained.sort();
+        assert_eq!(vec!["human", "tomato", "unknown"], retained);
+
+        let mut subtree = hits.clone();
+        let solanum = TaxonFilter::new(&[4107], Some("misc/taxonomy_nodes.dmp"), None, None);
+        assert_eq!(vec![4081, 4107, 4113], {
+            let mut taxa: Vec<u64> = solanum.excluded_taxa.iter().copied().collect();
+            taxa.sort();
+            taxa
+        });
+        solanum.apply(&mut subtree);
+        let mut retained: Vec<&String> = subtree.keys().collect();
+        retained.sort();
+        assert_eq!(vec!["human", "unknown"], retained);
+
+        let mut down_weighted = hits;
+        TaxonFilter::new(&[4107], Some("misc/taxonomy_nodes.dmp"), Some(0.1), None)
+            .apply(&mut down_weighted);
+        assert_eq!(4, down_weighted.len());
+        assert_eq!(Some(0.1), down_weighted["potato"].evidence_weight);
+        assert_eq!(None, down_weighted["human"].evidence_weight);
+    }
+
+    #[test]
+    fn weights_hits_by_phylogenetic_distance() {
+        let parents = parse_nodes_dmp("misc/taxonomy_nodes.dmp");
+        assert_eq!(
+            Some(vec![4081, 4107, 4070, 2759, 131567, 1]),
+            lineage(4081, &parents)
+        );
+        let potato = PhylogeneticWeights::new(4113, parents);
+        assert_eq!(5, potato.query_depth);
+        assert_eq!(Some(1.0), potato.weight(4113));
+        assert_eq!(Some(0.8), potato.weight(4081));
+        assert_eq!(Some(0.4), potato.weight(9606));
+        assert_eq!(Some(0.2), potato.weight(562));
+        assert_eq!(None, potato.weight(999));
+
+        let names_dmp = "misc/taxonomy_names.dmp";
+        assert_eq!(
+            Some(4113),
+            find_taxon_by_name(names_dmp, "solanum tuberosum")
+        );
+        assert_eq!(None, find_taxon_by_name(names_dmp, "potato"));
+    }
+}
@@ -0,0 +1,399 @@
+//! Reading UniProtKB flat files (`.dat`), e.g. `uniprot_sprot.dat.gz`, is implemented in this
+//! module. Their entries carry the structured protein names (`DE   RecName: Full=...` or, for
+//! unreviewed entries, `DE   SubName: Full=...`) and EC numbers, which are used as Hit
+//! descriptions instead of the regex filtered Hit titles (`stitle`). Small flat files, e.g.
+//! Swiss-Prot, are read into memory on every run. Huge ones, e.g. TrEMBL, must be indexed once
+//! (see `UniProtFlatFile::build_index`) into a memory mapped index file.
+use super::hit::SourceDatabase;
+use super::input_reader::open_input_file;
+use super::reference_fasta::{
+    is_index_file, lookup_accessions, starts_with_magic, ReferenceIndexFile,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+/// The first bytes of every index file written by `UniProtFlatFile::build_index`.
+pub const UNIPROT_INDEX_FILE_MAGIC: &[u8; 8] = b"PSUIDX01";
+
+/// The entries of a UniProtKB flat file, either read into memory or in an index file.
+#[derive(Debug)]
+pub enum UniProtEntries {
+    /// The entries of a flat file, read on every run.
+    InMemory(UniProtFlatFile),
+    /// A memory mapped index file built with `UniProtFlatFile::build_index`. Its headers are the
+    /// entries serialized as JSON.
+    OnDisk(ReferenceIndexFile),
+}
+
+impl UniProtEntries {
+    /// Opens the argument `path`, which is either an index file built with
+    /// `UniProtFlatFile::build_index` or a (possibly compressed) UniProtKB flat file. Index files
+    /// are recognized by their first bytes (see `UNIPROT_INDEX_FILE_MAGIC`). Panics if the file is
+    /// an index of a reference FASTA.
+    ///
+    /// # Arguments
+    ///
+    /// * `path: &str` - The path to the index file or to the UniProtKB flat file
+    pub fn open(path: &str) -> UniProtEntries {
+        if starts_with_magic(path, UNIPROT_INDEX_FILE_MAGIC) {
+            UniProtEntries::OnDisk(ReferenceIndexFile::open_with_magic(
+                path,
+                UNIPROT_INDEX_FILE_MAGIC,
+            ))
+        } else if is_index_file(path) {
+            panic!("\n\nFile {:?} is an index of a reference FASTA, not of a UniProt flat file. Please build the latter with 'prot-scriber index --uniprot-dat'.\n\n", path);
+        } else {
+            UniProtEntries::InMemory(UniProtFlatFile::from_dat(path))
+        }
+    }
+
+    /// Returns the entry of the argument `sacc`, if any (see `UniProtFlatFile::get`).
+    ///
+    /// # Arguments
+    ///
+    /// * `sacc: &str` - The Hit's identifier (`sacc` in Blast terminology)
+    pub fn get(&self, sacc: &str) -> Option<UniProtEntry> {
+        match self {
+            UniProtEntries::InMemory(flat_file) => flat_file.get(sacc).cloned(),
+            UniProtEntries::OnDisk(index) => index.get(sacc).map(|record| {
+                serde_json::from_str(record).unwrap_or_else(|e| {
+                    panic!(
+                        "\n\nUniProt index {:?} is corrupt. Please rebuild it with 'prot-scriber index --uniprot-dat':\n{:?}\n\n",
+                        index.path, e
+                    )
+                })
+            }),
+        }
+    }
+}
+
+/// The structured fields of a UniProtKB flat file entry used to annotate Hits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UniProtEntry {
+    /// The recommended full name (`RecName: Full=`) or, if there is none, the first submitted full
+    /// name (`SubName: Full=`) of the protein.
+    pub name: String,
+    /// The EC numbers of the recommended, or submitted, name (`EC=`).
+    pub ec_numbers: Vec<String>,
+    /// The organism (`OS`), e.g. `Homo sapiens (Human)`.
+    pub organism: Option<String>,
+    /// The NCBI taxonomy identifier of the organism (`OX   NCBI_TaxID=`).
+    pub taxon_id: Option<u64>,
+    /// The name of the encoding gene (`GN   Name=`).
+    pub gene_name: Option<String>,
+    /// The protein existence level (`PE`), from one to five.
+    pub protein_existence: Option<u8>,
+    /// Swiss-Prot for reviewed and TrEMBL for unreviewed entries (`ID`).
+    pub source_db: Option<SourceDatabase>,
+}
+
+/// The entries of a UniProtKB flat file, keyed by their accessions and entry names. All entries
+/// are held in memory, so this is meant for small flat files, e.g. Swiss-Prot. Index huge ones,
+/// e.g. TrEMBL, with `build_index` instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UniProtFlatFile {
+    /// The parsed entries. Entries without protein name are not retained.
+    pub entries: Vec<UniProtEntry>,
+    /// The indices of the `entries`, keyed by their primary and secondary accessions (`AC`) and
+    /// their entry names (`ID`).
+    pub accessions: HashMap<String, usize>,
+}
+
+impl UniProtFlatFile {
+    /// Reads the (possibly compressed) UniProtKB flat file found at argument `path` and indexes
+    /// its entries. Sequences and all fields not in `UniProtEntry` are not retained. Panics if the
+    /// file cannot be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `path: &str` - The path to the UniProtKB flat file
+    pub fn from_dat(path: &str) -> UniProtFlatFile {
+        let reader = open_input_file(path).unwrap_or_else(|e| {
+            panic!(
+                "\n\nCould not read UniProt flat file {:?}:\n{:?}\n\n",
+                path, e
+            )
+        });
+        let mut flat_file = UniProtFlatFile::default();
+        for entry_lines in read_entry_lines(reader) {
+            let entry_lines = entry_lines.unwrap_or_else(|e| {
+                panic!(
+                    "\n\nCould not read UniProt flat file {:?}:\n{:?}\n\n",
+                    path, e
+                )
+            });
+            flat_file.insert(&entry_lines);
+        }
+        flat_file
+    }
+
+    /// Indexes the entries of the (possibly compressed) UniProtKB flat file found at the argument
+    /// `dat_path` and writes the index file to the argument `index_path`. The index has the format
+    /// of a `ReferenceIndexFile`, whose headers are the entries serialized as JSON. Entries are
+    /// streamed into the index, so only their accessions are held in memory. Returns the number
+    /// of indexed entries and accessions.
+    ///
+    /// # Arguments
+    ///
+    /// * `dat_path: &str` - The path to the UniProtKB flat file
+    /// * `index_path: &str` - The path to write the index file to
+    pub fn build_index(dat_path: &str, index_path: &str) -> io::Result<(usize, usize)> {
+        let entries =
+            read_entry_lines(open_input_file(dat_path)?).filter_map(
+                |entry_lines| match entry_lines {
+                    Ok(entry_lines) => parse_entry(&entry_lines).map(|(accessions, entry)| {
+                        let record = serde_json::to_string(&entry)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                        Ok((record, accessions))
+                    }),
+                    Err(e) => Some(Err(e)),
+                },
+            );
+        ReferenceIndexFile::build_from_records(entries, UNIPROT_INDEX_FILE_MAGIC, index_path)
+    }
+
+    /// Parses the argument `entry_lines` (see `parse_entry`) and adds the entry to this flat
+    /// file, if it has a protein name. If an accession is found in more than one entry, the first
+    /// entry is retained.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry_lines: &[String]` - The lines of a single flat file entry
+    pub fn insert(&mut self, entry_lines: &[String]) {
+        if let Some((accessions, entry)) = parse_entry(entry_lines) {
+            let i = self.entries.len();
+            for accession in accessions {
+                self.accessions.entry(accession).or_insert(i);
+            }
+            self.entries.push(entry);
+        }
+    }
+
+    /// Returns the entry of the argument `sacc`, if any. The argument is looked up like in
+    /// `reference_fasta::ReferenceIndex::get`, e.g. `sp|P15538|C11B1_HUMAN` is found by its
+    /// accession `P15538`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sacc: &str` - The Hit's identifier (`sacc` in Blast terminology)
+    pub fn get(&self, sacc: &str) -> Option<&UniProtEntry> {
+        lookup_accessions(sacc)
+            .iter()
+            .find_map(|accession| self.accessions.get(*accession))
+            .map(|i| &self.entries[*i])
+    }
+}
+
+/// Returns an iterator over the entries of the UniProtKB flat file read by the argument `reader`,
+/// each given by its lines. Entries are terminated by `//` lines. Sequence lines are skipped.
+///
+/// # Arguments
+///
+/// * `reader: R` - A reader of the (decompressed) flat file
+fn read_entry_lines<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<Vec<String>>> {
+    let mut lines = reader.lines();
+    let mut exhausted = false;
+    std::iter::from_fn(move || {
+        if exhausted {
+            return None;
+        }
+        let mut entry_lines: Vec<String> = vec![];
+        loop {
+            match lines.next() {
+                Some(Ok(line)) if line.starts_with("//") => return Some(Ok(entry_lines)),
+                // Sequence lines start with blanks and are skipped.
+                Some(Ok(line)) if line.starts_with("  ") => {}
+                Some(Ok(line)) => entry_lines.push(line),
+                Some(Err(e)) => {
+                    exhausted = true;
+                    return Some(Err(e));
+                }
+                None => {
+                    exhausted = true;
+                    return Some(Ok(entry_lines));
+                }
+            }
+        }
+    })
+}
+
+/// Parses the argument `entry_lines` of a UniProtKB flat file entry into its accessions, i.e. its
+/// entry name (`ID`) and its primary and secondary accessions (`AC`), and a `UniProtEntry`.
+/// Names and EC numbers of included domains or contained chains (`Includes:` and `Contains:`)
+/// are ignored. Evidence tags, e.g. `{ECO:0000269|PubMed:1234}`, are removed. Returns `None`, if
+/// the entry has no accession or no protein name.
+///
+/// # Arguments
+///
+/// * `entry_lines: &[String]` - The lines of a single flat file entry
+pub fn parse_entry(entry_lines: &[String]) -> Option<(Vec<String>, UniProtEntry)> {
+    let mut accessions: Vec<String> = vec![];
+    let mut entry = UniProtEntry::default();
+    let mut rec_name: Option<String> = None;
+    let mut rec_name_ec_numbers: Vec<String> = vec![];
+    let mut sub_name: Option<String> = None;
+    let mut sub_name_ec_numbers: Vec<String> = vec![];
+    // The top level `DE` category the current line belongs to, e.g. `RecName` or `Contains`:
+    let mut de_category = "";
+    let mut organism: Vec<&str> = vec![];
+    for line in entry_lines {
+        let (code, value) = (line.get(..2).unwrap_or(""), line.get(5..).unwrap_or(""));
+        match code {
+            "ID" => {
+                if let Some(entry_name) = value.split_whitespace().next() {
+                    accessions.push(entry_name.to_string());
+                }
+                entry.source_db = if value.contains("Unreviewed;") {
+                    Some(SourceDatabase::TrEMBL)
+                } else if value.contains("Reviewed;") {
+                    Some(SourceDatabase::SwissProt)
+                } else {
+                    None
+                };
+            }
+            "AC" => accessions.extend(
+                value
+                    .split(';')
+                    .map(|accession| accession.trim())
+                    .filter(|accession| !accession.is_empty())
+                    .map(|accession| accession.to_string()),
+            ),
+            "DE" => {
+                // Continuation lines are indented, i.e. belong to the last top level category:
+                let field = if value.starts_with(' ') {
+                    value.trim()
+                } else {
+                    let (category, field) = value.split_once(':').unwrap_or((value, ""));
+                    // Only the first submitted name is retained:
+                    de_category = match category.trim() {
+                        "SubName" if sub_name.is_some() => "",
+                        category => category,
+                    };
+                    field.trim()
+                };
+                let (key, field_value) = field.split_once('=').unwrap_or(("", ""));
+                let field_value = strip_evidence(field_value);
+                match (de_category, key) {
+                    ("RecName", "Full") if rec_name.is_none() => {
+                        rec_name = Some(field_value.to_string())
+                    }
+                    ("SubName", "Full") if sub_name.is_none() => {
+                        sub_name = Some(field_value.to_string())
+                    }
+                    ("RecName", "EC") => rec_name_ec_numbers.push(field_value.to_string()),
+                    ("SubName", "EC") => sub_name_ec_numbers.push(field_value.to_string()),
+                    _ => {}
+                }
+            }
+            "GN" if entry.gene_name.is_none() => {
+                entry.gene_name = value
+                    .split(';')
+                    .find_map(|field| field.trim().strip_prefix("Name="))
+                    .map(|name| strip_evidence(name).to_string())
+            }
+            "OS" => organism.push(value.trim()),
+            "OX" => {
+                entry.taxon_id = value
+                    .trim()
+                    .strip_prefix("NCBI_TaxID=")
+                    .and_then(|taxon_id| strip_evidence(taxon_id).parse().ok())
+            }
+            "PE" => {
+                entry.protein_existence = value
+                    .split(':')
+                    .next()
+                    .and_then(|level| level.trim().parse().ok())
+            }
+            _ => {}
+        }
+    }
+    let organism = organism.join(" ");
+    let organism = organism.trim_end_matches('.').trim();
+    if !organism.is_empty() {
+        entry.organism = Some(organism.to_string());
+    }
+    let (name, ec_numbers) = match (rec_name, sub_name) {
+        (Some(name), _) => (name, rec_name_ec_numbers),
+        (None, Some(name)) => (name, sub_name_ec_numbers),
+        (None, None) => return None,
+    };
+    if accessions.is_empty() || name.is_empty() {
+        return None;
+    }
+    entry.name = name;
+    entry.ec_numbers = ec_numbers;
+    Some((accessions, entry))
+}
+
+/// Returns the argument `value` of a flat file field without its trailing `;` and evidence tags,
+/// e.g. `Cytochrome P450 11B1` for `Cytochrome P450 11B1 {ECO:0000269|PubMed:1234};`.
+fn strip_evidence(value: &str) -> &str {
+    let value = value.trim().trim_end_matches(';');
+    value.split(" {").next().unwrap_or(value).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn reads_uniprot_flat_file_entries() {
+        let dat = Path::new("misc").join("uniprot_entries.dat");
+        let flat_file = UniProtFlatFile::from_dat(dat.to_str().unwrap());
+        assert_eq!(2, flat_file.entries.len());
+        let cyp = UniProtEntry {
+            name: "Cytochrome P450 11B1, mitochondrial".to_string(),
+            ec_numbers: vec!["1.14.15.4".to_string(), "1.14.15.5".to_string()],
+            organism: Some("Homo sapiens (Human)".to_string()),
+            taxon_id: Some(9606),
+            gene_name: Some("CYP11B1".to_string()),
+            protein_existence: Some(1),
+            source_db: Some(SourceDatabase::SwissProt),
+        };
+        for sacc in ["sp|P15538|C11B1_HUMAN", "P15538", "Q16726.2", "C11B1_HUMAN"] {
+            assert_eq!(Some(&cyp), flat_file.get(sacc));
+        }
+        let pigbos = flat_file.get("tr|A0A0B4J2F0|A0A0B4J2F0_HUMAN").unwrap();
+        assert_eq!("Protein PIGBOS1", pigbos.name);
+        assert!(pigbos.ec_numbers.is_empty());
+        assert_eq!(Some(SourceDatabase::TrEMBL), pigbos.source_db);
+        assert_eq!(Some(4), pigbos.protein_existence);
+        // Entries without protein name are not retained:
+        assert_eq!(None, flat_file.get("Q00000"));
+    }
+
+    #[test]
+    fn looks_up_entries_in_uniprot_index_file() {
+        let dat = Path::new("misc").join("uniprot_entries.dat");
+        let index_path = std::env::temp_dir().join(format!(
+            "prot-scriber_{}_uniprot_index_file_test.psui",
+            std::process::id()
+        ));
+        let (n_entries, n_accessions) =
+            UniProtFlatFile::build_index(dat.to_str().unwrap(), index_path.to_str().unwrap())
+                .unwrap();
+        assert_eq!(2, n_entries);
+        assert_eq!(5, n_accessions);
+        assert!(!is_index_file(index_path.to_str().unwrap()));
+        let index = UniProtEntries::open(index_path.to_str().unwrap());
+        let in_memory = UniProtEntries::open(dat.to_str().unwrap());
+        assert!(matches!(index, UniProtEntries::OnDisk(_)));
+        assert!(matches!(in_memory, UniProtEntries::InMemory(_)));
+        for sacc in [
+            "sp|P15538|C11B1_HUMAN",
+            "Q16726.2",
+            "C11B1_HUMAN",
+            "A0A0B4J2F0",
+            "Q00000",
+        ] {
+            assert_eq!(in_memory.get(sacc), index.get(sacc));
+        }
+        assert_eq!(
+            Some("Protein PIGBOS1".to_string()),
+            index.get("A0A0B4J2F0").map(|entry| entry.name)
+        );
+        std::fs::remove_file(index_path).unwrap();
+    }
+}
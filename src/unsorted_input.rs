@@ -0,0 +1,273 @@
+//! Sequence similarity search result tables not sorted by query identifiers (`qacc` in Blast
+//! terminology) are read with the help of this module. The parsers send a query every time its
+//! contiguous block of rows ends, so in unsorted tables the same query is sent several times.
+//! Here these partial queries are grouped, either in memory or by partitioning them into
+//! temporary files, before they are handed on to the annotation process.
+use super::default::UNSORTED_INPUT_PARTITIONS;
+use super::hit_thresholds::HitThresholds;
+use super::query::Query;
+use super::seq_sim_table_reader::send_query;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+/// Counts the tables grouped on disk, so each of them gets its own temporary directory:
+static N_ON_DISK_GROUPINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// The ways to read sequence similarity search result tables with respect to their order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnsortedInputMode {
+    /// Tables are sorted by query identifiers and queries are streamed on to the annotation
+    /// process as soon as they are parsed. This is the fastest mode.
+    Sorted,
+    /// Tables may be unsorted, the Hits of each query are grouped in memory.
+    InMemory,
+    /// Tables may be unsorted, queries are partitioned by their identifiers into temporary files,
+    /// which are then grouped in memory one at a time.
+    OnDisk,
+}
+
+impl UnsortedInputMode {
+    /// Parses the argument `mode_arg` into an `UnsortedInputMode` (case insensitive). Returns
+    /// `None` if the argument does not name a known mode. Note that `"default"` and `"none"` are
+    /// parsed into `UnsortedInputMode::Sorted`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode_arg: &str` - The name of the mode, e.g. as passed on the command line.
+    pub fn from_arg(mode_arg: &str) -> Option<UnsortedInputMode> {
+        match mode_arg.trim().to_lowercase().as_str() {
+            "default" | "none" | "sorted" => Some(UnsortedInputMode::Sorted),
+            "in-memory" => Some(UnsortedInputMode::InMemory),
+            "on-disk" => Some(UnsortedInputMode::OnDisk),
+            _ => None,
+        }
+    }
+}
+
+/// Runs the argument `parse` function, which parses a single sequence similarity search result
+/// table, and makes sure every query is sent exactly once using the argument `transmitter`. In
+/// mode `UnsortedInputMode::Sorted` the parsed queries are sent on unchanged. In the other modes
+/// the partial queries sent by `parse` are grouped by their identifiers and the per query
+/// `hit_thresholds` (see `HitThresholds::select`) are applied once more to the grouped Hits, whose
+/// evidence has already been weighted, and whose taxa have already been filtered, by `parse`, if
+/// requested. If a Hit identifier (`sacc`) occurs more than once for a query, its first occurrence
+/// is retained. Returns what `parse` returns.
+///
+/// # Arguments
+///
+/// * `mode: &UnsortedInputMode` - How to group the queries
+/// * `hit_thresholds: &HitThresholds` - The thresholds to apply to the grouped queries
+/// * `transmitter: Sender<(String, Query)>` - Used to send the grouped queries to any receiver
+/// * `parse` - Parses the table and sends its queries to the transmitter it is called with
+pub fn group_queries<F, R>(
+    mode: &UnsortedInputMode,
+    hit_thresholds: &HitThresholds,
+    transmitter: Sender<(String, Query)>,
+    parse: F,
+) -> R
+where
+    F: FnOnce(Sender<(String, Query)>) -> R + Send,
+    R: Send,
+{
+    match mode {
+        UnsortedInputMode::Sorted => parse(transmitter),
+        UnsortedInputMode::InMemory => {
+            let (tx, rx) = channel();
+            let mut queries: HashMap<String, Query> = HashMap::new();
+            let parsed = thread::scope(|s| {
+                let parser = s.spawn(move || parse(tx));
+                for (qacc, query) in rx {
+                    merge_query(&mut queries, qacc, query);
+                }
+                join(parser)
+            });
+            for (qacc, query) in queries {
+                send_query(&transmitter, qacc, query, hit_thresholds, None, None);
+            }
+            parsed
+        }
+        UnsortedInputMode::OnDisk => {
+            let tmp_dir = std::env::temp_dir().join(format!(
+                "prot-scriber-{}-{}",
+                process::id(),
+                N_ON_DISK_GROUPINGS.fetch_add(1, Ordering::SeqCst)
+            ));
+            fs::create_dir_all(&tmp_dir).unwrap_or_else(|e| {
+                panic!(
+                    "\n\nCould not create temporary directory {:?}:\n{:?}\n\n",
+                    tmp_dir, e
+                )
+            });
+            let partitions: Vec<PathBuf> = (0..*UNSORTED_INPUT_PARTITIONS)
+                .map(|k| tmp_dir.join(format!("partition_{}.jsonl", k)))
+                .collect();
+            let (tx, rx) = channel();
+            let parsed = thread::scope(|s| {
+                let parser = s.spawn(move || parse(tx));
+                write_partitions(&partitions, rx);
+                join(parser)
+            });
+            for partition in &partitions {
+                let mut queries: HashMap<String, Query> = HashMap::new();
+                for (qacc, query) in read_partition(partition) {
+                    merge_query(&mut queries, qacc, query);
+                }
+                for (qacc, query) in queries {
+                    send_query(&transmitter, qacc, query, hit_thresholds, None, None);
+                }
+            }
+            // Temporary files are no longer needed:
+            fs::remove_dir_all(&tmp_dir).unwrap_or_else(|e| {
+                eprintln!(
+                    "\nCould not remove temporary directory {:?}:\n{:?}\nContinuing anyway!\n",
+                    tmp_dir, e
+                )
+            });
+            parsed
+        }
+    }
+}
+
+/// Waits for the argument `parser` thread to finish and returns its result. If the thread
+/// panicked, e.g. because of a malformed row, the panic is passed on unchanged.
+fn join<R>(parser: thread::ScopedJoinHandle<'_, R>) -> R {
+    parser
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Merges the argument `query` into the query with the same identifier (`qacc`) in the argument
+/// `queries`, or inserts it, if there is none. Hits already present are retained.
+///
+/// # Arguments
+///
+/// * `queries: &mut HashMap<String, Query>` - The queries grouped so far, keyed by identifier
+/// * `qacc: String` - The identifier of the argument `query`
+/// * `query: Query` - The (partial) query to merge
+pub fn merge_query(queries: &mut HashMap<String, Query>, qacc: String, query: Query) {
+    let grouped = queries.entry(qacc).or_default();
+    for (sacc, hit) in query.hits {
+        grouped.hits.entry(sacc).or_insert(hit);
+    }
+}
+
+/// Writes each query received by the argument `receiver` as a line of JSON into one of the
+/// argument `partitions` files. The partition is chosen by the hash of the query identifier, so
+/// all partial queries with the same identifier end up in the same file.
+fn write_partitions<I>(partitions: &[PathBuf], receiver: I)
+where
+    I: IntoIterator<Item = (String, Query)>,
+{
+    let mut writers: Vec<BufWriter<File>> = partitions
+        .iter()
+        .map(|path| {
+            BufWriter::new(File::create(path).unwrap_or_else(|e| {
+                panic!(
+                    "\n\nCould not create temporary file {:?}:\n{:?}\n\n",
+                    path, e
+                )
+            }))
+        })
+        .collect();
+    for (qacc, query) in receiver {
+        let mut hasher = DefaultHasher::new();
+        qacc.hash(&mut hasher);
+        let k = (hasher.finish() % writers.len() as u64) as usize;
+        serde_json::to_writer(&mut writers[k], &(qacc, query))
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(writers[k]))
+            .unwrap_or_else(|e| {
+                panic!(
+                    "\n\nCould not write temporary file {:?}:\n{:?}\n\n",
+                    partitions[k], e
+                )
+            });
+    }
+    for (writer, path) in writers.iter_mut().zip(partitions) {
+        writer.flush().unwrap_or_else(|e| {
+            panic!(
+                "\n\nCould not write temporary file {:?}:\n{:?}\n\n",
+                path, e
+            )
+        });
+    }
+}
+
+/// Reads back the queries written into the temporary `partition` file by `write_partitions`.
+fn read_partition(partition: &Path) -> impl Iterator<Item = (String, Query)> + '_ {
+    let file = File::open(partition).unwrap_or_else(|e| {
+        panic!(
+            "\n\nCould not read temporary file {:?}:\n{:?}\n\n",
+            partition, e
+        )
+    });
+    BufReader::new(file).lines().map(move |line| {
+        line.ok()
+            .and_then(|l| serde_json::from_str(&l).ok())
+            .unwrap_or_else(|| {
+                panic!(
+                    "\n\nCould not parse temporary file {:?}. Has it been modified?\n\n",
+                    partition
+                )
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hit;
+
+    /// Sends the queries of an unsorted table, i.e. `Query1` twice, and returns the grouped
+    /// queries sorted by identifier.
+    fn group_unsorted_queries(mode: UnsortedInputMode) -> Vec<(String, Query)> {
+        let hit_thresholds = HitThresholds {
+            top_n_hits: Some(2),
+            ..Default::default()
+        };
+        let blocks = vec![
+            ("Query1", vec![("A", 100.0), ("B", 50.0)]),
+            ("Query2", vec![("C", 10.0)]),
+            ("Query1", vec![("A", 20.0), ("D", 80.0)]),
+        ];
+        let (tx, rx) = channel();
+        group_queries(&mode, &hit_thresholds, tx, |tx_parse| {
+            for (qacc, hits) in blocks {
+                let mut query = Query::new();
+                for (sacc, bitscore) in hits {
+                    let mut hit = Hit::default();
+                    hit.scores.bitscore = Some(bitscore);
+                    query.hits.insert(sacc.to_string(), hit);
+                }
+                tx_parse.send((qacc.to_string(), query)).unwrap();
+            }
+        });
+        let mut queries: Vec<(String, Query)> = rx.iter().collect();
+        queries.sort_by(|a, b| a.0.cmp(&b.0));
+        queries
+    }
+
+    #[test]
+    fn groups_queries_of_unsorted_tables() {
+        assert_eq!(3, group_unsorted_queries(UnsortedInputMode::Sorted).len());
+        for mode in [UnsortedInputMode::InMemory, UnsortedInputMode::OnDisk] {
+            let queries = group_unsorted_queries(mode);
+            assert_eq!(2, queries.len());
+            assert_eq!("Query1", queries[0].0);
+            let mut retained: Vec<&String> = queries[0].1.hits.keys().collect();
+            retained.sort();
+            assert_eq!(vec!["A", "D"], retained);
+            // The first occurrence of a Hit is retained:
+            assert_eq!(Some(100.0), queries[0].1.hits["A"].scores.bitscore);
+            assert_eq!(1, queries[1].1.hits.len());
+        }
+    }
+}